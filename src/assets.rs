@@ -0,0 +1,118 @@
+//! Files the game loads at runtime.
+//!
+//! Every asset is loaded through an [`Assets`] registry for its type, which
+//! hands out reference counted [`Handle`]s. Loading the same path again
+//! while a handle to it is alive shares the asset instead of reading and
+//! decoding the file twice, and once the last handle is dropped the asset
+//! is freed.
+
+use rusttype::Font;
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Weak};
+
+/// A type that can be decoded from the contents of a file
+pub trait Asset: Sized {
+    /// What the asset is called in error messages
+    const KIND: &'static str;
+
+    fn decode(data: Vec<u8>) -> Result<Self, String>;
+}
+
+impl Asset for Font<'static> {
+    const KIND: &'static str = "font";
+
+    fn decode(data: Vec<u8>) -> Result<Self, String> {
+        Font::try_from_vec(data).ok_or_else(|| String::from("not a TrueType or OpenType font"))
+    }
+}
+
+/// Shared access to a loaded asset
+pub struct Handle<T>(Arc<T>);
+
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        Handle(Arc::clone(&self.0))
+    }
+}
+
+impl<T> Handle<T> {
+    /// Whether two handles share the same loaded asset
+    pub fn ptr_eq(a: &Handle<T>, b: &Handle<T>) -> bool {
+        Arc::ptr_eq(&a.0, &b.0)
+    }
+}
+
+impl<T> Deref for Handle<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+#[derive(Debug)]
+pub struct AssetError {
+    kind: &'static str,
+    path: PathBuf,
+    reason: String,
+}
+
+impl fmt::Display for AssetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "could not load {} {:?}: {}",
+            self.kind, self.path, self.reason
+        )
+    }
+}
+
+pub struct Assets<T> {
+    loaded: HashMap<PathBuf, Weak<T>>,
+}
+
+impl<T> Default for Assets<T> {
+    fn default() -> Self {
+        Assets {
+            loaded: HashMap::new(),
+        }
+    }
+}
+
+impl<T: Asset> Assets<T> {
+    /// Returns the asset at `path`, reading it from disk only if no handle
+    /// to it is still alive
+    pub fn load(&mut self, path: &Path) -> Result<Handle<T>, AssetError> {
+        if let Some(asset) = self.loaded.get(path).and_then(Weak::upgrade) {
+            return Ok(Handle(asset));
+        }
+
+        let error = |reason: String| AssetError {
+            kind: T::KIND,
+            path: path.to_path_buf(),
+            reason,
+        };
+        let data = std::fs::read(path).map_err(|err| match err.kind() {
+            std::io::ErrorKind::NotFound => {
+                let cwd = std::env::current_dir().unwrap_or_default();
+                error(format!("the file does not exist (looked in {cwd:?})"))
+            }
+            _ => error(err.to_string()),
+        })?;
+        let asset = Arc::new(T::decode(data).map_err(error)?);
+        self.loaded
+            .insert(path.to_path_buf(), Arc::downgrade(&asset));
+        Ok(Handle(asset))
+    }
+
+    /// How many assets loaded through this registry are still in use
+    pub fn live(&self) -> usize {
+        self.loaded
+            .values()
+            .filter(|asset| asset.strong_count() > 0)
+            .count()
+    }
+}
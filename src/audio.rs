@@ -0,0 +1,759 @@
+//! Sound assets.
+//!
+//! Code refers to sounds by symbolic name through [`Sfx`]; which file plays
+//! for each name is decided by the manifest in `assets/audio/`. A sound pack
+//! in `assets/audio/packs/<name>/` can replace any subset of those files
+//! with a manifest of its own, and debug builds reload the manifests and
+//! sound files whenever they change on disk. A manifest can also name a
+//! `music` track, which loops under the sounds, followed by any number of
+//! `music_layer` stems. Stems play in step with the track and fade in one
+//! after another as the game's music intensity rises.
+//!
+//! Levels can bring their own [`Theme`]: a sound pack layered over the
+//! player's, and a music track. Anything a theme names that can't be loaded
+//! is reported and left as it was without the theme.
+//!
+//! Playing sounds goes through the [`Mixer`], which mixes every playing
+//! sound into one stream at the volumes set in the settings menu. Sounds
+//! heard over and over, like bricks being hit, can be played at a slightly
+//! random pitch each time so they don't get monotonous. The mixer
+//! runs on its own thread and gameplay only ever sends it [`Command`]s
+//! through a channel that never blocks the sender, so a slow or missing
+//! audio device can't hold up a game tick.
+//!
+//! The mixed stream is played by piping raw samples into the system's
+//! command line player, `aplay` or `paplay`. With neither installed the
+//! game runs silently.
+
+use crate::assets::{Asset, Assets, Handle};
+use crate::paths;
+use crate::rng::Rng;
+use crate::settings::Volumes;
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, Command as Process, Stdio};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+const AUDIO_DIR: &str = "assets/audio";
+const MANIFEST: &str = "manifest.txt";
+// The manifest name of the music track
+const MUSIC: &str = "music";
+const MUSIC_LAYER: &str = "music_layer";
+// How long a layer takes to fade all the way in or out
+const LAYER_FADE_SECONDS: f32 = 1.5;
+// Debug builds look for changed files this often, in mixed buffers
+const RELOAD_CHECK_FRAMES: u32 = 30;
+pub const OUTPUT_SAMPLE_RATE: u32 = 44100;
+// Players tried in turn for sound output, each set to read raw 16 bit mono
+// samples from its input
+const PLAYERS: &[(&str, &[&str])] = &[
+    (
+        "aplay",
+        &["-q", "-t", "raw", "-f", "S16_LE", "-r", "44100", "-c", "1"],
+    ),
+    (
+        "paplay",
+        &["--raw", "--format=s16le", "--rate=44100", "--channels=1"],
+    ),
+];
+// The mixer thread hands the backend this many samples at a time
+const BUFFER_SAMPLES: usize = OUTPUT_SAMPLE_RATE as usize / 60;
+// Sounds beyond this many at once cut off the oldest
+const MAX_VOICES: usize = 16;
+// Sounds played with a varied pitch play up to this fraction higher or
+// lower than usual
+const PITCH_VARIATION: f32 = 0.06;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Sfx {
+    PaddleHit,
+    BrickBreak,
+    BallLost,
+    LaserFire,
+    PowerUp,
+    MenuMove,
+    MenuConfirm,
+    // Bricks of each material being hit
+    GlassHit,
+    StoneHit,
+    MetalHit,
+}
+
+impl Sfx {
+    pub const ALL: [Sfx; 10] = [
+        Sfx::PaddleHit,
+        Sfx::BrickBreak,
+        Sfx::BallLost,
+        Sfx::LaserFire,
+        Sfx::PowerUp,
+        Sfx::MenuMove,
+        Sfx::MenuConfirm,
+        Sfx::GlassHit,
+        Sfx::StoneHit,
+        Sfx::MetalHit,
+    ];
+
+    /// The name a manifest uses for this sound
+    pub fn name(self) -> &'static str {
+        match self {
+            Sfx::PaddleHit => "paddle_hit",
+            Sfx::BrickBreak => "brick_break",
+            Sfx::BallLost => "ball_lost",
+            Sfx::LaserFire => "laser_fire",
+            Sfx::PowerUp => "power_up",
+            Sfx::MenuMove => "menu_move",
+            Sfx::MenuConfirm => "menu_confirm",
+            Sfx::GlassHit => "glass_hit",
+            Sfx::StoneHit => "stone_hit",
+            Sfx::MetalHit => "metal_hit",
+        }
+    }
+}
+
+/// Decoded mono samples in `-1.0..=1.0`
+pub struct Clip {
+    pub samples: Vec<f32>,
+    pub sample_rate: u32,
+}
+
+impl Asset for Clip {
+    const KIND: &'static str = "sound";
+
+    fn decode(data: Vec<u8>) -> Result<Self, String> {
+        decode_wav(&data)
+    }
+}
+
+impl Clip {
+    pub fn seconds(&self) -> f32 {
+        self.samples.len() as f32 / self.sample_rate as f32
+    }
+}
+
+/// The sounds and music a level asks for, on top of the defaults
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Theme {
+    // A pack under `assets/audio/packs/`
+    pub sound_pack: Option<&'static str>,
+    // Relative to `assets/audio/`
+    pub music: Option<&'static str>,
+    // Stems for `music`, in the order they come in
+    pub music_layers: &'static [&'static str],
+}
+
+impl Theme {
+    /// The player's sounds and the usual music
+    pub const DEFAULT: Theme = Theme {
+        sound_pack: None,
+        music: None,
+        music_layers: &[],
+    };
+}
+
+/// Every sound named by the manifests, decoded and ready to play
+#[derive(Default)]
+pub struct SoundBank {
+    pack: Option<String>,
+    theme: Theme,
+    clips: HashMap<Sfx, Handle<Clip>>,
+    music: Option<Handle<Clip>>,
+    layers: Vec<Handle<Clip>>,
+    // Sounds assigned to more than one name are only loaded once
+    assets: Assets<Clip>,
+    // Manifests and sound files with the modification time they had when
+    // they were loaded
+    watched: Vec<(PathBuf, Option<SystemTime>)>,
+    frames_until_check: u32,
+}
+
+impl SoundBank {
+    /// Loads the base manifest, then lets `pack` override it
+    pub fn load(pack: Option<&str>) -> SoundBank {
+        let mut bank = SoundBank {
+            pack: pack.map(String::from),
+            ..SoundBank::default()
+        };
+        bank.reload();
+        bank
+    }
+
+    /// Switches to a level's theme, reloading only if it changed. Returns
+    /// whether that changed the music track.
+    pub fn set_theme(&mut self, theme: Theme) -> bool {
+        if theme == self.theme {
+            return false;
+        }
+        let music = self.music.clone();
+        self.theme = theme;
+        self.reload();
+        match (&music, &self.music) {
+            (Some(before), Some(after)) => !Handle::ptr_eq(before, after),
+            (None, None) => false,
+            _ => true,
+        }
+    }
+
+    fn reload(&mut self) {
+        // Holding on to the old sounds until the new ones are loaded saves
+        // reading the files they share again
+        let _previous = (
+            std::mem::take(&mut self.clips),
+            self.music.take(),
+            std::mem::take(&mut self.layers),
+        );
+        self.watched.clear();
+        let base = paths::resource(AUDIO_DIR);
+        self.load_manifest(&base);
+        if let Some(pack) = self.pack.clone() {
+            self.load_manifest(&base.join("packs").join(pack));
+        }
+        if let Some(pack) = self.theme.sound_pack {
+            self.load_manifest(&base.join("packs").join(pack));
+        }
+        if let Some(music) = self.theme.music {
+            let path = base.join(music);
+            self.watch(&path);
+            match self.assets.load(&path) {
+                Ok(clip) => {
+                    self.music = Some(clip);
+                    self.layers.clear();
+                    for layer in self.theme.music_layers {
+                        let path = base.join(layer);
+                        self.watch(&path);
+                        match self.assets.load(&path) {
+                            Ok(clip) => self.layers.push(clip),
+                            Err(err) => eprintln!("ERROR! Skipping a music layer: {err}"),
+                        }
+                    }
+                }
+                Err(err) => eprintln!("ERROR! Keeping the usual music: {err}"),
+            }
+        }
+        for sfx in Sfx::ALL {
+            if !self.clips.contains_key(&sfx) {
+                eprintln!("ERROR! No sound is assigned to `{}`", sfx.name());
+            }
+        }
+    }
+
+    fn load_manifest(&mut self, dir: &Path) {
+        let path = dir.join(MANIFEST);
+        self.watch(&path);
+        let text = match std::fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(err) => {
+                eprintln!("ERROR! Could not read sound manifest {path:?}: {err}");
+                return;
+            }
+        };
+
+        for (line_number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Err(err) = self.load_entry(dir, line) {
+                eprintln!(
+                    "ERROR! Ignoring line {} of {path:?}: {err}",
+                    line_number + 1
+                );
+            }
+        }
+    }
+
+    fn load_entry(&mut self, dir: &Path, line: &str) -> Result<(), String> {
+        let (name, file) = line
+            .split_once('=')
+            .ok_or_else(|| format!("expected `name = file`, found `{line}`"))?;
+        let name = name.trim();
+        if name == MUSIC || name == MUSIC_LAYER {
+            let path = dir.join(file.trim());
+            self.watch(&path);
+            let clip = self.assets.load(&path).map_err(|err| err.to_string())?;
+            // A new track drops the stems of the one it replaces
+            if name == MUSIC {
+                self.music = Some(clip);
+                self.layers.clear();
+            } else {
+                self.layers.push(clip);
+            }
+            return Ok(());
+        }
+        let sfx = Sfx::ALL
+            .into_iter()
+            .find(|sfx| sfx.name() == name)
+            .ok_or_else(|| format!("unknown sound `{name}`"))?;
+        let path = dir.join(file.trim());
+        self.watch(&path);
+        let clip = self.assets.load(&path).map_err(|err| err.to_string())?;
+        self.clips.insert(sfx, clip);
+        Ok(())
+    }
+
+    fn watch(&mut self, path: &Path) {
+        self.watched.push((path.to_path_buf(), modified(path)));
+    }
+
+    /// Reloads everything if any watched file has changed. Only debug
+    /// builds watch for changes.
+    pub fn reload_if_changed(&mut self) {
+        if !cfg!(debug_assertions) {
+            return;
+        }
+        if self.frames_until_check > 0 {
+            self.frames_until_check -= 1;
+            return;
+        }
+        self.frames_until_check = RELOAD_CHECK_FRAMES;
+        let changed = self
+            .watched
+            .iter()
+            .any(|(path, loaded)| modified(path) != *loaded);
+        if changed {
+            self.reload();
+        }
+    }
+
+    pub fn get(&self, sfx: Sfx) -> Option<&Clip> {
+        self.clips.get(&sfx).map(|clip| &**clip)
+    }
+
+    pub fn music(&self) -> Option<&Clip> {
+        self.music.as_deref()
+    }
+
+    pub fn music_layers(&self) -> impl Iterator<Item = &Clip> {
+        self.layers.iter().map(|layer| &**layer)
+    }
+
+    /// A one line summary for the debug overlay
+    pub fn describe(&self) -> String {
+        let seconds = self.clips.values().map(|clip| clip.seconds()).sum::<f32>();
+        format!(
+            "{}{} {}/{} sounds {} files {seconds:.1}s",
+            self.pack.as_deref().unwrap_or("default"),
+            self.theme
+                .sound_pack
+                .map_or_else(String::new, |pack| format!("+{pack}")),
+            self.clips.len(),
+            Sfx::ALL.len(),
+            self.assets.live()
+        )
+    }
+}
+
+/// A sound that is playing, `position` output samples in
+struct Voice {
+    sfx: Sfx,
+    position: usize,
+    // Plays this many times faster than recorded, and that much higher
+    pitch: f32,
+}
+
+/// Mixes every playing sound into a single output stream
+pub struct Mixer {
+    pub volumes: Volumes,
+    // Paused mixers put out silence and hold every sound where it is
+    pub paused: bool,
+    voices: Vec<Voice>,
+    // Output samples into the music track, which loops
+    music_position: usize,
+    // From 0.0 for just the track to 1.0 for every layer
+    music_intensity: f32,
+    // How far each layer has faded in
+    layer_gains: Vec<f32>,
+    // Loudest sample of the last mix, for the debug overlay
+    peak: f32,
+    // Picks pitches for varied sounds. Kept apart from the game's so that
+    // sounds can't change how a seeded run plays out.
+    rng: Rng,
+}
+
+impl Mixer {
+    pub fn new(volumes: Volumes) -> Self {
+        Mixer {
+            volumes,
+            paused: false,
+            voices: Vec::new(),
+            music_position: 0,
+            music_intensity: 0.0,
+            layer_gains: Vec::new(),
+            peak: 0.0,
+            rng: Rng::from_time(),
+        }
+    }
+
+    pub fn play(&mut self, sfx: Sfx) {
+        self.play_at_pitch(sfx, 1.0);
+    }
+
+    /// Plays `sfx` a little higher or lower than usual
+    pub fn play_varied(&mut self, sfx: Sfx) {
+        let pitch = self
+            .rng
+            .range_f32(1.0 - PITCH_VARIATION, 1.0 + PITCH_VARIATION);
+        self.play_at_pitch(sfx, pitch);
+    }
+
+    fn play_at_pitch(&mut self, sfx: Sfx, pitch: f32) {
+        if self.voices.len() == MAX_VOICES {
+            self.voices.remove(0);
+        }
+        self.voices.push(Voice {
+            sfx,
+            position: 0,
+            pitch,
+        });
+    }
+
+    /// Starts the music from the top, for when the track changes
+    pub fn restart_music(&mut self) {
+        self.music_position = 0;
+    }
+
+    pub fn set_music_intensity(&mut self, intensity: f32) {
+        self.music_intensity = intensity.clamp(0.0, 1.0);
+    }
+
+    /// Fills `output` with the next samples of every playing sound, at
+    /// [`OUTPUT_SAMPLE_RATE`], and drops the sounds that have finished
+    pub fn mix(&mut self, bank: &SoundBank, output: &mut [f32]) {
+        output.fill(0.0);
+        if self.paused {
+            self.peak = 0.0;
+            return;
+        }
+        let gain = self.volumes.master * self.volumes.sfx;
+        self.voices.retain_mut(|voice| {
+            let Some(clip) = bank.get(voice.sfx) else {
+                return false;
+            };
+            for (offset, sample) in output.iter_mut().enumerate() {
+                // Nearest neighbour resampling is plenty for short effects
+                let source =
+                    (voice.position + offset) as f64 * clip.sample_rate as f64 * voice.pitch as f64
+                        / OUTPUT_SAMPLE_RATE as f64;
+                match clip.samples.get(source as usize) {
+                    Some(source_sample) => *sample += source_sample * gain,
+                    None => return false,
+                }
+            }
+            voice.position += output.len();
+            true
+        });
+        if let Some(music) = bank.music() {
+            let gain = self.volumes.master * self.volumes.music;
+            mix_looped(music, self.music_position, gain, output);
+            self.mix_layers(bank, gain, output);
+            self.music_position += output.len();
+        }
+        for sample in output.iter_mut() {
+            *sample = sample.clamp(-1.0, 1.0);
+        }
+        self.peak = output
+            .iter()
+            .fold(0.0, |peak, sample| sample.abs().max(peak));
+    }
+
+    /// Layers come in one after another as the intensity rises, each
+    /// fading towards where the intensity puts it rather than jumping
+    fn mix_layers(&mut self, bank: &SoundBank, gain: f32, output: &mut [f32]) {
+        let layer_count = bank.music_layers().count();
+        self.layer_gains.resize(layer_count, 0.0);
+        let fade_step = output.len() as f32 / (LAYER_FADE_SECONDS * OUTPUT_SAMPLE_RATE as f32);
+        let layers = bank.music_layers().zip(self.layer_gains.iter_mut());
+        for (index, (layer, layer_gain)) in layers.enumerate() {
+            let target = (self.music_intensity * layer_count as f32 - index as f32).clamp(0.0, 1.0);
+            *layer_gain += (target - *layer_gain).clamp(-fade_step, fade_step);
+            if *layer_gain > 0.0 {
+                mix_looped(layer, self.music_position, gain * *layer_gain, output);
+            }
+        }
+    }
+
+    /// A one line summary for the debug overlay
+    pub fn describe(&self) -> String {
+        format!(
+            "{} playing peak {:.2} music x{:.2}",
+            self.voices.len(),
+            self.peak,
+            self.music_intensity
+        )
+    }
+}
+
+/// Adds `clip` into `output`, starting `position` output samples into it
+/// and wrapping around at its end
+fn mix_looped(clip: &Clip, position: usize, gain: f32, output: &mut [f32]) {
+    if clip.samples.is_empty() {
+        return;
+    }
+    for (offset, sample) in output.iter_mut().enumerate() {
+        let source =
+            (position + offset) as u64 * clip.sample_rate as u64 / OUTPUT_SAMPLE_RATE as u64;
+        *sample += clip.samples[source as usize % clip.samples.len()] * gain;
+    }
+}
+
+/// Where mixed samples end up
+pub trait AudioBackend: Send {
+    /// Plays a buffer of mono samples at [`OUTPUT_SAMPLE_RATE`], blocking
+    /// until the device is ready for more
+    fn write(&mut self, samples: &[f32]) -> Result<(), String>;
+}
+
+/// Discards everything, for running without an audio device. It still
+/// takes as long as the samples would to play, so sounds finish on time.
+pub struct NullBackend;
+
+impl AudioBackend for NullBackend {
+    fn write(&mut self, samples: &[f32]) -> Result<(), String> {
+        let seconds = samples.len() as f64 / OUTPUT_SAMPLE_RATE as f64;
+        std::thread::sleep(std::time::Duration::from_secs_f64(seconds));
+        Ok(())
+    }
+}
+
+/// Feeds samples to a command line player. Writes block once the pipe is
+/// full, which keeps the mixer in step with the sound device.
+pub struct PipeBackend {
+    player: Child,
+    input: ChildStdin,
+    bytes: Vec<u8>,
+}
+
+impl PipeBackend {
+    /// Starts the first player in [`PLAYERS`] that is installed
+    pub fn open() -> Option<PipeBackend> {
+        PLAYERS.iter().find_map(|(program, args)| {
+            let mut player = Process::new(program)
+                .args(*args)
+                .stdin(Stdio::piped())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .spawn()
+                .ok()?;
+            let input = player.stdin.take()?;
+            Some(PipeBackend {
+                player,
+                input,
+                bytes: Vec::new(),
+            })
+        })
+    }
+}
+
+impl AudioBackend for PipeBackend {
+    fn write(&mut self, samples: &[f32]) -> Result<(), String> {
+        self.bytes.clear();
+        for sample in samples {
+            let sample = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+            self.bytes.extend_from_slice(&sample.to_le_bytes());
+        }
+        self.input
+            .write_all(&self.bytes)
+            .map_err(|err| format!("sound player stopped: {err}"))
+    }
+}
+
+impl Drop for PipeBackend {
+    fn drop(&mut self) {
+        let _ = self.player.kill();
+        let _ = self.player.wait();
+    }
+}
+
+/// The sound device if a player can be started, or silence if not
+pub fn open_backend() -> Box<dyn AudioBackend> {
+    match PipeBackend::open() {
+        Some(backend) => Box::new(backend),
+        None => {
+            eprintln!("ERROR! Found no sound player (aplay or paplay), running without sound");
+            Box::new(NullBackend)
+        }
+    }
+}
+
+pub enum Command {
+    Play(Sfx),
+    PlayVaried(Sfx),
+    SetVolumes(Volumes),
+    SetPaused(bool),
+    SetTheme(Theme),
+    SetMusicIntensity(f32),
+}
+
+/// The game's side of the audio thread
+pub struct Audio {
+    commands: Sender<Command>,
+    // Summary line for the debug overlay, written by the audio thread
+    status: Arc<Mutex<String>>,
+}
+
+impl Default for Audio {
+    /// Audio that goes nowhere, since nothing receives its commands
+    fn default() -> Self {
+        let (commands, _) = mpsc::channel();
+        Audio {
+            commands,
+            status: Arc::default(),
+        }
+    }
+}
+
+impl Audio {
+    /// Starts mixing sounds from `bank` into `backend` on a new thread
+    pub fn start(backend: Box<dyn AudioBackend>, bank: SoundBank, volumes: Volumes) -> Audio {
+        let (commands, receiver) = mpsc::channel();
+        let status = Arc::<Mutex<String>>::default();
+        let thread_status = Arc::clone(&status);
+        let spawned = std::thread::Builder::new()
+            .name(String::from("audio"))
+            .spawn(move || run_mixer(receiver, bank, Mixer::new(volumes), backend, thread_status));
+        if let Err(err) = spawned {
+            eprintln!("ERROR! Could not start the audio thread: {err}");
+        }
+        Audio { commands, status }
+    }
+
+    pub fn play(&self, sfx: Sfx) {
+        // A send only fails once the audio thread has gone, and then there
+        // is nothing left to play sounds anyway
+        let _ = self.commands.send(Command::Play(sfx));
+    }
+
+    pub fn play_varied(&self, sfx: Sfx) {
+        let _ = self.commands.send(Command::PlayVaried(sfx));
+    }
+
+    pub fn set_volumes(&self, volumes: Volumes) {
+        let _ = self.commands.send(Command::SetVolumes(volumes));
+    }
+
+    pub fn set_paused(&self, paused: bool) {
+        let _ = self.commands.send(Command::SetPaused(paused));
+    }
+
+    pub fn set_theme(&self, theme: Theme) {
+        let _ = self.commands.send(Command::SetTheme(theme));
+    }
+
+    pub fn set_music_intensity(&self, intensity: f32) {
+        let _ = self.commands.send(Command::SetMusicIntensity(intensity));
+    }
+
+    /// A one line summary for the debug overlay
+    pub fn describe(&self) -> String {
+        // Never wait on the audio thread from the game loop
+        match self.status.try_lock() {
+            Ok(status) if !status.is_empty() => status.clone(),
+            Ok(_) => String::from("not running"),
+            Err(_) => String::from("busy"),
+        }
+    }
+}
+
+fn run_mixer(
+    commands: Receiver<Command>,
+    mut bank: SoundBank,
+    mut mixer: Mixer,
+    mut backend: Box<dyn AudioBackend>,
+    status: Arc<Mutex<String>>,
+) {
+    let mut buffer = vec![0.0; BUFFER_SAMPLES];
+    loop {
+        loop {
+            match commands.try_recv() {
+                Ok(Command::Play(sfx)) => mixer.play(sfx),
+                Ok(Command::PlayVaried(sfx)) => mixer.play_varied(sfx),
+                Ok(Command::SetVolumes(volumes)) => mixer.volumes = volumes,
+                Ok(Command::SetPaused(paused)) => mixer.paused = paused,
+                Ok(Command::SetTheme(theme)) => {
+                    if bank.set_theme(theme) {
+                        mixer.restart_music();
+                    }
+                }
+                Ok(Command::SetMusicIntensity(intensity)) => mixer.set_music_intensity(intensity),
+                Err(TryRecvError::Empty) => break,
+                // The game has shut down
+                Err(TryRecvError::Disconnected) => return,
+            }
+        }
+
+        bank.reload_if_changed();
+        mixer.mix(&bank, &mut buffer);
+        if let Err(err) = backend.write(&buffer) {
+            eprintln!("ERROR! Audio output failed, continuing without sound: {err}");
+            backend = Box::new(NullBackend);
+        }
+        if let Ok(mut status) = status.lock() {
+            *status = format!("{}, {}", bank.describe(), mixer.describe());
+        }
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/// Decodes 8 or 16 bit PCM WAV data, mixing all channels down to mono
+fn decode_wav(data: &[u8]) -> Result<Clip, String> {
+    if data.len() < 12 || &data[0..4] != b"RIFF" || &data[8..12] != b"WAVE" {
+        return Err(String::from("not a WAV file"));
+    }
+
+    let mut format = None;
+    let mut rest = &data[12..];
+    while rest.len() >= 8 {
+        let id = &rest[0..4];
+        let len = u32::from_le_bytes([rest[4], rest[5], rest[6], rest[7]]) as usize;
+        let body = rest
+            .get(8..8 + len)
+            .ok_or_else(|| String::from("truncated chunk"))?;
+        match id {
+            b"fmt " if body.len() >= 16 => {
+                let audio_format = u16::from_le_bytes([body[0], body[1]]);
+                let channels = u16::from_le_bytes([body[2], body[3]]) as usize;
+                let sample_rate = u32::from_le_bytes([body[4], body[5], body[6], body[7]]);
+                let bits = u16::from_le_bytes([body[14], body[15]]);
+                if audio_format != 1 || channels == 0 || !matches!(bits, 8 | 16) {
+                    return Err(format!(
+                        "unsupported format {audio_format} with {channels} channels at {bits} bits"
+                    ));
+                }
+                format = Some((channels, sample_rate, bits));
+            }
+            b"data" => {
+                let (channels, sample_rate, bits) =
+                    format.ok_or_else(|| String::from("data chunk before fmt chunk"))?;
+                let samples = match bits {
+                    8 => body
+                        .iter()
+                        .map(|&byte| (byte as f32 - 128.0) / 128.0)
+                        .collect::<Vec<_>>(),
+                    _ => body
+                        .chunks_exact(2)
+                        .map(|pair| i16::from_le_bytes([pair[0], pair[1]]) as f32 / 32768.0)
+                        .collect(),
+                };
+                let samples = samples
+                    .chunks_exact(channels)
+                    .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+                    .collect();
+                return Ok(Clip {
+                    samples,
+                    sample_rate,
+                });
+            }
+            _ => (),
+        }
+        // Chunks are padded to an even length
+        rest = rest.get(8 + len + len % 2..).unwrap_or(&[]);
+    }
+    Err(String::from("no data chunk"))
+}
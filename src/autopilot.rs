@@ -0,0 +1,65 @@
+//! Plays the game without a player: the paddle follows the ball and serves
+//! it on its own. Used by the demo on the menu, and by the commands that
+//! play runs without a window.
+
+use crate::rng::Rng;
+use crate::GameState;
+use minifb::Key;
+
+// The paddle picks a new spot to catch the ball on this often, so that the
+// ball leaves it at different angles
+const AIM_INTERVAL_TICKS: u32 = 120;
+// And presses fire about once in this many ticks, to serve the ball and
+// fire the laser
+const FIRE_ONE_IN: usize = 30;
+
+pub struct Autopilot {
+    aim: Rng,
+    offset: f32,
+    ticks: u32,
+}
+
+impl Autopilot {
+    /// The same seed always plays the same way
+    pub fn new(seed: u64) -> Self {
+        Autopilot {
+            aim: Rng::new(seed),
+            offset: 0.0,
+            ticks: 0,
+        }
+    }
+
+    /// Moves the paddle and maybe presses fire, for the tick about to be
+    /// played
+    pub fn steer(&mut self, state: &mut GameState) {
+        if self.ticks.is_multiple_of(AIM_INTERVAL_TICKS) {
+            self.offset = self.aim.range_f32(-0.5, 0.5);
+        }
+        self.ticks = self.ticks.wrapping_add(1);
+        follow_ball(state, self.offset);
+        if self.aim.below(FIRE_ONE_IN) == 0 {
+            // Straight to the game, since a key from the player ends the demo
+            state.handle_key(Key::Space);
+        }
+    }
+}
+
+/// Steers the paddle to where the next ball will come down, or under the
+/// lowest ball while none is coming down, catching it `offset` of a paddle
+/// width away from the middle
+fn follow_ball(state: &mut GameState, offset: f32) {
+    let size = state.ball_size();
+    let lowest = state
+        .balls
+        .iter()
+        .min_by(|a, b| a.pos.y.total_cmp(&b.pos.y))
+        .map(|ball| ball.pos.x + size.x / 2.0);
+    // The same prediction auto-move and the landing markers use
+    let Some(center) = state.auto_move_target().or(lowest) else {
+        state.paddle_vel_x = 0.0;
+        return;
+    };
+    let target = center - state.paddle_width * (0.5 + offset);
+    let speed = state.paddle_movement_speed;
+    state.paddle_vel_x = (target - state.paddle_pos.x).clamp(-speed, speed);
+}
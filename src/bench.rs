@@ -0,0 +1,181 @@
+//! `breakrs bench-light` times how long drawing a frame takes with the ball
+//! light off and on. It draws the same seeded run both ways, without a
+//! window, with the autopilot serving and following the ball so the run
+//! keeps going.
+//!
+//! `breakrs bench-rotation` times the two ways turned shapes are drawn:
+//! rects a row at a time, and pictures by sampling every pixel around them,
+//! against plain rects of the same size.
+
+use crate::autopilot::Autopilot;
+use crate::clock::seconds;
+use crate::config::Config;
+use crate::{
+    draw_rect, draw_rotated_rect, draw_rotated_subcanvas, Canvas, GameState, Res, RunMode, HEIGHT,
+    WIDTH,
+};
+use std::f32::consts::TAU;
+use std::time::{Duration, Instant};
+
+const DEFAULT_SEED: u64 = 1;
+const DEFAULT_FRAMES: u32 = seconds(60);
+const DEFAULT_SHAPES: u32 = 100_000;
+// Sizes in pixels of the shapes drawn, from about a capsule up to about a
+// boss
+const ROTATION_SIZES: [(usize, usize); 3] = [(48, 18), (96, 32), (200, 80)];
+// Shapes are turned by this many different angles, all the way around
+const ROTATION_STEPS: u32 = 360;
+const SHAPE_COLOR: u32 = 0xC04040;
+const SPRITE_COLOR_KEY: u32 = 0x010101;
+
+/// Runs the benchmark with the options in `args`, reporting the timings on
+/// stdout
+pub fn run_cli(args: &[String]) -> Res<()> {
+    let mut seed = DEFAULT_SEED;
+    let mut frames = DEFAULT_FRAMES;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let value = args.next();
+        let parsed = match (arg.as_str(), value) {
+            ("--seed", Some(value)) => value.parse().map(|value| seed = value).is_ok(),
+            ("--frames", Some(value)) => value
+                .parse()
+                .map(|value| frames = value)
+                .is_ok_and(|_| frames > 0),
+            _ => false,
+        };
+        if !parsed {
+            eprintln!("ERROR! Usage: breakrs bench-light [--seed <seed>] [--frames <frames>]");
+            return Err(());
+        }
+    }
+
+    let off = time_drawing(seed, frames, false);
+    let on = time_drawing(seed, frames, true);
+    let per_frame = |total: Duration| total.as_secs_f64() * 1000.0 / frames as f64;
+    println!("Drew {frames} frames with seed {seed}");
+    println!("  ball light off: {:.3} ms per frame", per_frame(off));
+    println!("  ball light on:  {:.3} ms per frame", per_frame(on));
+    println!(
+        "  the light costs {:.3} ms per frame",
+        per_frame(on.saturating_sub(off))
+    );
+    Ok(())
+}
+
+/// Plays a campaign run from the first level, returning the time spent
+/// drawing. Only drawing is timed, so the rest of the tick doesn't hide
+/// what the light costs.
+fn time_drawing(seed: u64, frames: u32, ball_light: bool) -> Duration {
+    let mut state = GameState {
+        config: Config {
+            seed: Some(seed),
+            ball_light,
+            ..Config::default()
+        },
+        ..GameState::default()
+    };
+    state.start_run(RunMode::Campaign, 0);
+    let mut canvas = Canvas {
+        buffer: vec![0; WIDTH * HEIGHT],
+        stride: WIDTH,
+    };
+
+    let mut autopilot = Autopilot::new(seed.wrapping_add(1));
+    let mut drawing = Duration::ZERO;
+    for _ in 0..frames {
+        autopilot.steer(&mut state);
+        state.tick();
+        let start = Instant::now();
+        state.draw_scene(&mut canvas);
+        drawing += start.elapsed();
+    }
+    drawing
+}
+
+/// Runs the rotation benchmark with the options in `args`, reporting the
+/// timings on stdout
+pub fn run_rotation_cli(args: &[String]) -> Res<()> {
+    let mut shapes = DEFAULT_SHAPES;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let value = args.next();
+        let parsed = match (arg.as_str(), value) {
+            ("--shapes", Some(value)) => value
+                .parse()
+                .map(|value| shapes = value)
+                .is_ok_and(|_| shapes > 0),
+            _ => false,
+        };
+        if !parsed {
+            eprintln!("ERROR! Usage: breakrs bench-rotation [--shapes <shapes>]");
+            return Err(());
+        }
+    }
+
+    let mut canvas = Canvas {
+        buffer: vec![0; WIDTH * HEIGHT],
+        stride: WIDTH,
+    };
+    let per_shape = |total: Duration| total.as_secs_f64() * 1_000_000.0 / shapes as f64;
+    println!("Drew {shapes} shapes of each size, turned all the way around");
+    for (width, height) in ROTATION_SIZES {
+        // A picture with its corners keyed out, like a capsule's
+        let mut sprite = Canvas {
+            buffer: vec![SHAPE_COLOR; width * height],
+            stride: width,
+        };
+        for y in [0, height - 1] {
+            sprite.buffer[y * width] = SPRITE_COLOR_KEY;
+            sprite.buffer[y * width + width - 1] = SPRITE_COLOR_KEY;
+        }
+
+        let flat = time_shapes(shapes, |_| {
+            draw_rect(
+                &mut canvas,
+                (WIDTH - width) / 2,
+                (HEIGHT - height) / 2,
+                width,
+                height,
+                SHAPE_COLOR,
+            );
+        });
+        let (center_x, center_y) = (WIDTH as f32 / 2.0, HEIGHT as f32 / 2.0);
+        let rect = time_shapes(shapes, |angle| {
+            draw_rotated_rect(
+                &mut canvas,
+                center_x,
+                center_y,
+                width as f32,
+                height as f32,
+                angle,
+                SHAPE_COLOR,
+            );
+        });
+        let picture = time_shapes(shapes, |angle| {
+            draw_rotated_subcanvas(
+                &mut canvas,
+                &sprite,
+                center_x,
+                center_y,
+                angle,
+                Some(SPRITE_COLOR_KEY),
+            );
+        });
+        println!("  {width}x{height}:");
+        println!("    plain rect:     {:.3} us per shape", per_shape(flat));
+        println!("    turned rect:    {:.3} us per shape", per_shape(rect));
+        println!("    turned picture: {:.3} us per shape", per_shape(picture));
+    }
+    Ok(())
+}
+
+/// Calls `draw` `shapes` times with angles going around and around,
+/// returning the time it took
+fn time_shapes(shapes: u32, mut draw: impl FnMut(f32)) -> Duration {
+    let start = Instant::now();
+    for shape in 0..shapes {
+        draw((shape % ROTATION_STEPS) as f32 * TAU / ROTATION_STEPS as f32);
+    }
+    start.elapsed()
+}
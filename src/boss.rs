@@ -0,0 +1,250 @@
+//! The boss met at the end of every few campaign levels, once the bricks
+//! are cleared. It is a wide block that patrols above the paddle, bouncing
+//! balls like a brick does and now and then dropping a hazard that stuns a
+//! paddle it lands on. Every ball hit takes a point of health, and the
+//! level is cleared when none is left.
+//!
+//! A level file can script its own boss instead, which moves, fires and
+//! waits as its steps say rather than patrolling at random.
+
+use crate::clock::seconds;
+use crate::level::{BossScript, BossStep};
+use crate::physics::PlayField;
+use crate::rng::Rng;
+use crate::units::{WorldPos, WorldVec};
+
+// Every this many campaign levels ends with a boss
+pub const BOSS_INTERVAL: usize = 3;
+// The usual boss's health, and the most a scripted one can have
+pub const MAX_HEALTH: u32 = 12;
+pub const MAX_SCRIPTED_HEALTH: u32 = 100;
+pub const SIZE: WorldVec = WorldVec::new(0.4, 0.1);
+// Where the top of the boss patrols
+const TOP: f32 = 0.55;
+// World units per tick when unhurt. It gets faster as it is hurt, up to
+// twice as fast on its last point of health.
+const SPEED: f32 = 0.005;
+// It turns around at random now and then, on average this often
+const MEAN_TURN_TICKS: f32 = seconds(3) as f32;
+// Hazards drop a random time apart in this range, sooner as it is hurt
+const MIN_HAZARD_TICKS: u32 = seconds(2);
+const MAX_HAZARD_TICKS: u32 = seconds(5);
+// How long it flashes after a hit
+const FLASH_TICKS: u32 = 8;
+// How long after a hit before it can be hurt again, so a ball caught
+// against it can't take several points at once
+const HIT_COOLDOWN_TICKS: u32 = seconds(1) / 4;
+
+#[derive(Clone)]
+pub struct Boss {
+    // Top left corner
+    pub pos: WorldPos,
+    // Moving right when positive
+    direction: f32,
+    pub health: u32,
+    max_health: u32,
+    hazard_ticks: u32,
+    pub flash_ticks: u32,
+    cooldown_ticks: u32,
+    script: Option<Script>,
+}
+
+/// Where a scripted boss is in its steps
+#[derive(Clone)]
+struct Script {
+    steps: &'static [BossStep],
+    next: usize,
+    // Where it is heading, as an x position
+    target: Option<f32>,
+    wait_ticks: u32,
+}
+
+/// What a ball hitting the boss reverses of its velocity, as for bricks
+pub struct BossHit {
+    pub flip_x: bool,
+    pub flip_y: bool,
+}
+
+impl Boss {
+    /// A boss in the middle of the field, heading a random way, or
+    /// following `script` if the level has one
+    pub fn new(field: &PlayField, rng: &mut Rng, script: Option<&BossScript>) -> Self {
+        let direction = if rng.next_f32() < 0.5 { -1.0 } else { 1.0 };
+        let health = script.map_or(MAX_HEALTH, |script| script.health);
+        Boss {
+            pos: WorldPos::new(field.center_x() - SIZE.x / 2.0, TOP),
+            direction,
+            health,
+            max_health: health,
+            hazard_ticks: MAX_HAZARD_TICKS,
+            flash_ticks: 0,
+            cooldown_ticks: 0,
+            script: script.map(|script| Script {
+                steps: script.steps,
+                next: 0,
+                target: None,
+                wait_ticks: 0,
+            }),
+        }
+    }
+
+    /// Moves the boss on by a tick, returning where a hazard drops from if
+    /// one is due
+    pub fn update(&mut self, field: &PlayField, rng: &mut Rng) -> Option<WorldPos> {
+        self.flash_ticks = self.flash_ticks.saturating_sub(1);
+        self.cooldown_ticks = self.cooldown_ticks.saturating_sub(1);
+        if self.script.is_some() {
+            return self.run_script(field);
+        }
+        if rng.next_f32() < 1.0 / MEAN_TURN_TICKS {
+            self.direction = -self.direction;
+        }
+        self.pos.x += self.direction * SPEED * (1.0 + self.hurt());
+        let max_x = field.right - SIZE.x;
+        if self.pos.x <= field.left || self.pos.x >= max_x {
+            self.pos.x = self.pos.x.clamp(field.left, max_x);
+            self.direction = -self.direction;
+        }
+
+        self.hazard_ticks = self.hazard_ticks.saturating_sub(1);
+        if self.hazard_ticks > 0 {
+            return None;
+        }
+        let wait = rng.range_f32(MIN_HAZARD_TICKS as f32, MAX_HAZARD_TICKS as f32);
+        self.hazard_ticks = (wait * (1.0 - self.hurt() / 2.0)) as u32;
+        Some(self.hazard_drop())
+    }
+
+    /// Takes a scripted boss on by a tick. Moves and waits take time, and
+    /// the steps between them all happen in the same tick.
+    fn run_script(&mut self, field: &PlayField) -> Option<WorldPos> {
+        let mut script = self.script.take()?;
+        let mut drop = None;
+        if let Some(target) = script.target {
+            let speed = SPEED * (1.0 + self.hurt());
+            let distance = target - self.pos.x;
+            if distance.abs() <= speed {
+                self.pos.x = target;
+                script.target = None;
+            } else {
+                self.pos.x += speed.copysign(distance);
+            }
+        } else if script.wait_ticks > 0 {
+            script.wait_ticks -= 1;
+        } else {
+            // Stops after one pass in case no step takes any time
+            for _ in 0..script.steps.len() {
+                let step = script.steps[script.next];
+                script.next = (script.next + 1) % script.steps.len();
+                match step {
+                    BossStep::MoveTo(across) => {
+                        let max_x = field.right - SIZE.x;
+                        script.target = Some(field.left + (max_x - field.left) * across);
+                        break;
+                    }
+                    BossStep::Wait(ticks) => {
+                        script.wait_ticks = ticks;
+                        break;
+                    }
+                    BossStep::Fire => drop = Some(self.hazard_drop()),
+                    BossStep::SetHealth(health) => {
+                        self.health = health.clamp(1, self.max_health);
+                    }
+                }
+            }
+        }
+        self.script = Some(script);
+        drop
+    }
+
+    /// Where hazards drop from, under its middle
+    fn hazard_drop(&self) -> WorldPos {
+        WorldPos::new(self.pos.x + SIZE.x / 2.0, self.pos.y - SIZE.y)
+    }
+
+    /// How much health is gone, from 0.0 to 1.0
+    pub fn hurt(&self) -> f32 {
+        1.0 - self.health as f32 / self.max_health as f32
+    }
+
+    /// Whether a hit would hurt it, which it can't for a moment after the
+    /// last one
+    pub fn is_vulnerable(&self) -> bool {
+        self.cooldown_ticks == 0
+    }
+
+    /// Takes a point of health, returning whether that was the last
+    pub fn damage(&mut self) -> bool {
+        self.health = self.health.saturating_sub(1);
+        self.flash_ticks = FLASH_TICKS;
+        self.cooldown_ticks = HIT_COOLDOWN_TICKS;
+        self.health == 0
+    }
+
+    /// Whether a ball of `size` at `pos` moving by `vel` runs into the
+    /// boss this tick, and how it bounces off if so
+    pub fn collision(&self, pos: WorldPos, vel: WorldVec, size: WorldVec) -> Option<BossHit> {
+        let overlaps_x =
+            |ball_x: f32| ball_x + size.x >= self.pos.x && ball_x <= self.pos.x + SIZE.x;
+        let overlaps_y =
+            |ball_y: f32| ball_y >= self.pos.y - SIZE.y && ball_y - size.y <= self.pos.y;
+        if !overlaps_x(pos.x + vel.x) || !overlaps_y(pos.y + vel.y) {
+            return None;
+        }
+        // A ball that was already above or below the boss hit its top or
+        // bottom, one level with it hit a side, and anything else a corner
+        let was_above_or_below = overlaps_x(pos.x);
+        let was_level = overlaps_y(pos.y);
+        // The boss moved into a ball that was already there. Bouncing it
+        // would turn it back and forth inside the boss every tick, so it is
+        // left to carry on out.
+        if was_above_or_below && was_level {
+            return None;
+        }
+        Some(BossHit {
+            flip_x: !was_above_or_below,
+            flip_y: was_above_or_below || !was_level,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_scripted_boss_follows_its_steps() {
+        const STEPS: &[BossStep] = &[
+            BossStep::MoveTo(0.0),
+            BossStep::Fire,
+            BossStep::SetHealth(3),
+            BossStep::Wait(2),
+        ];
+        let script = BossScript {
+            health: 5,
+            steps: STEPS,
+        };
+        let field = PlayField::FULL;
+        let mut rng = Rng::new(1);
+        let mut boss = Boss::new(&field, &mut rng, Some(&script));
+        assert_eq!(boss.health, 5);
+
+        // Heads for the left wall and fires once it gets there
+        let mut drops = Vec::new();
+        let mut ticks = 0;
+        while drops.is_empty() {
+            drops.extend(boss.update(&field, &mut rng));
+            ticks += 1;
+            assert!(ticks < 1000, "never fired");
+        }
+        assert_eq!(boss.pos.x, field.left);
+        assert_eq!(boss.health, 3);
+        // Then waits before starting over with the move, which it is
+        // already at the end of
+        assert_eq!(boss.update(&field, &mut rng), None);
+        assert_eq!(boss.update(&field, &mut rng), None);
+        assert_eq!(boss.update(&field, &mut rng), None);
+        assert_eq!(boss.update(&field, &mut rng), None);
+        assert!(boss.update(&field, &mut rng).is_some());
+    }
+}
@@ -0,0 +1,380 @@
+//! Challenges: fixed playlists of levels with their own rules and
+//! leaderboards. The game's own playlists are files in
+//! `assets/challenges`, and any `.txt` file in the `challenges` directory
+//! under the data directory adds another, listed after them.
+
+use crate::modifiers::{ModifierEffects, NEUTRAL};
+use crate::versioned::{self, Format};
+use crate::{compute_multiline_text_data, draw_focus_outline, draw_subcanvas, Canvas};
+use minifb::Key;
+use rusttype::Font;
+use std::collections::BTreeMap;
+use std::io::ErrorKind;
+use std::path::Path;
+
+const LEADERBOARD_SIZE: usize = 5;
+const TEXT_HEIGHT: f32 = 20.0;
+const ROW_GAP: usize = 12;
+// Playlists can change the usual sizes and speeds by these factors at most
+const MIN_MULTIPLIER: f32 = 0.25;
+const MAX_MULTIPLIER: f32 = 4.0;
+
+const FORMAT: Format = Format {
+    name: "leaderboards",
+    migrations: &[versioned::add_version],
+};
+
+/// A fixed sequence of levels played back to back under its own rules,
+/// with a separate leaderboard
+pub struct Playlist {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+    pub levels: &'static [usize],
+    pub effects: ModifierEffects,
+}
+
+/// Reads the playlists in every `.txt` file in `dir`, named after the file.
+/// Playlists whose file can't be read or parsed, or that play a level past
+/// the first `level_count`, are skipped, reporting why.
+pub fn load_dir(dir: &Path, level_count: usize) -> Vec<&'static Playlist> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Vec::new(),
+        Err(err) => {
+            eprintln!("ERROR! Could not read challenges from {dir:?}: {err}");
+            return Vec::new();
+        }
+    };
+    let mut paths = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|extension| extension == "txt"))
+        .collect::<Vec<_>>();
+    paths.sort();
+
+    paths
+        .iter()
+        .filter_map(|path| {
+            let id = path.file_stem()?.to_str()?;
+            let playlist = std::fs::read_to_string(path)
+                .map_err(|err| err.to_string())
+                .and_then(|text| parse(id, &text, level_count));
+            match playlist {
+                Ok(playlist) => Some(&*Box::leak(Box::new(playlist))),
+                Err(err) => {
+                    eprintln!("ERROR! Skipping challenge {path:?}: {err}");
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// Parses a playlist file: `key = value` lines, with `#` starting a
+/// comment line. Errors say which line is wrong and why.
+///
+/// ```text
+/// name = Gauntlet
+/// description = every level back to back, faster ball
+/// # The levels in the order they are played, counting from 1
+/// levels = 1 2 3 4
+/// # Optional: how the run differs from usual, as multiples of the usual
+/// # paddle_width, paddle_speed, ball_speed, ball_diameter and points, and
+/// # laser shots to start with
+/// ball_speed = 1.2
+/// laser_ammo = 36
+/// ```
+pub fn parse(id: &str, text: &str, level_count: usize) -> Result<Playlist, String> {
+    let mut file = PlaylistFile {
+        name: None,
+        description: None,
+        levels: None,
+        effects: NEUTRAL,
+        level_count,
+    };
+    for (line_number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        file.set(line)
+            .map_err(|err| format!("line {}: {err}", line_number + 1))?;
+    }
+    Ok(Playlist {
+        id: String::from(id).leak(),
+        name: file.name.ok_or("missing `name`")?.leak(),
+        description: file.description.ok_or("missing `description`")?.leak(),
+        levels: file.levels.ok_or("missing `levels`")?.leak(),
+        effects: file.effects,
+    })
+}
+
+/// What a playlist file says so far
+struct PlaylistFile {
+    name: Option<String>,
+    description: Option<String>,
+    levels: Option<Vec<usize>>,
+    effects: ModifierEffects,
+    // How many levels there are to choose from
+    level_count: usize,
+}
+
+impl PlaylistFile {
+    fn set(&mut self, line: &str) -> Result<(), String> {
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| format!("expected `key = value`, found `{line}`"))?;
+        let value = value.trim();
+        match key.trim() {
+            "name" | "description" if value.is_empty() => {
+                return Err(format!("the {} is empty", key.trim()));
+            }
+            "name" => self.name = Some(String::from(value)),
+            "description" => self.description = Some(String::from(value)),
+            "levels" => self.levels = Some(parse_levels(value, self.level_count)?),
+            "paddle_width" => self.effects.paddle_width = parse_multiplier(value)?,
+            "paddle_speed" => self.effects.paddle_speed = parse_multiplier(value)?,
+            "ball_speed" => self.effects.ball_speed = parse_multiplier(value)?,
+            "ball_diameter" => self.effects.ball_diameter = parse_multiplier(value)?,
+            "points" => self.effects.points = parse_multiplier(value)?,
+            "laser_ammo" => {
+                self.effects.laser_ammo = value
+                    .parse()
+                    .map_err(|err| format!("invalid number `{value}`: {err}"))?;
+            }
+            key => return Err(format!("unknown key `{key}`")),
+        }
+        Ok(())
+    }
+}
+
+/// Parses level numbers counting from 1 into level indices
+fn parse_levels(value: &str, level_count: usize) -> Result<Vec<usize>, String> {
+    let levels = value
+        .split_whitespace()
+        .map(|level| match level.parse::<usize>() {
+            Ok(number) if (1..=level_count).contains(&number) => Ok(number - 1),
+            Ok(number) => Err(format!(
+                "there is no level {number}, there are {level_count}"
+            )),
+            Err(err) => Err(format!("invalid level number `{level}`: {err}")),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if levels.is_empty() {
+        return Err(String::from("no levels to play"));
+    }
+    Ok(levels)
+}
+
+fn parse_multiplier(value: &str) -> Result<f32, String> {
+    let number = value
+        .parse::<f32>()
+        .map_err(|err| format!("invalid number `{value}`: {err}"))?;
+    if !(MIN_MULTIPLIER..=MAX_MULTIPLIER).contains(&number) {
+        return Err(format!(
+            "expected {MIN_MULTIPLIER} to {MAX_MULTIPLIER}, found {number}"
+        ));
+    }
+    Ok(number)
+}
+
+/// Best scores for each playlist, stored one score per line as
+/// `<playlist id> <score>`. Scores are kept even for playlists that are
+/// missing, so they come back with their playlist file.
+#[derive(Default)]
+pub struct Leaderboards {
+    scores: BTreeMap<String, Vec<u32>>,
+}
+
+impl Leaderboards {
+    pub fn load(path: &Path) -> Leaderboards {
+        let mut leaderboards = Leaderboards::default();
+        let text = match versioned::read(path, &FORMAT) {
+            Ok(text) => text,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return leaderboards,
+            Err(err) => {
+                eprintln!("ERROR! Could not read leaderboards from {path:?}: {err}");
+                return leaderboards;
+            }
+        };
+
+        for (line_number, line) in text.lines().enumerate() {
+            let mut fields = line.split_whitespace();
+            let entry = match (fields.next(), fields.next().map(str::parse::<u32>)) {
+                (Some(id), Some(Ok(score))) => Some((id, score)),
+                (None, None) => continue,
+                _ => None,
+            };
+            match entry {
+                Some((id, score)) => leaderboards.insert(id, score),
+                None => eprintln!(
+                    "ERROR! Ignoring malformed leaderboard entry on line {} of {path:?}",
+                    line_number + 1
+                ),
+            }
+        }
+        leaderboards
+    }
+
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        let text = self
+            .scores
+            .iter()
+            .flat_map(|(id, scores)| scores.iter().map(move |score| format!("{id} {score}\n")))
+            .collect::<String>();
+        versioned::write(path, &FORMAT, &text)
+    }
+
+    pub fn scores(&self, id: &str) -> &[u32] {
+        self.scores.get(id).map_or(&[], Vec::as_slice)
+    }
+
+    /// Adds a score to a playlist's leaderboard, keeping only the best few
+    pub fn insert(&mut self, id: &str, score: u32) {
+        let scores = self.scores.entry(String::from(id)).or_default();
+        scores.push(score);
+        scores.sort_unstable_by(|a, b| b.cmp(a));
+        scores.truncate(LEADERBOARD_SIZE);
+    }
+}
+
+pub enum ChallengeSelection {
+    None,
+    Start(usize),
+    Back,
+}
+
+pub struct ChallengeSelect {
+    pub cursor: usize,
+}
+
+impl ChallengeSelect {
+    /// Moves through `playlist_count` playlists
+    pub fn key_pressed(&mut self, key: Key, playlist_count: usize) -> ChallengeSelection {
+        match key {
+            Key::Up | Key::W => self.cursor = self.cursor.saturating_sub(1),
+            Key::Down | Key::S => {
+                self.cursor = (self.cursor + 1).min(playlist_count.saturating_sub(1));
+            }
+            Key::Enter | Key::Space if self.cursor < playlist_count => {
+                return ChallengeSelection::Start(self.cursor);
+            }
+            Key::Backspace | Key::L => return ChallengeSelection::Back,
+            _ => (),
+        }
+        ChallengeSelection::None
+    }
+
+    pub fn draw(
+        &self,
+        canvas: &mut Canvas,
+        font: Option<&Font>,
+        playlists: &[&Playlist],
+        leaderboards: &Leaderboards,
+        focus: f32,
+    ) {
+        let Some(font) = font else {
+            return;
+        };
+        let title = compute_multiline_text_data(font, TEXT_HEIGHT * 1.5, &["CHALLENGES"]);
+        let mut y = canvas.height() / 8;
+        draw_subcanvas(
+            canvas,
+            &title,
+            canvas.width().saturating_sub(title.width()) / 2,
+            y,
+        );
+        y += title.height() + ROW_GAP * 2;
+
+        if playlists.is_empty() {
+            let text = compute_multiline_text_data(font, TEXT_HEIGHT, &["no challenges found"]);
+            let x = canvas.width().saturating_sub(text.width()) / 2;
+            draw_subcanvas(canvas, &text, x, y);
+            y += text.height() + ROW_GAP * 2;
+        }
+        for (index, playlist) in playlists.iter().enumerate() {
+            let text = compute_multiline_text_data(
+                font,
+                TEXT_HEIGHT,
+                &[playlist.name, playlist.description],
+            );
+            let x = canvas.width().saturating_sub(text.width()) / 2;
+            if index == self.cursor {
+                draw_focus_outline(
+                    canvas,
+                    x,
+                    y,
+                    text.width(),
+                    text.height(),
+                    ROW_GAP / 2,
+                    focus,
+                );
+            }
+            draw_subcanvas(canvas, &text, x, y);
+            y += text.height() + ROW_GAP * 2;
+        }
+
+        let mut lines = vec![String::from("best scores")];
+        let scores = playlists
+            .get(self.cursor)
+            .map_or(&[][..], |playlist| leaderboards.scores(playlist.id));
+        if scores.is_empty() {
+            lines.push(String::from("none yet"));
+        }
+        lines.extend(
+            scores
+                .iter()
+                .enumerate()
+                .map(|(rank, score)| format!("{}. {score}", rank + 1)),
+        );
+        lines.push(String::from(" "));
+        lines.push(String::from("[Enter] play  [L] back"));
+        let lines = lines.iter().map(String::as_str).collect::<Vec<_>>();
+        let text = compute_multiline_text_data(font, TEXT_HEIGHT, &lines);
+        let x = canvas.width().saturating_sub(text.width()) / 2;
+        draw_subcanvas(canvas, &text, x, y);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_a_playlist() {
+        let text = "\
+name = Gauntlet
+# Comments are skipped
+description = every level back to back, faster ball
+levels = 1 2 3 4
+ball_speed = 1.2
+laser_ammo = 36
+";
+        let playlist = parse("gauntlet", text, 4).unwrap();
+        assert_eq!(playlist.id, "gauntlet");
+        assert_eq!(playlist.name, "Gauntlet");
+        assert_eq!(playlist.levels, [0, 1, 2, 3]);
+        assert_eq!(playlist.effects.ball_speed, 1.2);
+        assert_eq!(playlist.effects.laser_ammo, 36);
+        assert_eq!(playlist.effects.paddle_width, 1.0);
+    }
+
+    #[test]
+    fn bad_playlists_say_what_is_wrong() {
+        let base = "name = Broken\ndescription = oops\n";
+        let cases = [
+            ("levels = 1 5", "line 3: there is no level 5"),
+            ("levels = 0", "line 3: there is no level 0"),
+            ("levels =", "line 3: no levels to play"),
+            ("levels = 1\nball_speed = 9", "line 4: expected 0.25 to 4"),
+            ("levels = 1\nlives = 3", "line 4: unknown key `lives`"),
+            ("", "missing `levels`"),
+        ];
+        for (rest, expected) in cases {
+            let err = parse("broken", &format!("{base}{rest}\n"), 4)
+                .err()
+                .expect("parsed a bad playlist");
+            assert!(err.starts_with(expected), "{err}");
+        }
+    }
+}
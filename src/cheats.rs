@@ -0,0 +1,61 @@
+//! Cheat codes typed during play. Any cheat marks the run, and a marked run
+//! keeps no scores, times or progress.
+
+use minifb::Key;
+
+#[derive(Clone, Copy)]
+pub enum Cheat {
+    MultiBall,
+    FullLives,
+}
+
+impl Cheat {
+    pub fn name(self) -> &'static str {
+        match self {
+            Cheat::MultiBall => "multiball",
+            Cheat::FullLives => "full lives",
+        }
+    }
+}
+
+const CODES: &[(&[Key], Cheat)] = &[
+    (&[Key::B, Key::A, Key::L, Key::L, Key::Z], Cheat::MultiBall),
+    (
+        &[
+            Key::Up,
+            Key::Up,
+            Key::Down,
+            Key::Down,
+            Key::Left,
+            Key::Right,
+            Key::Left,
+            Key::Right,
+            Key::B,
+            Key::A,
+        ],
+        Cheat::FullLives,
+    ),
+];
+
+/// Watches key presses for the end of a cheat code. The keys still do
+/// whatever they normally do as they are typed.
+#[derive(Default)]
+pub struct CheatCodes {
+    // The latest presses, as many as the longest code
+    recent: Vec<Key>,
+}
+
+impl CheatCodes {
+    /// Returns the cheat `key` finishes typing, if any
+    pub fn key_pressed(&mut self, key: Key) -> Option<Cheat> {
+        let longest = CODES.iter().map(|(code, _)| code.len()).max().unwrap_or(0);
+        if self.recent.len() == longest {
+            self.recent.remove(0);
+        }
+        self.recent.push(key);
+        let &(_, cheat) = CODES.iter().find(|(code, _)| self.recent.ends_with(code))?;
+        // Each code has to be typed out in full again to work again
+        self.recent.clear();
+        Some(cheat)
+    }
+}
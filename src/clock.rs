@@ -0,0 +1,89 @@
+/// Game time advances in fixed ticks, nominally this many per second of
+/// real time
+pub const TICKS_PER_SECOND: u32 = 60;
+
+pub const fn seconds(seconds: u32) -> u32 {
+    seconds * TICKS_PER_SECOND
+}
+
+/// The one source of game time. Every frame asks the clock how many ticks
+/// to simulate, so pausing and slowing the game down happen in one place,
+/// and run and level times are read from here instead of being counted
+/// separately by each system.
+pub struct GameClock {
+    run_ticks: u32,
+    level_ticks: u32,
+    paused: bool,
+    // Ticks simulated per frame; below 1.0 the game runs in slow motion
+    time_scale: f32,
+    // Fraction of a tick left over from earlier frames
+    carry: f32,
+}
+
+impl Default for GameClock {
+    fn default() -> Self {
+        GameClock {
+            run_ticks: 0,
+            level_ticks: 0,
+            paused: false,
+            time_scale: 1.0,
+            carry: 0.0,
+        }
+    }
+}
+
+impl GameClock {
+    pub fn new(time_scale: f32) -> Self {
+        GameClock {
+            time_scale,
+            ..GameClock::default()
+        }
+    }
+
+    /// Returns the number of ticks the game should simulate this frame
+    pub fn frame(&mut self) -> u32 {
+        if self.paused {
+            return 0;
+        }
+        self.carry += self.time_scale;
+        let ticks = self.carry.floor();
+        self.carry -= ticks;
+        ticks as u32
+    }
+
+    /// Counts one simulated tick
+    pub fn advance(&mut self) {
+        self.run_ticks += 1;
+        self.level_ticks += 1;
+    }
+
+    pub fn start_level(&mut self) {
+        self.level_ticks = 0;
+    }
+
+    pub fn run_ticks(&self) -> u32 {
+        self.run_ticks
+    }
+
+    pub fn level_ticks(&self) -> u32 {
+        self.level_ticks
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    pub fn time_scale(&self) -> f32 {
+        self.time_scale
+    }
+}
+
+/// Formats a tick count as `m:ss.t`
+pub fn format_ticks(ticks: u32) -> String {
+    let tenths = ticks * 10 / TICKS_PER_SECOND;
+    format!("{}:{:02}.{}", tenths / 600, tenths / 10 % 60, tenths % 10)
+}
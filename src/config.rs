@@ -0,0 +1,589 @@
+use crate::modifiers::{ModifierEffects, NEUTRAL};
+use crate::physics::constants::{
+    BALL_SPEED_STEP, PADDLE_EDGE_ANGLE, SPEED_RAMP_HITS, SPEED_RAMP_MAX,
+};
+use crate::rng::Rng;
+use crate::versioned::{self, Format};
+use minifb::Key;
+use std::fmt::Write;
+use std::path::Path;
+
+const FORMAT: Format = Format {
+    name: "config",
+    migrations: &[versioned::add_version],
+};
+
+/// Which keys move the paddle
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Controls {
+    Wasd,
+    Arrows,
+}
+
+impl Controls {
+    pub const ALL: [Controls; 2] = [Controls::Wasd, Controls::Arrows];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Controls::Wasd => "wasd",
+            Controls::Arrows => "arrows",
+        }
+    }
+
+    /// The keys that move the paddle left and right
+    pub fn keys(self) -> (Key, Key) {
+        match self {
+            Controls::Wasd => (Key::A, Key::D),
+            Controls::Arrows => (Key::Left, Key::Right),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Normal => "normal",
+            Difficulty::Hard => "hard",
+        }
+    }
+
+    /// Applied to every level on top of any modifiers. Easier levels score
+    /// fewer points.
+    pub fn effects(self) -> ModifierEffects {
+        match self {
+            Difficulty::Easy => ModifierEffects {
+                paddle_width: 1.25,
+                ball_speed: 0.85,
+                points: 0.75,
+                ..NEUTRAL
+            },
+            Difficulty::Normal => NEUTRAL,
+            Difficulty::Hard => ModifierEffects {
+                paddle_width: 0.85,
+                ball_speed: 1.2,
+                points: 1.5,
+                ..NEUTRAL
+            },
+        }
+    }
+
+    /// How forgiving collisions are. Easier levels count near misses.
+    pub fn hitboxes(self) -> Hitboxes {
+        match self {
+            Difficulty::Easy => Hitboxes {
+                paddle_reach: 0.02,
+                capsule_reach: 0.03,
+                save_grace_ms: 50.0,
+            },
+            Difficulty::Normal => Hitboxes {
+                paddle_reach: 0.01,
+                capsule_reach: 0.015,
+                save_grace_ms: 25.0,
+            },
+            Difficulty::Hard => Hitboxes {
+                paddle_reach: 0.0,
+                capsule_reach: 0.0,
+                save_grace_ms: 0.0,
+            },
+        }
+    }
+}
+
+/// How far past what is drawn things still collide, in world units, and how
+/// late the paddle can be to a ball
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Hitboxes {
+    // Added to each end of a paddle where it meets the ball
+    pub paddle_reach: f32,
+    // Added all around a capsule where a paddle catches it
+    pub capsule_reach: f32,
+    // A paddle arriving this long after the ball has gone past it still
+    // saves the ball
+    pub save_grace_ms: f32,
+}
+
+/// Colors of the playfield
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Palette {
+    pub background: u32,
+    pub ball: u32,
+    pub paddle: u32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Theme {
+    Classic,
+    Night,
+    Paper,
+}
+
+impl Theme {
+    pub const ALL: [Theme; 3] = [Theme::Classic, Theme::Night, Theme::Paper];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Theme::Classic => "classic",
+            Theme::Night => "night",
+            Theme::Paper => "paper",
+        }
+    }
+
+    pub fn palette(self) -> Palette {
+        match self {
+            Theme::Classic => Palette {
+                background: 0x00FFFF,
+                ball: 0xFF00FF,
+                paddle: 0xFFFF00,
+            },
+            Theme::Night => Palette {
+                background: 0x101830,
+                ball: 0xFFFFFF,
+                paddle: 0xFF8000,
+            },
+            Theme::Paper => Palette {
+                background: 0xF0E8D8,
+                ball: 0x202020,
+                paddle: 0x4060C0,
+            },
+        }
+    }
+}
+
+/// How the paddle sends the ball back up
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PaddleShape {
+    // The exit angle follows where the ball hits, or with the classic
+    // paddle, the middle third reflects the ball and the outer thirds angle
+    // it outwards
+    Flat,
+    // Curved, so the ball is angled further out the further from the
+    // middle it hits
+    Convex,
+    // Like flat, but the ball leaves a notch in the middle straight up
+    Notched,
+}
+
+impl PaddleShape {
+    pub const ALL: [PaddleShape; 3] =
+        [PaddleShape::Flat, PaddleShape::Convex, PaddleShape::Notched];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            PaddleShape::Flat => "flat",
+            PaddleShape::Convex => "convex",
+            PaddleShape::Notched => "notched",
+        }
+    }
+}
+
+fn parse_bool(value: &str) -> Result<bool, String> {
+    match value {
+        "true" => Ok(true),
+        "false" => Ok(false),
+        _ => Err(format!("expected true or false, found `{value}`")),
+    }
+}
+
+/// Finds the variant of `all` with the given name
+fn parse_named<T: Copy>(all: &[T], name: fn(T) -> &'static str, value: &str) -> Option<T> {
+    all.iter().copied().find(|item| name(*item) == value)
+}
+
+/// Player-tunable settings, read from `key = value` lines. Lines starting
+/// with `#` are comments and missing keys keep their defaults.
+pub struct Config {
+    // Serve angles are in degrees above the horizontal. Serves from the
+    // middle of the field go up at the max, and from the walls at the min.
+    pub serve_angle_min: f32,
+    pub serve_angle_max: f32,
+    // Fixes the gameplay random number generator so runs are repeatable
+    pub seed: Option<u64>,
+    // Game ticks per frame, so below 1.0 everything runs slower
+    pub game_speed: f32,
+    // Name of a sound pack in `assets/audio/packs/` to use over the default
+    // sounds
+    pub sound_pack: Option<String>,
+    pub controls: Controls,
+    pub difficulty: Difficulty,
+    pub theme: Theme,
+    // Shows where the ball will land, for players who find it hard to
+    // follow
+    pub landing_marker: bool,
+    // Also marks where a ball about to be served will come back down
+    pub aim_assist: bool,
+    // The paddle follows the ball by itself and Space nudges it along, for
+    // one-handed and single-switch play
+    pub auto_move: bool,
+    // Radians the normals of the paddle's outer zones tilt from vertical
+    pub paddle_edge_angle: f32,
+    // Fraction the ball speed changes by for each press of +/-
+    pub ball_speed_step: f32,
+    // Experimental: two paddles stacked one above the other, each half the
+    // usual width, moving together
+    pub elevator_paddles: bool,
+    pub paddle_shape: PaddleShape,
+    // Flat paddles use the original three zones instead of a smooth curve
+    pub classic_paddle: bool,
+    // A glow around each ball. It touches every pixel near every ball each
+    // frame, so it is off unless asked for
+    pub ball_light: bool,
+    // Replay the last few seconds in slow motion after a ball is lost
+    pub instant_replay: bool,
+    // During instant replays, show the ball close up in a corner
+    pub replay_zoom: bool,
+    // Show the run time in the window title
+    pub speedrun_timer: bool,
+    // The ball speeds up every this many paddle hits and broken bricks, or
+    // never if zero
+    pub speed_ramp_hits: u32,
+    // Fraction above the serve speed the ramp stops at
+    pub speed_ramp_max: f32,
+    // Balls fall under gravity and fly in arcs
+    pub gravity: bool,
+    // Show a small picture where each game sound comes from
+    pub sound_cues: bool,
+    // Outline what is drawn and what collides, for the paddle and capsules
+    pub show_hitboxes: bool,
+    // Where the window's top left corner was when the game last closed, in
+    // desktop coordinates, which span every monitor
+    pub window_position: Option<(isize, isize)>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            serve_angle_min: 30.0,
+            serve_angle_max: 60.0,
+            seed: None,
+            game_speed: 1.0,
+            sound_pack: None,
+            controls: Controls::Wasd,
+            difficulty: Difficulty::Normal,
+            theme: Theme::Classic,
+            landing_marker: false,
+            aim_assist: false,
+            auto_move: false,
+            paddle_edge_angle: PADDLE_EDGE_ANGLE,
+            ball_speed_step: BALL_SPEED_STEP,
+            elevator_paddles: false,
+            paddle_shape: PaddleShape::Flat,
+            classic_paddle: false,
+            ball_light: false,
+            instant_replay: true,
+            replay_zoom: true,
+            speedrun_timer: false,
+            speed_ramp_hits: SPEED_RAMP_HITS,
+            speed_ramp_max: SPEED_RAMP_MAX,
+            gravity: false,
+            sound_cues: false,
+            show_hitboxes: false,
+            window_position: None,
+        }
+    }
+}
+
+impl Config {
+    pub fn load(path: &Path) -> Config {
+        let mut config = Config::default();
+        let text = match versioned::read(path, &FORMAT) {
+            Ok(text) => text,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return config,
+            Err(err) => {
+                eprintln!("ERROR! Could not read config from {path:?}: {err}");
+                return config;
+            }
+        };
+
+        for (line_number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Err(err) = config.set(line) {
+                eprintln!(
+                    "ERROR! Ignoring config line {} of {path:?}: {err}",
+                    line_number + 1
+                );
+            }
+        }
+
+        if config.serve_angle_min > config.serve_angle_max {
+            eprintln!("ERROR! serve_angle_min is larger than serve_angle_max, using defaults");
+            config.serve_angle_min = Config::default().serve_angle_min;
+            config.serve_angle_max = Config::default().serve_angle_max;
+        }
+        config
+    }
+
+    fn set(&mut self, line: &str) -> Result<(), String> {
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| format!("expected `key = value`, found `{line}`"))?;
+        let value = value.trim();
+        match key.trim() {
+            "serve_angle_min" => self.serve_angle_min = parse_angle(value)?,
+            "serve_angle_max" => self.serve_angle_max = parse_angle(value)?,
+            "seed" => {
+                self.seed = Some(
+                    value
+                        .parse()
+                        .map_err(|err| format!("invalid seed `{value}`: {err}"))?,
+                )
+            }
+            "game_speed" => {
+                let speed = value
+                    .parse::<f32>()
+                    .map_err(|err| format!("invalid game speed `{value}`: {err}"))?;
+                if !(0.25..=2.0).contains(&speed) {
+                    return Err(format!("game speed {speed} is outside the range 0.25 to 2"));
+                }
+                self.game_speed = speed;
+            }
+            "sound_pack" => self.sound_pack = Some(String::from(value)),
+            "window_position" => {
+                let position = value
+                    .split_once(',')
+                    .and_then(|(x, y)| Some((x.trim().parse().ok()?, y.trim().parse().ok()?)));
+                self.window_position =
+                    Some(position.ok_or_else(|| format!("expected `x, y`, found `{value}`"))?);
+            }
+            "controls" => {
+                self.controls = parse_named(&Controls::ALL, Controls::name, value)
+                    .ok_or_else(|| format!("unknown controls `{value}`"))?;
+            }
+            "difficulty" => {
+                self.difficulty = parse_named(&Difficulty::ALL, Difficulty::name, value)
+                    .ok_or_else(|| format!("unknown difficulty `{value}`"))?;
+            }
+            "paddle_edge_angle" => {
+                let angle = value
+                    .parse::<f32>()
+                    .map_err(|err| format!("invalid angle `{value}`: {err}"))?;
+                if !(0.0..=60.0).contains(&angle) {
+                    return Err(format!(
+                        "angle {angle} is outside the range 0 to 60 degrees"
+                    ));
+                }
+                self.paddle_edge_angle = angle.to_radians();
+            }
+            "ball_speed_step" => {
+                let percent = value
+                    .parse::<f32>()
+                    .map_err(|err| format!("invalid speed step `{value}`: {err}"))?;
+                if !(1.0..=25.0).contains(&percent) {
+                    return Err(format!("speed step {percent} is outside the range 1 to 25"));
+                }
+                self.ball_speed_step = percent / 100.0;
+            }
+            "speed_ramp_hits" => {
+                self.speed_ramp_hits = value
+                    .parse()
+                    .map_err(|err| format!("invalid hit count `{value}`: {err}"))?;
+            }
+            "speed_ramp_max" => {
+                let percent = value
+                    .parse::<f32>()
+                    .map_err(|err| format!("invalid speed ramp cap `{value}`: {err}"))?;
+                if !(0.0..=100.0).contains(&percent) {
+                    return Err(format!(
+                        "speed ramp cap {percent} is outside the range 0 to 100"
+                    ));
+                }
+                self.speed_ramp_max = percent / 100.0;
+            }
+            "landing_marker" => {
+                self.landing_marker = parse_bool(value)?;
+            }
+            "aim_assist" => {
+                self.aim_assist = parse_bool(value)?;
+            }
+            "auto_move" => {
+                self.auto_move = parse_bool(value)?;
+            }
+            "elevator_paddles" => {
+                self.elevator_paddles = parse_bool(value)?;
+            }
+            "classic_paddle" => {
+                self.classic_paddle = parse_bool(value)?;
+            }
+            "ball_light" => {
+                self.ball_light = parse_bool(value)?;
+            }
+            "instant_replay" => {
+                self.instant_replay = parse_bool(value)?;
+            }
+            "replay_zoom" => {
+                self.replay_zoom = parse_bool(value)?;
+            }
+            "speedrun_timer" => {
+                self.speedrun_timer = parse_bool(value)?;
+            }
+            "gravity" => {
+                self.gravity = parse_bool(value)?;
+            }
+            "sound_cues" => {
+                self.sound_cues = parse_bool(value)?;
+            }
+            "show_hitboxes" => {
+                self.show_hitboxes = parse_bool(value)?;
+            }
+            "theme" => {
+                self.theme = parse_named(&Theme::ALL, Theme::name, value)
+                    .ok_or_else(|| format!("unknown theme `{value}`"))?;
+            }
+            "paddle_shape" => {
+                self.paddle_shape = parse_named(&PaddleShape::ALL, PaddleShape::name, value)
+                    .ok_or_else(|| format!("unknown paddle shape `{value}`"))?;
+            }
+            key => return Err(format!("unknown key `{key}`")),
+        }
+        Ok(())
+    }
+
+    /// Writes every setting in the form `load` reads
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        let text = self.to_text().map_err(std::io::Error::other)?;
+        versioned::write(path, &FORMAT, &text)
+    }
+
+    fn to_text(&self) -> Result<String, std::fmt::Error> {
+        let mut text = String::new();
+        writeln!(text, "serve_angle_min = {}", self.serve_angle_min)?;
+        writeln!(text, "serve_angle_max = {}", self.serve_angle_max)?;
+        writeln!(text, "game_speed = {}", self.game_speed)?;
+        writeln!(text, "controls = {}", self.controls.name())?;
+        writeln!(text, "difficulty = {}", self.difficulty.name())?;
+        writeln!(text, "theme = {}", self.theme.name())?;
+        writeln!(text, "landing_marker = {}", self.landing_marker)?;
+        writeln!(text, "aim_assist = {}", self.aim_assist)?;
+        writeln!(text, "auto_move = {}", self.auto_move)?;
+        writeln!(
+            text,
+            "paddle_edge_angle = {}",
+            self.paddle_edge_angle.to_degrees().round()
+        )?;
+        writeln!(
+            text,
+            "ball_speed_step = {}",
+            (self.ball_speed_step * 100.0).round()
+        )?;
+        writeln!(text, "elevator_paddles = {}", self.elevator_paddles)?;
+        writeln!(text, "paddle_shape = {}", self.paddle_shape.name())?;
+        writeln!(text, "classic_paddle = {}", self.classic_paddle)?;
+        writeln!(text, "ball_light = {}", self.ball_light)?;
+        writeln!(text, "instant_replay = {}", self.instant_replay)?;
+        writeln!(text, "replay_zoom = {}", self.replay_zoom)?;
+        writeln!(text, "speedrun_timer = {}", self.speedrun_timer)?;
+        writeln!(text, "speed_ramp_hits = {}", self.speed_ramp_hits)?;
+        writeln!(text, "speed_ramp_max = {}", self.speed_ramp_max * 100.0)?;
+        writeln!(text, "gravity = {}", self.gravity)?;
+        writeln!(text, "sound_cues = {}", self.sound_cues)?;
+        writeln!(text, "show_hitboxes = {}", self.show_hitboxes)?;
+        if let Some(seed) = self.seed {
+            writeln!(text, "seed = {seed}")?;
+        }
+        if let Some(sound_pack) = &self.sound_pack {
+            writeln!(text, "sound_pack = {sound_pack}")?;
+        }
+        if let Some((x, y)) = self.window_position {
+            writeln!(text, "window_position = {x}, {y}")?;
+        }
+        Ok(text)
+    }
+
+    /// Records the window position in the config file at `path`, leaving
+    /// every other line as the player wrote it
+    pub fn save_window_position(
+        path: &Path,
+        position: Option<(isize, isize)>,
+    ) -> std::io::Result<()> {
+        let text = versioned::read(path, &FORMAT)?;
+        let is_position = |line: &str| {
+            line.split_once('=')
+                .is_some_and(|(key, _)| key.trim() == "window_position")
+        };
+        let mut position_line = position.map(|(x, y)| format!("window_position = {x}, {y}"));
+        let mut lines = Vec::new();
+        for line in text.lines() {
+            if !is_position(line) {
+                lines.push(String::from(line));
+            } else if let Some(position_line) = position_line.take() {
+                // Where the old position was
+                lines.push(position_line);
+            }
+        }
+        lines.extend(position_line);
+        let mut text = lines.join("\n");
+        text.push('\n');
+        versioned::write(path, &FORMAT, &text)
+    }
+
+    pub fn rng(&self) -> Rng {
+        self.seed.map_or_else(Rng::from_time, Rng::new)
+    }
+}
+
+/// Serves must go upwards without being vertical or horizontal
+fn parse_angle(value: &str) -> Result<f32, String> {
+    let angle = value
+        .parse::<f32>()
+        .map_err(|err| format!("invalid angle `{value}`: {err}"))?;
+    if (5.0..=85.0).contains(&angle) {
+        Ok(angle)
+    } else {
+        Err(format!(
+            "angle {angle} is outside the range 5 to 85 degrees"
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn saved_settings_load_back_the_same() {
+        let path = std::env::temp_dir().join(format!("breakrs-saved-{}.txt", std::process::id()));
+        let config = Config {
+            seed: Some(7),
+            sound_pack: Some(String::from("retro")),
+            window_position: Some((10, 20)),
+            gravity: true,
+            ..Config::default()
+        };
+        config.save(&path).unwrap();
+        let loaded = Config::load(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.to_text(), config.to_text());
+    }
+
+    #[test]
+    fn saving_the_window_position_keeps_hand_edits() {
+        let path = std::env::temp_dir().join(format!("breakrs-config-{}.txt", std::process::id()));
+        let hand_written =
+            "# my settings\ngame_speed = 1.5\nwindow_position = 1, 2\n  not a setting\n";
+        versioned::write(&path, &FORMAT, hand_written).unwrap();
+
+        Config::save_window_position(&path, Some((30, 40))).unwrap();
+        let text = versioned::read(&path, &FORMAT).unwrap();
+        assert_eq!(
+            text,
+            "# my settings\ngame_speed = 1.5\nwindow_position = 30, 40\n  not a setting\n"
+        );
+
+        Config::save_window_position(&path, None).unwrap();
+        let text = versioned::read(&path, &FORMAT).unwrap();
+        assert_eq!(text, "# my settings\ngame_speed = 1.5\n  not a setting\n");
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,168 @@
+//! The daily challenge: one level made up from the date, the same for
+//! everyone on the same day, with its own best score for the day.
+//!
+//! Days start at midnight UTC, so players in different time zones still
+//! share a level.
+
+use crate::audio::Theme;
+use crate::clock::seconds;
+use crate::level::{Cell, Layout, Level};
+use crate::physics::PlayField;
+use crate::rng::Rng;
+use crate::{PowerUp, ROW_STYLES, STANDARD_COLUMNS};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const MIN_ROWS: usize = 4;
+const MAX_ROWS: usize = 6;
+// Out of 100, how often a spot in the grid is each kind of brick. The rest
+// are one hit bricks.
+const EMPTY_CHANCE: usize = 15;
+const EXPLOSIVE_CHANCE: usize = 5;
+const METAL_CHANCE: usize = 5;
+const TOUGH_CHANCE: usize = 20;
+// Bricks taking more than one hit take this many
+const TOUGH_HIT_POINTS: u8 = 2;
+// Pairs of power-up bricks, one on each side
+const POWER_UP_PAIRS: usize = 2;
+const PAR_TICKS: u32 = seconds(120);
+
+/// Today's level, generated once and kept for as long as the day lasts
+pub struct Daily {
+    pub day: u32,
+    pub level: &'static Level,
+}
+
+impl Daily {
+    pub fn new(day: u32) -> Daily {
+        // Generated levels last as long as the game, like level files
+        Daily {
+            day,
+            level: Box::leak(Box::new(generate(day))),
+        }
+    }
+}
+
+/// Days since 1970-01-01, in UTC
+pub fn today() -> u32 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| (elapsed.as_secs() / 86_400) as u32)
+}
+
+/// A day as `YYYY-MM-DD`
+pub fn format_day(day: u32) -> String {
+    // Counting from March makes the leap day the last day of the year
+    let days = i64::from(day) + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day_of_month = day_of_year - (153 * month_from_march + 2) / 5 + 1;
+    let month = if month_from_march < 10 {
+        month_from_march + 3
+    } else {
+        month_from_march - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{year}-{month:02}-{day_of_month:02}")
+}
+
+/// Makes up a level from `day`. The left and right halves mirror each
+/// other, like the built in levels.
+fn generate(day: u32) -> Level {
+    // Spread the bits of neighbouring days apart, so they don't start out
+    // looking alike
+    let mut rng = Rng::new(u64::from(day).wrapping_mul(0xD6E8_FEB8_6659_FD93));
+    let rows = MIN_ROWS + rng.below(MAX_ROWS - MIN_ROWS + 1);
+    let columns = STANDARD_COLUMNS;
+
+    let mut cells = vec![vec![Cell::Empty; columns]; rows];
+    for row in &mut cells {
+        for column in 0..columns.div_ceil(2) {
+            let roll = rng.below(100);
+            let cell = if roll < EMPTY_CHANCE {
+                Cell::Empty
+            } else if roll < EMPTY_CHANCE + EXPLOSIVE_CHANCE {
+                Cell::Explosive
+            } else if roll < EMPTY_CHANCE + EXPLOSIVE_CHANCE + METAL_CHANCE {
+                Cell::Metal
+            } else if roll < EMPTY_CHANCE + EXPLOSIVE_CHANCE + METAL_CHANCE + TOUGH_CHANCE {
+                Cell::Brick {
+                    hit_points: TOUGH_HIT_POINTS,
+                }
+            } else {
+                Cell::Brick { hit_points: 1 }
+            };
+            row[column] = cell;
+            row[columns - 1 - column] = cell;
+        }
+    }
+    let breakable = |cell: &Cell| matches!(cell, Cell::Brick { .. } | Cell::Explosive);
+    if !cells.iter().flatten().any(breakable) {
+        cells[0][columns / 2] = Cell::Brick { hit_points: 1 };
+    }
+
+    // Power-ups only go in bricks on the left half, mirrored onto the right
+    let spots = cells
+        .iter()
+        .enumerate()
+        .flat_map(|(row, cells)| {
+            cells[..columns / 2]
+                .iter()
+                .enumerate()
+                .filter(|(_, cell)| breakable(cell))
+                .map(move |(column, _)| (row, column))
+        })
+        .collect::<Vec<_>>();
+    let helpful = PowerUp::ALL
+        .into_iter()
+        .filter(|power_up| !power_up.is_debuff())
+        .collect::<Vec<_>>();
+    let mut power_ups = Vec::new();
+    for _ in 0..POWER_UP_PAIRS.min(spots.len()) {
+        let (row, column) = spots[rng.below(spots.len())];
+        if power_ups.iter().any(|&(r, c, _)| (r, c) == (row, column)) {
+            continue;
+        }
+        let power_up = helpful[rng.below(helpful.len())];
+        power_ups.push((row, column, power_up));
+        power_ups.push((row, columns - 1 - column, power_up));
+    }
+
+    let styles = &ROW_STYLES[..rows];
+    let par_score = cells
+        .iter()
+        .zip(styles)
+        .map(|(cells, &(_, points))| {
+            cells.iter().filter(|cell| breakable(cell)).count() as u32 * points
+        })
+        .sum();
+
+    Level {
+        name: format!("Daily {}", format_day(day)).leak(),
+        rows,
+        par_score,
+        par_ticks: PAR_TICKS,
+        scripts: &[],
+        field: PlayField::FULL,
+        hit_points: &[],
+        metal: &[],
+        materials: &[],
+        layout: Some(Layout {
+            cells: cells
+                .into_iter()
+                .map(|row| &*row.leak())
+                .collect::<Vec<_>>()
+                .leak(),
+            styles,
+            power_ups: power_ups.leak(),
+        }),
+        ball_speed: 1.0,
+        score_zones: &[],
+        secrets: &[],
+        theme: Theme::DEFAULT,
+        boss: None,
+    }
+}
@@ -0,0 +1,293 @@
+//! `breakrs record-demo` plays a seeded run without a window, with the
+//! paddle following the ball, and saves it as an animated GIF for the README
+//! and the website. The same seed always makes the same GIF, so the capture
+//! can be made again whenever the game's look changes.
+//!
+//! The run is played twice: the first time to find the colors it uses, the
+//! second to write the frames with a palette made from the most common ones.
+
+use crate::assets::Handle;
+use crate::autopilot::Autopilot;
+use crate::clock::{seconds, TICKS_PER_SECOND};
+use crate::config::Config;
+use crate::gif::{FrameRect, GifWriter};
+use crate::loading::load_font;
+use crate::{draw_subcanvas_scaled, Canvas, GameState, Res, RunMode, ScaleFilter, HEIGHT, WIDTH};
+use rusttype::Font;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::PathBuf;
+
+const DEFAULT_SEED: u64 = 1;
+const DEFAULT_SECONDS: u32 = 30;
+const DEFAULT_OUTPUT: &str = "demo.gif";
+// Every this many ticks becomes a frame, which is plenty to look smooth
+const TICKS_PER_FRAME: u32 = 2;
+// The GIF is this many times smaller than the window each way
+const SCALE: usize = 2;
+// Pixels that are the same as in the frame before are left this color
+const TRANSPARENT: u8 = 255;
+
+const GIF_WIDTH: usize = WIDTH / SCALE;
+const GIF_HEIGHT: usize = HEIGHT / SCALE;
+
+/// Records the demo with the options in `args`
+pub fn run_cli(args: &[String]) -> Res<()> {
+    let mut seed = DEFAULT_SEED;
+    let mut length = DEFAULT_SECONDS;
+    let mut output = PathBuf::from(DEFAULT_OUTPUT);
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let value = args.next();
+        let parsed = match (arg.as_str(), value) {
+            ("--seed", Some(value)) => value.parse().map(|value| seed = value).is_ok(),
+            ("--seconds", Some(value)) => value
+                .parse()
+                .map(|value| length = value)
+                .is_ok_and(|_| length > 0),
+            ("--output", Some(value)) => {
+                output = PathBuf::from(value);
+                true
+            }
+            _ => false,
+        };
+        if !parsed {
+            eprintln!(
+                "ERROR! Usage: breakrs record-demo [--seed <seed>] [--seconds <seconds>] [--output <file>]"
+            );
+            return Err(());
+        }
+    }
+
+    let font = load_font().map_err(|err| eprintln!("ERROR! {err}"))?;
+    let ticks = seconds(length);
+
+    let mut counts = HashMap::<u32, u32>::new();
+    play(seed, ticks, &font, |_, picture| {
+        for &color in picture {
+            *counts.entry(color).or_default() += 1;
+        }
+    });
+    let palette = Palette::from_counts(&counts);
+
+    let file = File::create(&output)
+        .map_err(|err| eprintln!("ERROR! Could not create {output:?}: {err}"))?;
+    let mut encoder = Encoder {
+        gif: GifWriter::new(
+            BufWriter::new(file),
+            GIF_WIDTH as u16,
+            GIF_HEIGHT as u16,
+            &palette.colors,
+        )
+        .map_err(|err| eprintln!("ERROR! Could not write {output:?}: {err}"))?,
+        palette,
+        shown: None,
+        pending: None,
+        frames: 0,
+    };
+    let mut failed = None;
+    play(seed, ticks, &font, |tick, picture| {
+        if failed.is_none() {
+            failed = encoder.add(tick, picture).err();
+        }
+    });
+    let frames = encoder.frames;
+    let written = match failed {
+        Some(err) => Err(err),
+        None => encoder.finish(ticks),
+    };
+    written.map_err(|err| eprintln!("ERROR! Could not write {output:?}: {err}"))?;
+
+    let size = std::fs::metadata(&output).map_or(0, |metadata| metadata.len());
+    println!(
+        "Recorded {length} seconds with seed {seed} as {frames} frames, {} kB, to {output:?}",
+        size / 1024
+    );
+    Ok(())
+}
+
+/// Plays a campaign run from the first level for `ticks` ticks, handing
+/// every frame that goes in the GIF to `on_frame`, shrunk to the GIF's size
+fn play(
+    seed: u64,
+    ticks: u32,
+    font: &Handle<Font<'static>>,
+    mut on_frame: impl FnMut(u32, &[u32]),
+) {
+    let mut state = GameState {
+        config: Config {
+            seed: Some(seed),
+            ..Config::default()
+        },
+        font: Some(font.clone()),
+        ..GameState::default()
+    };
+    state.start_run(RunMode::Campaign, 0);
+    let mut canvas = Canvas {
+        buffer: vec![0; WIDTH * HEIGHT],
+        stride: WIDTH,
+    };
+    let mut picture = Canvas {
+        buffer: vec![0; GIF_WIDTH * GIF_HEIGHT],
+        stride: GIF_WIDTH,
+    };
+    let mut autopilot = Autopilot::new(seed.wrapping_add(1));
+
+    for tick in 0..ticks {
+        autopilot.steer(&mut state);
+        state.tick();
+        if tick % TICKS_PER_FRAME == 0 {
+            state.draw_scene(&mut canvas);
+            // Blending the pixels in each block keeps thin lines and text
+            // readable at the smaller size
+            let bounds = picture.bounds();
+            draw_subcanvas_scaled(&mut picture, &canvas, bounds, ScaleFilter::Bilinear);
+            on_frame(tick, &picture.buffer);
+        }
+    }
+}
+
+/// The most common colors of the run, leaving one index for transparency
+struct Palette {
+    colors: Vec<u32>,
+    // Which index each color seen so far is drawn with
+    indices: HashMap<u32, u8>,
+}
+
+impl Palette {
+    fn from_counts(counts: &HashMap<u32, u32>) -> Self {
+        let mut common = counts.iter().collect::<Vec<_>>();
+        // Ties go to the smaller color so the palette is the same every time
+        common.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+        let colors = common
+            .into_iter()
+            .take(TRANSPARENT as usize)
+            .map(|(&color, _)| color)
+            .collect::<Vec<_>>();
+        let indices = colors
+            .iter()
+            .enumerate()
+            .map(|(index, &color)| (color, index as u8))
+            .collect();
+        Palette { colors, indices }
+    }
+
+    /// The index of `color`, or of the nearest color in the palette
+    fn index(&mut self, color: u32) -> u8 {
+        if let Some(&index) = self.indices.get(&color) {
+            return index;
+        }
+        let channels = |color: u32| [(color >> 16) & 0xFF, (color >> 8) & 0xFF, color & 0xFF];
+        let wanted = channels(color);
+        let distance = |other: u32| {
+            channels(other)
+                .iter()
+                .zip(wanted)
+                .map(|(&a, b)| a.abs_diff(b).pow(2))
+                .sum::<u32>()
+        };
+        let index = (0..self.colors.len())
+            .min_by_key(|&index| distance(self.colors[index]))
+            .unwrap_or(0) as u8;
+        self.indices.insert(color, index);
+        index
+    }
+}
+
+/// Turns pictures into GIF frames that only hold what changed, holding back
+/// each frame until the next different one shows how long it stays up
+struct Encoder {
+    gif: GifWriter<BufWriter<File>>,
+    palette: Palette,
+    // The picture as it looks once every frame so far is drawn
+    shown: Option<Vec<u8>>,
+    // A frame waiting to be written, with the tick it went up on
+    pending: Option<(Vec<u8>, FrameRect, u32)>,
+    frames: u32,
+}
+
+impl Encoder {
+    fn add(&mut self, tick: u32, picture: &[u32]) -> std::io::Result<()> {
+        let indices = picture
+            .iter()
+            .map(|&color| self.palette.index(color))
+            .collect::<Vec<_>>();
+        let (pixels, rect) = match &self.shown {
+            None => (indices.clone(), full_rect()),
+            Some(shown) => {
+                let Some(rect) = changed_rect(shown, &indices) else {
+                    // Nothing changed, so the frame before just stays up longer
+                    return Ok(());
+                };
+                (changes(shown, &indices, rect), rect)
+            }
+        };
+        self.write_pending(tick)?;
+        self.pending = Some((pixels, rect, tick));
+        self.shown = Some(indices);
+        Ok(())
+    }
+
+    fn finish(mut self, end_tick: u32) -> std::io::Result<()> {
+        self.write_pending(end_tick)?;
+        self.gif.finish().map(|_| ())
+    }
+
+    fn write_pending(&mut self, until: u32) -> std::io::Result<()> {
+        let Some((pixels, rect, since)) = self.pending.take() else {
+            return Ok(());
+        };
+        // Rounded from the start of the run so the delays don't drift
+        let centiseconds = |tick: u32| tick * 100 / TICKS_PER_SECOND;
+        let delay = (centiseconds(until) - centiseconds(since)).min(u16::MAX as u32) as u16;
+        let transparent = (self.frames > 0).then_some(TRANSPARENT);
+        self.gif.frame(&pixels, rect, delay, transparent)?;
+        self.frames += 1;
+        Ok(())
+    }
+}
+
+fn full_rect() -> FrameRect {
+    FrameRect {
+        x: 0,
+        y: 0,
+        width: GIF_WIDTH as u16,
+        height: GIF_HEIGHT as u16,
+    }
+}
+
+/// The smallest rectangle holding every pixel that differs, if any do
+fn changed_rect(before: &[u8], after: &[u8]) -> Option<FrameRect> {
+    let (mut left, mut top) = (GIF_WIDTH, GIF_HEIGHT);
+    let (mut right, mut bottom) = (0, 0);
+    for (i, (a, b)) in before.iter().zip(after).enumerate() {
+        if a != b {
+            let (x, y) = (i % GIF_WIDTH, i / GIF_WIDTH);
+            left = left.min(x);
+            right = right.max(x);
+            top = top.min(y);
+            bottom = bottom.max(y);
+        }
+    }
+    (left <= right).then(|| FrameRect {
+        x: left as u16,
+        y: top as u16,
+        width: (right - left + 1) as u16,
+        height: (bottom - top + 1) as u16,
+    })
+}
+
+/// The pixels of `after` inside `rect`, with those that match `before` left
+/// transparent
+fn changes(before: &[u8], after: &[u8], rect: FrameRect) -> Vec<u8> {
+    let mut pixels = Vec::with_capacity(rect.width as usize * rect.height as usize);
+    for y in rect.y as usize..(rect.y + rect.height) as usize {
+        let row = y * GIF_WIDTH;
+        for x in rect.x as usize..(rect.x + rect.width) as usize {
+            let (a, b) = (before[row + x], after[row + x]);
+            pixels.push(if a == b { TRANSPARENT } else { b });
+        }
+    }
+    pixels
+}
@@ -0,0 +1,136 @@
+//! `breakrs verify-determinism` plays the same seeded run with the same
+//! scripted input twice, without a window, and compares a hash of the game
+//! state after every frame. Replays and netplay only work if the two runs
+//! never diverge.
+
+use crate::clock::seconds;
+use crate::config::Config;
+use crate::rng::Rng;
+use crate::{GameState, Res, RunMode};
+use minifb::Key;
+use std::hash::{Hash, Hasher};
+
+const DEFAULT_SEED: u64 = 1;
+const DEFAULT_FRAMES: u32 = seconds(120);
+// The scripted player changes direction this often
+const INPUT_INTERVAL_FRAMES: u32 = 20;
+// And fires the laser about once in this many frames
+const FIRE_ONE_IN: usize = 30;
+
+/// Runs the check with the options in `args`, reporting the result on
+/// stdout. Fails if the runs diverge.
+pub fn run_cli(args: &[String]) -> Res<()> {
+    let mut seed = DEFAULT_SEED;
+    let mut frames = DEFAULT_FRAMES;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let value = args.next();
+        let parsed = match (arg.as_str(), value) {
+            ("--seed", Some(value)) => value.parse().map(|value| seed = value).is_ok(),
+            ("--frames", Some(value)) => value.parse().map(|value| frames = value).is_ok(),
+            _ => false,
+        };
+        if !parsed {
+            eprintln!(
+                "ERROR! Usage: breakrs verify-determinism [--seed <seed>] [--frames <frames>]"
+            );
+            return Err(());
+        }
+    }
+
+    let first = run(seed, frames);
+    let second = run(seed, frames);
+    match first.iter().zip(&second).position(|(a, b)| a != b) {
+        Some(frame) => {
+            println!(
+                "Runs with seed {seed} diverged at frame {frame}: {:016x} vs {:016x}",
+                first[frame], second[frame]
+            );
+            Err(())
+        }
+        None => {
+            println!("Runs with seed {seed} matched for all {frames} frames");
+            Ok(())
+        }
+    }
+}
+
+/// Plays a campaign run from the first level, returning the state hash
+/// after each frame
+fn run(seed: u64, frames: u32) -> Vec<u64> {
+    let mut state = GameState {
+        config: Config {
+            seed: Some(seed),
+            ..Config::default()
+        },
+        ..GameState::default()
+    };
+    state.start_run(RunMode::Campaign, 0);
+
+    // The scripted input has its own generator so that it doesn't disturb
+    // the game's
+    let mut input = Rng::new(seed.wrapping_add(1));
+    (0..frames)
+        .map(|frame| {
+            if frame % INPUT_INTERVAL_FRAMES == 0 {
+                let direction = input.below(3) as f32 - 1.0;
+                state.paddle_vel_x = direction * state.paddle_movement_speed;
+            }
+            if input.below(FIRE_ONE_IN) == 0 {
+                state.key_pressed(Key::Space);
+            }
+            state.tick();
+            state_hash(&state)
+        })
+        .collect()
+}
+
+/// Hashes everything that affects how play continues. Cheap enough to show
+/// every frame in the debug stats
+pub fn state_hash(state: &GameState) -> u64 {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    std::mem::discriminant(&state.scene).hash(&mut hasher);
+    for ball in &state.balls {
+        for value in [ball.pos.x, ball.pos.y, ball.vel.x, ball.vel.y] {
+            value.to_bits().hash(&mut hasher);
+        }
+        ball.held.is_some().hash(&mut hasher);
+    }
+    for value in [state.paddle_pos.x, state.paddle_pos.y, state.paddle_width] {
+        value.to_bits().hash(&mut hasher);
+    }
+    for capsule in &state.capsules {
+        capsule.pos.x.to_bits().hash(&mut hasher);
+        capsule.pos.y.to_bits().hash(&mut hasher);
+        std::mem::discriminant(&capsule.power_up).hash(&mut hasher);
+    }
+    for bolt in &state.laser_bolts {
+        bolt.pos.x.to_bits().hash(&mut hasher);
+        bolt.pos.y.to_bits().hash(&mut hasher);
+    }
+    for hazard in &state.hazards {
+        hazard.x.to_bits().hash(&mut hasher);
+        hazard.y.to_bits().hash(&mut hasher);
+    }
+    if let Some(boss) = &state.boss {
+        boss.pos.x.to_bits().hash(&mut hasher);
+        boss.health.hash(&mut hasher);
+    }
+    if let Some(barrier) = &state.barrier {
+        barrier.top.to_bits().hash(&mut hasher);
+        barrier.hits_left.hash(&mut hasher);
+    }
+    state.bricks.is_destroyed.hash(&mut hasher);
+    state.bricks.hidden.hash(&mut hasher);
+    state.bricks.hit_points.hash(&mut hasher);
+    for y in &state.bricks.y_positions {
+        y.to_bits().hash(&mut hasher);
+    }
+    state.lives.hash(&mut hasher);
+    state.score.hash(&mut hasher);
+    state.score_bank.points.hash(&mut hasher);
+    state.level_index.hash(&mut hasher);
+    state.clock.run_ticks().hash(&mut hasher);
+    state.rng.hash(&mut hasher);
+    hasher.finish()
+}
@@ -0,0 +1,209 @@
+//! Gamepad input and rumble.
+//!
+//! Buttons are translated into the keys they stand in for, so menus and
+//! gameplay don't need to know where a press came from. Rumble pulses are
+//! only sent to connected gamepads that support force feedback.
+//!
+//! The gamepad played with last is the active one. If it is unplugged the
+//! game is told so that it can pause, and when the same device is plugged
+//! back in it becomes the active one again without any setup.
+
+use gilrs::ff::{BaseEffect, BaseEffectType, Effect, EffectBuilder, Repeat, Replay, Ticks};
+use gilrs::{Axis, Button, EventType, GamepadId, Gilrs};
+use minifb::Key;
+
+// Stick positions closer to the center than this are treated as centered
+const STICK_DEAD_ZONE: f32 = 0.25;
+
+pub enum GamepadInput {
+    Press(Key),
+    // Paddle movement from `-1.0` (full left) to `1.0` (full right)
+    Move(f32),
+    // `returning` if it is the active gamepad coming back after it was
+    // unplugged
+    Connected { name: String, returning: bool },
+    Disconnected { name: String, active: bool },
+}
+
+/// Short vibrations that accompany gameplay events
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Pulse {
+    PaddleHit,
+    BrickBreak,
+    BallLost,
+}
+
+impl Pulse {
+    /// Strong and weak motor magnitudes at full intensity, and duration in
+    /// milliseconds
+    fn shape(self) -> (u16, u16, u32) {
+        match self {
+            Pulse::PaddleHit => (0, 20_000, 60),
+            Pulse::BrickBreak => (12_000, 30_000, 80),
+            Pulse::BallLost => (60_000, 40_000, 400),
+        }
+    }
+}
+
+pub struct Gamepads {
+    gilrs: Gilrs,
+    // Effects stop when dropped, so they are kept until they have finished
+    // along with the number of frames they have left
+    playing: Vec<(Effect, u32)>,
+    active: Option<GamepadId>,
+    // The device the active gamepad was, if it has been unplugged. Ids can
+    // change when a gamepad is plugged back in, so it is recognized by its
+    // UUID instead.
+    lost: Option<[u8; 16]>,
+    // Whether the stick is out of its dead zone and steering the paddle.
+    // Only then does it take over from the keyboard.
+    stick_steering: bool,
+}
+
+impl Gamepads {
+    pub fn new() -> Option<Gamepads> {
+        match Gilrs::new() {
+            Ok(gilrs) => Some(Gamepads {
+                gilrs,
+                playing: Vec::new(),
+                active: None,
+                lost: None,
+                stick_steering: false,
+            }),
+            Err(err) => {
+                eprintln!("ERROR! Gamepads are unavailable: {err}");
+                None
+            }
+        }
+    }
+
+    /// Collects everything the gamepads have done since the last poll
+    pub fn poll(&mut self) -> Vec<GamepadInput> {
+        self.playing.retain_mut(|(_, frames)| {
+            *frames = frames.saturating_sub(1);
+            *frames > 0
+        });
+
+        let mut inputs = Vec::new();
+        while let Some(event) = self.gilrs.next_event() {
+            let id = event.id;
+            match event.event {
+                EventType::Connected => {
+                    let gamepad = self.gilrs.gamepad(id);
+                    let returning = self.lost == Some(gamepad.uuid());
+                    if returning {
+                        self.lost = None;
+                        self.active = Some(id);
+                    }
+                    inputs.push(GamepadInput::Connected {
+                        name: String::from(gamepad.name()),
+                        returning,
+                    });
+                }
+                EventType::Disconnected => {
+                    let gamepad = self.gilrs.gamepad(id);
+                    let active = self.active == Some(id);
+                    if active {
+                        self.active = None;
+                        self.lost = Some(gamepad.uuid());
+                    }
+                    inputs.push(GamepadInput::Disconnected {
+                        name: String::from(gamepad.name()),
+                        active,
+                    });
+                }
+                EventType::ButtonPressed(button, _) => {
+                    self.make_active(id);
+                    let key = match button {
+                        Button::South => Key::Space,
+                        Button::East => Key::Backspace,
+                        Button::Start => Key::P,
+                        Button::DPadUp => Key::Up,
+                        Button::DPadDown => Key::Down,
+                        Button::DPadLeft => {
+                            inputs.push(GamepadInput::Move(-1.0));
+                            Key::Left
+                        }
+                        Button::DPadRight => {
+                            inputs.push(GamepadInput::Move(1.0));
+                            Key::Right
+                        }
+                        _ => continue,
+                    };
+                    inputs.push(GamepadInput::Press(key));
+                }
+                EventType::ButtonReleased(Button::DPadLeft | Button::DPadRight, _) => {
+                    inputs.push(GamepadInput::Move(0.0));
+                }
+                EventType::AxisChanged(Axis::LeftStickX, value, _) => {
+                    if value.abs() < STICK_DEAD_ZONE {
+                        // Drift around the center mustn't stop the paddle
+                        // the keyboard is moving, so the stick only lets go
+                        // once when it comes back
+                        if self.stick_steering {
+                            self.stick_steering = false;
+                            inputs.push(GamepadInput::Move(0.0));
+                        }
+                    } else {
+                        self.make_active(id);
+                        self.stick_steering = true;
+                        inputs.push(GamepadInput::Move(value));
+                    }
+                }
+                _ => (),
+            }
+        }
+        inputs
+    }
+
+    fn make_active(&mut self, id: GamepadId) {
+        self.active = Some(id);
+        // Playing on with another gamepad means the lost one isn't missed
+        self.lost = None;
+    }
+
+    /// Vibrates every connected gamepad that can, scaled by `intensity` in
+    /// `0.0..=1.0`
+    pub fn rumble(&mut self, pulse: Pulse, intensity: f32) {
+        if intensity <= 0.0 {
+            return;
+        }
+        let ids = self
+            .gilrs
+            .gamepads()
+            .filter(|(_, gamepad)| gamepad.is_ff_supported())
+            .map(|(id, _)| id)
+            .collect::<Vec<_>>();
+        if ids.is_empty() {
+            return;
+        }
+
+        let (strong, weak, millis) = pulse.shape();
+        let duration = Ticks::from_ms(millis);
+        let scheduling = Replay {
+            play_for: duration,
+            ..Replay::default()
+        };
+        let effect = EffectBuilder::new()
+            .add_effect(BaseEffect {
+                kind: BaseEffectType::Strong { magnitude: strong },
+                scheduling,
+                ..BaseEffect::default()
+            })
+            .add_effect(BaseEffect {
+                kind: BaseEffectType::Weak { magnitude: weak },
+                scheduling,
+                ..BaseEffect::default()
+            })
+            .repeat(Repeat::For(duration))
+            .gain(intensity)
+            .gamepads(&ids)
+            .finish(&mut self.gilrs)
+            .and_then(|effect| effect.play().map(|()| effect));
+        match effect {
+            // Hold on to the effect a little longer than it plays for
+            Ok(effect) => self.playing.push((effect, millis / 16 + 2)),
+            Err(err) => eprintln!("ERROR! Could not rumble gamepad: {err}"),
+        }
+    }
+}
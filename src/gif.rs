@@ -0,0 +1,265 @@
+//! A minimal animated GIF writer, just enough for `breakrs record-demo`.
+//!
+//! Every frame shares one global palette of up to 256 colors, and frames
+//! only cover the part of the picture that changed since the last one, with
+//! unchanged pixels inside that part left transparent so they compress to
+//! almost nothing.
+
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+// Every frame's pixels are palette indices of this many bits
+const MIN_CODE_SIZE: u8 = 8;
+// LZW codes never get longer than this
+const MAX_CODE_SIZE: u8 = 12;
+// Image data is split into blocks of at most this many bytes
+const MAX_BLOCK: usize = 255;
+
+/// Where a frame goes on the picture, in pixels
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FrameRect {
+    pub x: u16,
+    pub y: u16,
+    pub width: u16,
+    pub height: u16,
+}
+
+pub struct GifWriter<W: Write> {
+    out: W,
+}
+
+impl<W: Write> GifWriter<W> {
+    /// Writes the header for a looping animation of `width` by `height`
+    /// pixels. `palette` holds `0xRRGGBB` colors, at most 256 of them.
+    pub fn new(mut out: W, width: u16, height: u16, palette: &[u32]) -> io::Result<Self> {
+        out.write_all(b"GIF89a")?;
+        out.write_all(&width.to_le_bytes())?;
+        out.write_all(&height.to_le_bytes())?;
+        // A global palette of 256 colors, with 8 bits per channel
+        out.write_all(&[0xF7, 0, 0])?;
+        for index in 0..256 {
+            let color = palette.get(index).copied().unwrap_or(0);
+            out.write_all(&[(color >> 16) as u8, (color >> 8) as u8, color as u8])?;
+        }
+        // Loop forever
+        out.write_all(&[0x21, 0xFF, 0x0B])?;
+        out.write_all(b"NETSCAPE2.0")?;
+        out.write_all(&[0x03, 0x01, 0x00, 0x00, 0x00])?;
+        Ok(GifWriter { out })
+    }
+
+    /// Draws `pixels`, palette indices row by row, over `rect` of the
+    /// picture so far and shows it for `delay` hundredths of a second.
+    /// Pixels of the `transparent` index leave what is already there.
+    pub fn frame(
+        &mut self,
+        pixels: &[u8],
+        rect: FrameRect,
+        delay: u16,
+        transparent: Option<u8>,
+    ) -> io::Result<()> {
+        // Graphic control: keep the frame when the next is drawn over it
+        let flags = (1 << 2) | u8::from(transparent.is_some());
+        self.out.write_all(&[0x21, 0xF9, 0x04, flags])?;
+        self.out.write_all(&delay.to_le_bytes())?;
+        self.out.write_all(&[transparent.unwrap_or(0), 0x00])?;
+
+        self.out.write_all(&[0x2C])?;
+        for value in [rect.x, rect.y, rect.width, rect.height] {
+            self.out.write_all(&value.to_le_bytes())?;
+        }
+        self.out.write_all(&[0x00, MIN_CODE_SIZE])?;
+        for block in lzw_encode(pixels).chunks(MAX_BLOCK) {
+            self.out.write_all(&[block.len() as u8])?;
+            self.out.write_all(block)?;
+        }
+        self.out.write_all(&[0x00])
+    }
+
+    /// Ends the file
+    pub fn finish(mut self) -> io::Result<W> {
+        self.out.write_all(&[0x3B])?;
+        self.out.flush()?;
+        Ok(self.out)
+    }
+}
+
+/// Compresses palette indices the way GIF image data is compressed
+fn lzw_encode(pixels: &[u8]) -> Vec<u8> {
+    let clear = 1u16 << MIN_CODE_SIZE;
+    let end = clear + 1;
+    let mut bits = BitWriter::default();
+    let mut code_size = MIN_CODE_SIZE + 1;
+    let mut codes = HashMap::<(u16, u8), u16>::new();
+    let mut next_code = end + 1;
+
+    bits.write(clear, code_size);
+    let mut pixels = pixels.iter();
+    if let Some(&first) = pixels.next() {
+        let mut prefix = u16::from(first);
+        for &pixel in pixels {
+            if let Some(&code) = codes.get(&(prefix, pixel)) {
+                prefix = code;
+                continue;
+            }
+            bits.write(prefix, code_size);
+            if next_code == 1 << MAX_CODE_SIZE {
+                // The table is full, so start it again
+                bits.write(clear, code_size);
+                codes.clear();
+                next_code = end + 1;
+                code_size = MIN_CODE_SIZE + 1;
+            } else {
+                codes.insert((prefix, pixel), next_code);
+                next_code += 1;
+                // Decoders add each code a step later than this, so codes
+                // only get longer once the one after the largest that fits
+                // has been added
+                if next_code > 1 << code_size {
+                    code_size += 1;
+                }
+            }
+            prefix = u16::from(pixel);
+        }
+        bits.write(prefix, code_size);
+    }
+    bits.write(end, code_size);
+    bits.finish()
+}
+
+/// Packs codes into bytes, least significant bit first
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    pending: u32,
+    pending_bits: u8,
+}
+
+impl BitWriter {
+    fn write(&mut self, code: u16, size: u8) {
+        self.pending |= u32::from(code) << self.pending_bits;
+        self.pending_bits += size;
+        while self.pending_bits >= 8 {
+            self.bytes.push(self.pending as u8);
+            self.pending >>= 8;
+            self.pending_bits -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.pending_bits > 0 {
+            self.bytes.push(self.pending as u8);
+        }
+        self.bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    /// Undoes [`lzw_encode`] the way a GIF decoder reads image data
+    fn lzw_decode(data: &[u8]) -> Vec<u8> {
+        let clear = 1usize << MIN_CODE_SIZE;
+        let end = clear + 1;
+        let fresh_table = || {
+            let mut table: Vec<Vec<u8>> = (0..=255).map(|byte| vec![byte]).collect();
+            // The clear and end codes have no pixels
+            table.extend([Vec::new(), Vec::new()]);
+            table
+        };
+        let mut table = fresh_table();
+        let mut code_size = MIN_CODE_SIZE + 1;
+        let mut previous: Option<usize> = None;
+        let mut pixels = Vec::new();
+        let (mut pending, mut pending_bits, mut bytes) = (0u32, 0u8, data.iter());
+        loop {
+            while pending_bits < code_size {
+                let byte = bytes.next().expect("image data ended without an end code");
+                pending |= u32::from(*byte) << pending_bits;
+                pending_bits += 8;
+            }
+            let code = (pending & ((1 << code_size) - 1)) as usize;
+            pending >>= code_size;
+            pending_bits -= code_size;
+
+            if code == clear {
+                table = fresh_table();
+                code_size = MIN_CODE_SIZE + 1;
+                previous = None;
+                continue;
+            }
+            if code == end {
+                return pixels;
+            }
+            let entry = match (table.get(code), previous) {
+                (Some(entry), _) => entry.clone(),
+                // The code being defined by this very step
+                (None, Some(previous)) if code == table.len() => {
+                    let mut entry = table[previous].clone();
+                    entry.push(entry[0]);
+                    entry
+                }
+                _ => panic!("code {code} is not in the table"),
+            };
+            pixels.extend_from_slice(&entry);
+            if let Some(previous) = previous {
+                if table.len() < 1 << MAX_CODE_SIZE {
+                    let mut added = table[previous].clone();
+                    added.push(entry[0]);
+                    table.push(added);
+                    if table.len() == 1 << code_size && code_size < MAX_CODE_SIZE {
+                        code_size += 1;
+                    }
+                }
+            }
+            previous = Some(code);
+        }
+    }
+
+    #[test]
+    fn encodes_a_tiny_image_exactly() {
+        // Clear, 0, then the code for "0 0", 0 and end, nine bits each
+        assert_eq!(lzw_encode(&[0; 4]), [0x00, 0x01, 0x08, 0x04, 0x10, 0x10]);
+    }
+
+    #[test]
+    fn round_trips_runs_and_noise() {
+        let mut rng = Rng::new(7);
+        let mut pixels = vec![3; 500];
+        // Enough varied pixels to fill the code table and start it again
+        pixels.extend((0..40_000).map(|_| rng.below(7) as u8));
+        pixels.extend((0..=255).cycle().take(3000));
+        assert_eq!(lzw_decode(&lzw_encode(&pixels)), pixels);
+        assert_eq!(lzw_decode(&lzw_encode(&[])), Vec::<u8>::new());
+        assert_eq!(lzw_decode(&lzw_encode(&[42])), [42]);
+    }
+
+    #[test]
+    fn writes_a_tiny_file_exactly() {
+        let mut gif = GifWriter::new(Vec::new(), 2, 2, &[0xFF0000, 0x00FF00]).unwrap();
+        let rect = FrameRect {
+            x: 0,
+            y: 0,
+            width: 2,
+            height: 2,
+        };
+        gif.frame(&[0; 4], rect, 5, Some(1)).unwrap();
+        let bytes = gif.finish().unwrap();
+
+        let mut expected = b"GIF89a".to_vec();
+        expected.extend([2, 0, 2, 0, 0xF7, 0, 0]);
+        expected.extend([0xFF, 0, 0, 0, 0xFF, 0]);
+        expected.extend([0; 254 * 3]);
+        expected.extend([0x21, 0xFF, 0x0B]);
+        expected.extend(b"NETSCAPE2.0");
+        expected.extend([0x03, 0x01, 0x00, 0x00, 0x00]);
+        // Graphic control with a delay of 5 and index 1 transparent
+        expected.extend([0x21, 0xF9, 0x04, 0x05, 5, 0, 1, 0]);
+        expected.extend([0x2C, 0, 0, 0, 0, 2, 0, 2, 0, 0x00, 8]);
+        expected.extend([6, 0x00, 0x01, 0x08, 0x04, 0x10, 0x10, 0x00]);
+        expected.push(0x3B);
+        assert_eq!(bytes, expected);
+    }
+}
@@ -0,0 +1,101 @@
+use minifb::Key;
+
+// Held keys that repeat wait this many frames before the first repeat and
+// then repeat every few frames, like typing in a text box
+const REPEAT_DELAY_FRAMES: u32 = 24;
+const REPEAT_INTERVAL_FRAMES: u32 = 6;
+
+// With auto-move on, the paddle follows the ball at this fraction of its
+// full speed, and at full speed for a while after a nudge
+const AUTO_MOVE_SPEED: f32 = 0.35;
+const NUDGE_TICKS: u32 = 20;
+
+/// Keys that keep firing while held. Everything else fires once per press.
+fn repeats(key: Key) -> bool {
+    matches!(
+        key,
+        Key::Up
+            | Key::Down
+            | Key::Left
+            | Key::Right
+            | Key::W
+            | Key::A
+            | Key::S
+            | Key::D
+            | Key::Equal
+            | Key::Minus
+            | Key::NumPadPlus
+            | Key::NumPadMinus
+    )
+}
+
+/// Turns the keys held down each frame into key presses: one when a key
+/// goes down and, for keys that repeat, more at a steady rate while it stays
+/// down. Presses don't depend on the frame rate or the platform's own key
+/// repeat settings.
+#[derive(Default)]
+pub struct KeyPresses {
+    // Keys that were down last frame and how many frames they've been down
+    held: Vec<(Key, u32)>,
+}
+
+impl KeyPresses {
+    pub fn update(&mut self, down: &[Key]) -> Vec<Key> {
+        let previous = std::mem::take(&mut self.held);
+        let mut presses = Vec::new();
+        for &key in down {
+            let frames = previous
+                .iter()
+                .find(|(held, _)| *held == key)
+                .map_or(0, |(_, frames)| frames + 1);
+            let repeat = frames >= REPEAT_DELAY_FRAMES
+                && (frames - REPEAT_DELAY_FRAMES).is_multiple_of(REPEAT_INTERVAL_FRAMES);
+            if frames == 0 || (repeat && repeats(key)) {
+                presses.push(key);
+            }
+            self.held.push((key, frames));
+        }
+        presses
+    }
+}
+
+/// Moves the paddle for players who can't steer it, for one-handed and
+/// single-switch play. It sits between the controls and the paddle: the
+/// paddle drifts towards where the ball is headed, and pressing the one
+/// switch nudges it along at full speed for a moment. Steering with the
+/// usual controls takes over whenever they are used.
+#[derive(Default)]
+pub struct AutoMove {
+    nudge_ticks: u32,
+}
+
+impl AutoMove {
+    pub fn nudge(&mut self) {
+        self.nudge_ticks = NUDGE_TICKS;
+    }
+
+    /// The paddle velocity for a tick, from the velocity the controls are
+    /// asking for, where the middle of the paddle is and where it should go
+    pub fn velocity(
+        &mut self,
+        steered: f32,
+        center: f32,
+        target: Option<f32>,
+        max_speed: f32,
+    ) -> f32 {
+        let nudging = self.nudge_ticks > 0;
+        self.nudge_ticks = self.nudge_ticks.saturating_sub(1);
+        if steered != 0.0 {
+            return steered;
+        }
+        let Some(target) = target else {
+            return 0.0;
+        };
+        let speed = if nudging {
+            max_speed
+        } else {
+            max_speed * AUTO_MOVE_SPEED
+        };
+        (target - center).clamp(-speed, speed)
+    }
+}
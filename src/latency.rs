@@ -0,0 +1,127 @@
+//! `breakrs latency-test` measures how long a key press takes to reach the
+//! screen. It opens a window like the game's and reads keys through the same
+//! input layer. The frame after a press is seen fills a corner of the
+//! screen white, and the time from the poll that saw the press to the end of
+//! presenting that frame is logged, along with how much of it was spent
+//! waiting on the frame pacer. Filming the flash alongside the key shows
+//! what the display adds on top.
+//!
+//! Each press is also checked against the raw key events, so that a press
+//! the input layer reports a frame late shows up in the log.
+
+use crate::input::KeyPresses;
+use crate::{create_window, draw_rect, present, Canvas, Res, HEIGHT, WIDTH};
+use minifb::{Key, KeyRepeat};
+use std::time::{Duration, Instant};
+
+const TITLE: &str = "BREAKRS latency test - press keys, Esc to stop";
+const BACKGROUND: u32 = 0x202020;
+const FLASH_COLOR: u32 = 0xFFFFFF;
+const FLASH_SIZE: usize = 150;
+
+/// A press that has been seen and is waiting for its flash to be presented
+struct Pending {
+    key: Key,
+    polled: Instant,
+    // Frames between the raw key event and the input layer reporting it
+    late_frames: u64,
+}
+
+/// Runs the latency test until the window is closed or Escape is pressed,
+/// logging each press on stdout
+pub fn run_cli(args: &[String]) -> Res<()> {
+    if !args.is_empty() {
+        eprintln!("ERROR! Usage: breakrs latency-test");
+        return Err(());
+    }
+
+    let mut window = create_window()?;
+    window.set_title(TITLE);
+    let mut canvas = Canvas {
+        buffer: vec![0; WIDTH * HEIGHT],
+        stride: WIDTH,
+    };
+    let mut key_presses = KeyPresses::default();
+    // The frame each key last went down on according to the window
+    let mut raw_presses: Vec<(Key, u64)> = Vec::new();
+    let mut pending: Option<Pending> = None;
+    let mut latencies = Vec::new();
+    let mut late_presses = 0;
+
+    println!("Press keys to flash the top left corner, Esc to stop");
+    let mut frame = 0_u64;
+    while window.is_open() {
+        canvas.buffer.fill(BACKGROUND);
+        if pending.is_some() {
+            draw_rect(&mut canvas, 0, 0, FLASH_SIZE, FLASH_SIZE, FLASH_COLOR);
+        }
+        let present_start = Instant::now();
+        present(&mut window, &canvas, TITLE)?;
+        let presented = Instant::now();
+        frame += 1;
+
+        if let Some(press) = pending.take() {
+            let latency = presented - press.polled;
+            println!(
+                "{:?}: {} from poll to present, {} of it presenting, reported {} frames late",
+                press.key,
+                format_ms(latency),
+                format_ms(presented - present_start),
+                press.late_frames
+            );
+            latencies.push(latency);
+            if press.late_frames > 0 {
+                late_presses += 1;
+            }
+        }
+
+        for key in window.get_keys_pressed(KeyRepeat::No) {
+            raw_presses.retain(|(raw, _)| *raw != key);
+            raw_presses.push((key, frame));
+        }
+        let presses = key_presses.update(&window.get_keys());
+        let polled = Instant::now();
+        if presses.contains(&Key::Escape) {
+            break;
+        }
+        // Only a key's first press is checked, since repeats have no raw
+        // event to compare with
+        if let Some(&key) = presses.first() {
+            let late_frames = raw_presses
+                .iter()
+                .find(|(raw, _)| *raw == key)
+                .map_or(0, |(_, raw_frame)| frame - raw_frame);
+            raw_presses.retain(|(raw, _)| *raw != key);
+            pending = Some(Pending {
+                key,
+                polled,
+                late_frames,
+            });
+        }
+    }
+
+    report(&latencies, late_presses);
+    Ok(())
+}
+
+fn report(latencies: &[Duration], late_presses: usize) {
+    let (Some(min), Some(max)) = (latencies.iter().min(), latencies.iter().max()) else {
+        println!("No presses were measured");
+        return;
+    };
+    let average = latencies.iter().sum::<Duration>() / latencies.len() as u32;
+    println!(
+        "{} presses: min {}, average {}, max {}",
+        latencies.len(),
+        format_ms(*min),
+        format_ms(average),
+        format_ms(*max)
+    );
+    if late_presses > 0 {
+        println!("{late_presses} presses were reported after their raw key event");
+    }
+}
+
+fn format_ms(duration: Duration) -> String {
+    format!("{:.2} ms", duration.as_secs_f64() * 1000.0)
+}
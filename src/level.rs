@@ -0,0 +1,396 @@
+use crate::audio::{Sfx, Theme};
+use crate::clock::seconds;
+use crate::physics::{GapKind, PlayField, Side, WallGap};
+use crate::PowerUp;
+
+/// Something that happens when a particular brick is destroyed
+#[derive(Clone, Copy, Debug)]
+pub enum BrickAction {
+    GivePowerUp(PowerUp),
+    ShowMessage(&'static str),
+}
+
+pub struct BrickScript {
+    pub row: usize,
+    pub column: usize,
+    pub action: BrickAction,
+}
+
+/// One step of a scripted boss. Steps run in order, starting over after
+/// the last one.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BossStep {
+    // Heads for this far across the field, from 0.0 against the left wall
+    // to 1.0 against the right, before going on to the next step
+    MoveTo(f32),
+    // Drops a hazard towards the paddle
+    Fire,
+    // Sets its health, at most its starting health
+    SetHealth(u32),
+    // Waits this many ticks before the next step
+    Wait(u32),
+}
+
+/// A boss written in a level file, fought once the level's bricks are
+/// cleared
+#[derive(Clone, Copy, Debug)]
+pub struct BossScript {
+    pub health: u32,
+    pub steps: &'static [BossStep],
+}
+
+/// One spot in a level file's brick grid
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Cell {
+    Empty,
+    Brick { hit_points: u8 },
+    Explosive,
+    Metal,
+    // A one hit brick listed in the level's secrets
+    Secret,
+}
+
+/// What a brick is made of, which decides how it sounds when hit
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Material {
+    // The usual brick sounds
+    #[default]
+    Plain,
+    Glass,
+    Stone,
+    Metal,
+}
+
+impl Material {
+    pub const ALL: [Material; 4] = [
+        Material::Plain,
+        Material::Glass,
+        Material::Stone,
+        Material::Metal,
+    ];
+
+    /// The name level files use
+    pub fn name(self) -> &'static str {
+        match self {
+            Material::Plain => "plain",
+            Material::Glass => "glass",
+            Material::Stone => "stone",
+            Material::Metal => "metal",
+        }
+    }
+
+    /// The sound of a ball hitting a brick of this material, or `None` for
+    /// the usual hit and break sounds
+    pub fn sound(self) -> Option<Sfx> {
+        match self {
+            Material::Plain => None,
+            Material::Glass => Some(Sfx::GlassHit),
+            Material::Stone => Some(Sfx::StoneHit),
+            Material::Metal => Some(Sfx::MetalHit),
+        }
+    }
+}
+
+/// Bricks laid out by a level file rather than the standard rows
+pub struct Layout {
+    // Rows from the bottom up, all the same length
+    pub cells: &'static [&'static [Cell]],
+    // The color of each row from the bottom up and the points its bricks
+    // are worth
+    pub styles: &'static [(u32, u32)],
+    // Row from the bottom and column of each brick carrying a power-up
+    pub power_ups: &'static [(usize, usize, PowerUp)],
+}
+
+impl Layout {
+    pub fn columns(&self) -> usize {
+        self.cells.first().map_or(0, |row| row.len())
+    }
+}
+
+/// A brick that is hidden until a brick next to it breaks, maybe holding
+/// one of the bonus letters
+#[derive(Clone, Copy, Debug)]
+pub struct Secret {
+    pub row: usize,
+    pub column: usize,
+    pub letter: Option<char>,
+}
+
+/// A strip of the field, top to bottom, where broken bricks score extra
+#[derive(Clone, Copy, Debug)]
+pub struct ScoreZone {
+    // Edges as fractions of the field's width from its left wall
+    pub from: f32,
+    pub to: f32,
+    pub multiplier: u32,
+}
+
+pub struct Level {
+    pub name: &'static str,
+    pub rows: usize,
+    pub par_score: u32,
+    pub par_ticks: u32,
+    pub scripts: &'static [BrickScript],
+    pub field: PlayField,
+    // Hits each row of bricks takes to destroy, from the bottom row up.
+    // Rows past the end take one.
+    pub hit_points: &'static [u8],
+    // The row and column of each brick that is metal and never breaks
+    pub metal: &'static [(usize, usize)],
+    // What each row of bricks is made of, from the bottom row up. Rows
+    // past the end are plain, and metal bricks are always metal.
+    pub materials: &'static [Material],
+    // Built in levels use the standard rows of bricks
+    pub layout: Option<Layout>,
+    // Serves go this many times as fast as usual
+    pub ball_speed: f32,
+    pub score_zones: &'static [ScoreZone],
+    // Row from the bottom and column of the secret bricks
+    pub secrets: &'static [Secret],
+    // Music and sounds for the level instead of the usual ones
+    pub theme: Theme,
+    // Built in levels have the usual boss every few levels instead
+    pub boss: Option<BossScript>,
+}
+
+/// Where endless runs start. Rows of bricks keep coming in at the top
+/// until they reach the paddle.
+pub const ENDLESS: Level = Level {
+    name: "Endless",
+    rows: 4,
+    par_score: 0,
+    par_ticks: 0,
+    scripts: &[],
+    field: PlayField::FULL,
+    hit_points: &[],
+    metal: &[],
+    materials: &[],
+    layout: None,
+    ball_speed: 1.0,
+    score_zones: &[],
+    secrets: &[],
+    theme: Theme::DEFAULT,
+    boss: None,
+};
+
+pub const LEVELS: &[Level] = &[
+    Level {
+        name: "Warm Up",
+        rows: 3,
+        par_score: 900,
+        par_ticks: seconds(60),
+        scripts: &[BrickScript {
+            row: 2,
+            column: 6,
+            action: BrickAction::ShowMessage("Watch out for explosives"),
+        }],
+        field: PlayField::FULL,
+        hit_points: &[],
+        metal: &[],
+        materials: &[],
+        layout: None,
+        ball_speed: 1.0,
+        score_zones: &[],
+        secrets: &[Secret {
+            row: 0,
+            column: 6,
+            letter: Some('E'),
+        }],
+        theme: Theme::DEFAULT,
+        boss: None,
+    },
+    Level {
+        name: "Rainbow",
+        rows: 6,
+        par_score: 4400,
+        par_ticks: seconds(120),
+        scripts: &[BrickScript {
+            row: 5,
+            column: 6,
+            action: BrickAction::ShowMessage("Over the rainbow"),
+        }],
+        field: PlayField::FULL,
+        hit_points: &[1, 1, 1, 1, 2, 2],
+        metal: &[(3, 2), (3, 10)],
+        // The armored rows on top
+        materials: &[
+            Material::Plain,
+            Material::Plain,
+            Material::Plain,
+            Material::Plain,
+            Material::Stone,
+            Material::Stone,
+        ],
+        layout: None,
+        ball_speed: 1.0,
+        score_zones: &[],
+        secrets: &[
+            Secret {
+                row: 2,
+                column: 6,
+                letter: Some('X'),
+            },
+            Secret {
+                row: 5,
+                column: 0,
+                letter: Some('T'),
+            },
+        ],
+        theme: Theme::DEFAULT,
+        boss: None,
+    },
+    Level {
+        name: "Half Way",
+        rows: 4,
+        par_score: 2000,
+        par_ticks: seconds(75),
+        scripts: &[],
+        // Tunnels between the side walls, and a top worth breaking through to
+        field: PlayField::FULL
+            .with_gaps(&[
+                WallGap {
+                    side: Side::Left,
+                    bottom: -0.3,
+                    top: 0.0,
+                    kind: GapKind::Wrap,
+                },
+                WallGap {
+                    side: Side::Right,
+                    bottom: -0.3,
+                    top: 0.0,
+                    kind: GapKind::Wrap,
+                },
+            ])
+            .with_open_top(),
+        hit_points: &[],
+        metal: &[],
+        materials: &[],
+        layout: None,
+        ball_speed: 1.0,
+        score_zones: &[],
+        secrets: &[
+            Secret {
+                row: 3,
+                column: 4,
+                letter: Some('R'),
+            },
+            Secret {
+                row: 2,
+                column: 8,
+                letter: None,
+            },
+        ],
+        theme: Theme::DEFAULT,
+        boss: None,
+    },
+    Level {
+        name: "The Wall",
+        rows: 6,
+        par_score: 6500,
+        par_ticks: seconds(100),
+        scripts: &[
+            BrickScript {
+                row: 5,
+                column: 0,
+                action: BrickAction::GivePowerUp(PowerUp::SplitBall),
+            },
+            BrickScript {
+                row: 5,
+                column: 12,
+                action: BrickAction::GivePowerUp(PowerUp::SplitBall),
+            },
+            BrickScript {
+                row: 0,
+                column: 6,
+                action: BrickAction::ShowMessage("The wall is crumbling"),
+            },
+        ],
+        field: PlayField::FULL,
+        hit_points: &[1, 1, 1, 2, 2, 3],
+        metal: &[(3, 1), (3, 4), (3, 8), (3, 11)],
+        materials: &[
+            Material::Glass,
+            Material::Glass,
+            Material::Glass,
+            Material::Stone,
+            Material::Stone,
+            Material::Stone,
+        ],
+        layout: None,
+        ball_speed: 1.0,
+        // Next to the gaps, where the ball is hardest to keep
+        score_zones: &[
+            ScoreZone {
+                from: 0.0,
+                to: 0.2,
+                multiplier: 2,
+            },
+            ScoreZone {
+                from: 0.8,
+                to: 1.0,
+                multiplier: 2,
+            },
+        ],
+        secrets: &[Secret {
+            row: 4,
+            column: 6,
+            letter: Some('A'),
+        }],
+        theme: Theme::DEFAULT,
+        boss: None,
+    },
+];
+
+impl Level {
+    /// The action attached to the brick at `row` and `column`, if any
+    pub fn brick_action(&self, row: usize, column: usize) -> Option<BrickAction> {
+        self.scripts
+            .iter()
+            .find(|script| script.row == row && script.column == column)
+            .map(|script| script.action)
+    }
+
+    /// The secret brick at `row` and `column`, if there is one
+    pub fn secret(&self, row: usize, column: usize) -> Option<&Secret> {
+        self.secrets
+            .iter()
+            .find(|secret| secret.row == row && secret.column == column)
+    }
+
+    /// What points are multiplied by for a brick whose middle is `x` of the
+    /// way across the field. Where zones overlap, the biggest wins.
+    pub fn score_multiplier(&self, x: f32) -> u32 {
+        self.score_zones
+            .iter()
+            .filter(|zone| (zone.from..=zone.to).contains(&x))
+            .map(|zone| zone.multiplier)
+            .max()
+            .unwrap_or(1)
+    }
+
+    pub fn is_metal(&self, row: usize, column: usize) -> bool {
+        self.metal.contains(&(row, column))
+    }
+
+    pub fn row_hit_points(&self, row: usize) -> u8 {
+        self.hit_points.get(row).copied().unwrap_or(1)
+    }
+
+    pub fn row_material(&self, row: usize) -> Material {
+        self.materials.get(row).copied().unwrap_or_default()
+    }
+
+    /// Clearing a level earns one star, beating its par score and beating
+    /// its par time earn one more each
+    pub fn stars(&self, score: u32, ticks: u32) -> u8 {
+        let mut stars = 1;
+        if score >= self.par_score {
+            stars += 1;
+        }
+        if ticks <= self.par_ticks {
+            stars += 1;
+        }
+        stars
+    }
+}
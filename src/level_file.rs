@@ -0,0 +1,696 @@
+//! Levels read from text files, so new levels can be added without
+//! rebuilding the game. Every `.txt` file in the `levels` directory is a
+//! level, played after the built in levels in the order of the file names.
+//!
+//! Files are `key = value` lines, and lines starting with `#` are comments:
+//!
+//! ```text
+//! name = Checkers
+//! # The score and time in seconds to beat for the extra stars
+//! par_score = 3000
+//! par_time = 90
+//! # Optional: serve speed compared to usual, and how far the side walls
+//! # are moved in
+//! ball_speed = 1.2
+//! field_inset = 0.1
+//! # Optional: a sound pack to play the level with over the player's, and
+//! # a music track, relative to `assets/audio`, with stems that come in as
+//! # the level gets tense
+//! sound_pack = retro
+//! music = music/checkers.wav
+//! music_layer = music/checkers_drums.wav
+//! music_layer = music/checkers_lead.wav
+//! # Rows of bricks from the top down: color, points for each brick, then
+//! # one character per brick. `.` is a gap, `#` a brick, `2` to `9` a brick
+//! # taking that many hits, `X` an explosive brick and `M` metal.
+//! row = FF0000 70 #.#.#.#.#.#.#
+//! row = FFA500 50 .#.#.#X#.#.#.
+//! row = 0000FF 30 MM#########MM
+//! # A power-up in the brick at a row (from the top) and column
+//! power_up = 1 7 laser
+//! # What a row's bricks sound like when hit: plain (the usual sounds),
+//! # glass, stone or metal. Metal bricks always sound like metal.
+//! material = 1 glass
+//! material = 3 stone
+//! # `?` in a row is a secret brick, hidden until a brick next to it
+//! # breaks. Secret bricks can hold a letter of the word EXTRA.
+//! row = 008000 40 #####?#?#####
+//! letter = 4 6 X
+//! # Bricks in the outer thirds of the field score double: the strip's
+//! # edges as fractions of the field's width, then the multiplier
+//! score_zone = 0 0.33 2
+//! score_zone = 0.67 1 2
+//! # What happens when the brick at a row (from the top) and column is
+//! # destroyed: it gives a power-up, or shows a message
+//! script = 1 1 power_up split_ball
+//! script = 3 7 message The wall is crumbling
+//! # Optional: a boss to beat once the bricks are cleared, with its health
+//! # and the steps it repeats. `move` heads for a spot from 0 (left) to 1
+//! # (right), `fire` drops a hazard, `health` sets its health and `wait`
+//! # pauses for that many seconds.
+//! boss_health = 20
+//! boss = move 0.1
+//! boss = fire
+//! boss = move 0.9
+//! boss = fire
+//! boss = wait 1.5
+//! ```
+//!
+//! Levels last as long as the game, so once a file is parsed its contents
+//! are leaked to give them the same `'static` lifetime as the built in
+//! levels.
+
+use crate::audio::Theme;
+use crate::boss::{MAX_HEALTH, MAX_SCRIPTED_HEALTH};
+use crate::clock::TICKS_PER_SECOND;
+use crate::level::{
+    BossScript, BossStep, BrickAction, BrickScript, Cell, Layout, Level, Material, ScoreZone,
+    Secret,
+};
+use crate::physics::PlayField;
+use crate::{Bricks, PowerUp, BONUS_WORD};
+use std::fmt::Display;
+use std::io::ErrorKind;
+use std::path::Path;
+use std::str::FromStr;
+
+const MAX_COLUMNS: usize = 26;
+const MIN_BALL_SPEED: f32 = 0.5;
+const MAX_BALL_SPEED: f32 = 2.0;
+const MAX_FIELD_INSET: f32 = 0.5;
+const MAX_SCORE_MULTIPLIER: u32 = 10;
+const MAX_BOSS_WAIT_SECONDS: f32 = 10.0;
+
+/// Reads the levels in every `.txt` file in `dir`. Files that can't be read
+/// or parsed are skipped, reporting why.
+pub fn load_dir(dir: &Path) -> Vec<&'static Level> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Vec::new(),
+        Err(err) => {
+            eprintln!("ERROR! Could not read levels from {dir:?}: {err}");
+            return Vec::new();
+        }
+    };
+    let mut paths = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|extension| extension == "txt"))
+        .collect::<Vec<_>>();
+    paths.sort();
+
+    paths
+        .iter()
+        .filter_map(|path| {
+            let level = std::fs::read_to_string(path)
+                .map_err(|err| err.to_string())
+                .and_then(|text| parse(&text));
+            match level {
+                Ok(level) => Some(&*Box::leak(Box::new(level))),
+                Err(err) => {
+                    eprintln!("ERROR! Skipping level {path:?}: {err}");
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// Parses the text of a level file. Errors say which line is wrong and why.
+pub fn parse(text: &str) -> Result<Level, String> {
+    let mut file = LevelFile::default();
+    for (line_number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        file.set(line)
+            .map_err(|err| format!("line {}: {err}", line_number + 1))?;
+    }
+    file.into_level()
+}
+
+/// What a level file says, before it is checked as a whole
+struct LevelFile {
+    name: Option<String>,
+    par_score: Option<u32>,
+    par_seconds: Option<u32>,
+    ball_speed: f32,
+    field_inset: f32,
+    sound_pack: Option<String>,
+    music: Option<String>,
+    music_layers: Vec<String>,
+    // Color, points and bricks of each row from the top down
+    rows: Vec<(u32, u32, Vec<Cell>)>,
+    // Row from the top and column, both counting from 1, of each power-up
+    power_ups: Vec<(usize, usize, PowerUp)>,
+    score_zones: Vec<ScoreZone>,
+    // Row from the top and column, both counting from 1, of each letter
+    letters: Vec<(usize, usize, char)>,
+    // Row from the top, counting from 1, and material of each row given one
+    materials: Vec<(usize, Material)>,
+    // Row from the top and column, both counting from 1, of each brick
+    // with an action
+    scripts: Vec<(usize, usize, BrickAction)>,
+    boss_health: Option<u32>,
+    boss_steps: Vec<BossStep>,
+}
+
+impl Default for LevelFile {
+    fn default() -> Self {
+        LevelFile {
+            name: None,
+            par_score: None,
+            par_seconds: None,
+            ball_speed: 1.0,
+            field_inset: 0.0,
+            sound_pack: None,
+            music: None,
+            music_layers: Vec::new(),
+            rows: Vec::new(),
+            power_ups: Vec::new(),
+            score_zones: Vec::new(),
+            letters: Vec::new(),
+            materials: Vec::new(),
+            scripts: Vec::new(),
+            boss_health: None,
+            boss_steps: Vec::new(),
+        }
+    }
+}
+
+impl LevelFile {
+    fn set(&mut self, line: &str) -> Result<(), String> {
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| format!("expected `key = value`, found `{line}`"))?;
+        let value = value.trim();
+        match key.trim() {
+            "name" if value.is_empty() => return Err(String::from("the name is empty")),
+            "name" => self.name = Some(String::from(value)),
+            "par_score" => self.par_score = Some(parse_number(value)?),
+            "par_time" => self.par_seconds = Some(parse_number(value)?),
+            "ball_speed" => {
+                self.ball_speed = parse_in_range(value, MIN_BALL_SPEED, MAX_BALL_SPEED)?;
+            }
+            "field_inset" => self.field_inset = parse_in_range(value, 0.0, MAX_FIELD_INSET)?,
+            "sound_pack" | "music" | "music_layer" if value.is_empty() => {
+                return Err(format!("the {} is empty", key.trim()));
+            }
+            "sound_pack" => self.sound_pack = Some(String::from(value)),
+            "music" => self.music = Some(String::from(value)),
+            "music_layer" => self.music_layers.push(String::from(value)),
+            "row" => self.rows.push(parse_row(value)?),
+            "power_up" => self.power_ups.push(parse_power_up(value)?),
+            "score_zone" => self.score_zones.push(parse_score_zone(value)?),
+            "letter" => self.letters.push(parse_letter(value)?),
+            "material" => self.materials.push(parse_material(value)?),
+            "script" => self.scripts.push(parse_script(value)?),
+            "boss_health" => self.boss_health = Some(parse_number(value)?),
+            "boss" => self.boss_steps.push(parse_boss_step(value)?),
+            key => return Err(format!("unknown key `{key}`")),
+        }
+        Ok(())
+    }
+
+    fn into_level(self) -> Result<Level, String> {
+        let name = self.name.ok_or("missing `name`")?;
+        let par_score = self.par_score.ok_or("missing `par_score`")?;
+        let par_seconds = self.par_seconds.ok_or("missing `par_time`")?;
+        if !self.music_layers.is_empty() && self.music.is_none() {
+            return Err(String::from(
+                "`music_layer` needs a `music` track to go with",
+            ));
+        }
+        let Some((_, _, first_row)) = self.rows.first() else {
+            return Err(String::from("no `row` lines"));
+        };
+        let boss = match (self.boss_health, self.boss_steps.is_empty()) {
+            (Some(_), true) => {
+                return Err(String::from("`boss_health` needs `boss` steps to go with"));
+            }
+            (None, true) => None,
+            (health, false) => {
+                let health = health.unwrap_or(MAX_HEALTH);
+                if !(1..=MAX_SCRIPTED_HEALTH).contains(&health) {
+                    return Err(format!(
+                        "boss_health must be 1 to {MAX_SCRIPTED_HEALTH}, found {health}"
+                    ));
+                }
+                let too_healthy = self
+                    .boss_steps
+                    .iter()
+                    .any(|&step| matches!(step, BossStep::SetHealth(points) if points > health));
+                if too_healthy {
+                    return Err(format!(
+                        "a boss `health` step is more than its boss_health of {health}"
+                    ));
+                }
+                let takes_time =
+                    |step: &BossStep| matches!(step, BossStep::MoveTo(_) | BossStep::Wait(_));
+                if !self.boss_steps.iter().any(takes_time) {
+                    return Err(String::from(
+                        "the boss needs a `move` or `wait` step to take up time",
+                    ));
+                }
+                Some(BossScript {
+                    health,
+                    steps: self.boss_steps.leak(),
+                })
+            }
+        };
+
+        let columns = first_row.len();
+        if columns > MAX_COLUMNS {
+            return Err(format!(
+                "rows can have at most {MAX_COLUMNS} bricks, the first row has {columns}"
+            ));
+        }
+        if let Some(row) = self
+            .rows
+            .iter()
+            .position(|(_, _, row)| row.len() != columns)
+        {
+            return Err(format!(
+                "row {} has {} bricks but the first row has {columns}",
+                row + 1,
+                self.rows[row].2.len()
+            ));
+        }
+        let field = PlayField::inset(self.field_inset);
+        let max_rows = Bricks::max_rows(columns, &field);
+        if self.rows.len() > max_rows {
+            return Err(format!(
+                "{} rows of {columns} bricks don't fit, at most {max_rows} do",
+                self.rows.len()
+            ));
+        }
+        let breakable = |cell: &Cell| !matches!(cell, Cell::Empty | Cell::Metal);
+        // Secret bricks don't need breaking to clear the level
+        let needed = |cell: &Cell| breakable(cell) && *cell != Cell::Secret;
+        if !self.rows.iter().any(|(_, _, row)| row.iter().any(needed)) {
+            return Err(String::from("there are no bricks to break"));
+        }
+
+        // Levels count rows from the bottom up
+        let row_count = self.rows.len();
+        let mut power_ups = Vec::new();
+        for (row, column, power_up) in self.power_ups {
+            let cell = self
+                .rows
+                .get(row.wrapping_sub(1))
+                .and_then(|(_, _, cells)| cells.get(column.wrapping_sub(1)));
+            if !cell.is_some_and(breakable) {
+                return Err(format!(
+                    "power_up at row {row} column {column} isn't on a brick that breaks"
+                ));
+            }
+            power_ups.push((row_count - row, column - 1, power_up));
+        }
+        let mut scripts = Vec::new();
+        for (row, column, action) in self.scripts {
+            let cell = self
+                .rows
+                .get(row.wrapping_sub(1))
+                .and_then(|(_, _, cells)| cells.get(column.wrapping_sub(1)));
+            if !cell.is_some_and(breakable) {
+                return Err(format!(
+                    "script at row {row} column {column} isn't on a brick that breaks"
+                ));
+            }
+            scripts.push(BrickScript {
+                row: row_count - row,
+                column: column - 1,
+                action,
+            });
+        }
+        let mut secrets = Vec::new();
+        for (top_row, cells) in self.rows.iter().map(|(_, _, cells)| cells).enumerate() {
+            for (column, _) in cells
+                .iter()
+                .enumerate()
+                .filter(|(_, cell)| **cell == Cell::Secret)
+            {
+                let letter = self
+                    .letters
+                    .iter()
+                    .find(|&&(r, c, _)| (r, c) == (top_row + 1, column + 1))
+                    .map(|&(_, _, letter)| letter);
+                secrets.push(Secret {
+                    row: row_count - 1 - top_row,
+                    column,
+                    letter,
+                });
+            }
+        }
+        for &(row, column, _) in &self.letters {
+            let cell = self
+                .rows
+                .get(row.wrapping_sub(1))
+                .and_then(|(_, _, cells)| cells.get(column.wrapping_sub(1)));
+            if cell != Some(&Cell::Secret) {
+                return Err(format!(
+                    "letter at row {row} column {column} isn't on a secret brick"
+                ));
+            }
+        }
+        let mut materials = vec![Material::Plain; row_count];
+        for (row, material) in self.materials {
+            if !(1..=row_count).contains(&row) {
+                return Err(format!(
+                    "material for row {row}, but there are {row_count} rows"
+                ));
+            }
+            materials[row_count - row] = material;
+        }
+        let mut styles = Vec::new();
+        let mut cells = Vec::new();
+        for (color, points, row) in self.rows.into_iter().rev() {
+            styles.push((color, points));
+            cells.push(&*row.leak());
+        }
+
+        Ok(Level {
+            name: name.leak(),
+            rows: row_count,
+            par_score,
+            par_ticks: par_seconds.saturating_mul(TICKS_PER_SECOND),
+            scripts: scripts.leak(),
+            field,
+            hit_points: &[],
+            metal: &[],
+            materials: materials.leak(),
+            layout: Some(Layout {
+                cells: cells.leak(),
+                styles: styles.leak(),
+                power_ups: power_ups.leak(),
+            }),
+            ball_speed: self.ball_speed,
+            score_zones: self.score_zones.leak(),
+            secrets: secrets.leak(),
+            theme: Theme {
+                sound_pack: self.sound_pack.map(|pack| &*pack.leak()),
+                music: self.music.map(|music| &*music.leak()),
+                music_layers: self
+                    .music_layers
+                    .into_iter()
+                    .map(|layer| &*layer.leak())
+                    .collect::<Vec<_>>()
+                    .leak(),
+            },
+            boss,
+        })
+    }
+}
+
+fn parse_number<T>(value: &str) -> Result<T, String>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    value
+        .parse()
+        .map_err(|err| format!("invalid number `{value}`: {err}"))
+}
+
+fn parse_in_range(value: &str, min: f32, max: f32) -> Result<f32, String> {
+    let number = parse_number::<f32>(value)?;
+    if !(min..=max).contains(&number) {
+        return Err(format!("expected {min} to {max}, found {number}"));
+    }
+    Ok(number)
+}
+
+/// Parses `<color> <points> <bricks>`
+fn parse_row(value: &str) -> Result<(u32, u32, Vec<Cell>), String> {
+    let [color, points, bricks] = value.split_whitespace().collect::<Vec<_>>()[..] else {
+        return Err(format!(
+            "expected `row = <color> <points> <bricks>`, found `row = {value}`"
+        ));
+    };
+    let color = (color.len() == 6)
+        .then(|| u32::from_str_radix(color, 16).ok())
+        .flatten()
+        .ok_or_else(|| format!("invalid color `{color}`, expected six hex digits like FF8000"))?;
+    let points = parse_number(points)?;
+    let cells = bricks
+        .chars()
+        .enumerate()
+        .map(|(column, brick)| match brick {
+            '.' => Ok(Cell::Empty),
+            '#' => Ok(Cell::Brick { hit_points: 1 }),
+            '2'..='9' => Ok(Cell::Brick {
+                hit_points: brick as u8 - b'0',
+            }),
+            'X' => Ok(Cell::Explosive),
+            'M' => Ok(Cell::Metal),
+            '?' => Ok(Cell::Secret),
+            _ => Err(format!(
+                "unknown brick `{brick}` in column {}, expected one of `.#23456789XM?`",
+                column + 1
+            )),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok((color, points, cells))
+}
+
+/// Parses `<row> <column> <power-up name>`
+fn parse_power_up(value: &str) -> Result<(usize, usize, PowerUp), String> {
+    let [row, column, name] = value.split_whitespace().collect::<Vec<_>>()[..] else {
+        return Err(format!(
+            "expected `power_up = <row> <column> <name>`, found `power_up = {value}`"
+        ));
+    };
+    Ok((
+        parse_number(row)?,
+        parse_number(column)?,
+        power_up_named(name)?,
+    ))
+}
+
+fn power_up_named(name: &str) -> Result<PowerUp, String> {
+    PowerUp::ALL
+        .into_iter()
+        .find(|power_up| power_up.name() == name)
+        .ok_or_else(|| {
+            let names = PowerUp::ALL.map(PowerUp::name).join(", ");
+            format!("unknown power-up `{name}`, expected one of {names}")
+        })
+}
+
+/// The first word of `text` and the rest after the spaces following it
+fn next_word(text: &str) -> Option<(&str, &str)> {
+    text.split_once(char::is_whitespace)
+        .map(|(word, rest)| (word, rest.trim_start()))
+}
+
+/// Parses `<row> <column> power_up <name>` or `<row> <column> message
+/// <text>`
+fn parse_script(value: &str) -> Result<(usize, usize, BrickAction), String> {
+    // The message can have spaces in it, so only the first three words
+    // are split off
+    let Some((row, column, action, argument)) = next_word(value).and_then(|(row, rest)| {
+        let (column, rest) = next_word(rest)?;
+        let (action, argument) = next_word(rest)?;
+        Some((row, column, action, argument))
+    }) else {
+        return Err(format!(
+            "expected `script = <row> <column> <action> <argument>`, found `script = {value}`"
+        ));
+    };
+    let action = match action {
+        "power_up" => BrickAction::GivePowerUp(power_up_named(argument)?),
+        "message" if argument.is_empty() => return Err(String::from("the message is empty")),
+        "message" => BrickAction::ShowMessage(String::from(argument).leak()),
+        action => {
+            return Err(format!(
+                "unknown action `{action}`, expected power_up or message"
+            ))
+        }
+    };
+    Ok((parse_number(row)?, parse_number(column)?, action))
+}
+
+/// Parses `move <across>`, `fire`, `health <points>` or `wait <seconds>`
+fn parse_boss_step(value: &str) -> Result<BossStep, String> {
+    match value.split_whitespace().collect::<Vec<_>>()[..] {
+        ["move", across] => Ok(BossStep::MoveTo(parse_in_range(across, 0.0, 1.0)?)),
+        ["fire"] => Ok(BossStep::Fire),
+        ["health", points] => match parse_number(points)? {
+            0 => Err(String::from("a boss's health must be at least 1")),
+            points => Ok(BossStep::SetHealth(points)),
+        },
+        ["wait", seconds] => {
+            let seconds = parse_in_range(seconds, 0.0, MAX_BOSS_WAIT_SECONDS)?;
+            Ok(BossStep::Wait(
+                (seconds * TICKS_PER_SECOND as f32).round() as u32
+            ))
+        }
+        _ => Err(format!(
+            "unknown boss step `{value}`, expected move, fire, health or wait"
+        )),
+    }
+}
+
+/// Parses `<row> <column> <letter>`
+fn parse_letter(value: &str) -> Result<(usize, usize, char), String> {
+    let [row, column, letter] = value.split_whitespace().collect::<Vec<_>>()[..] else {
+        return Err(format!(
+            "expected `letter = <row> <column> <letter>`, found `letter = {value}`"
+        ));
+    };
+    let mut chars = letter.chars();
+    let (Some(letter), None) = (chars.next(), chars.next()) else {
+        return Err(format!("expected a single letter, found `{letter}`"));
+    };
+    let letter = letter.to_ascii_uppercase();
+    if !BONUS_WORD.contains(letter) {
+        return Err(format!(
+            "`{letter}` isn't in {BONUS_WORD}, the word the letters spell"
+        ));
+    }
+    Ok((parse_number(row)?, parse_number(column)?, letter))
+}
+
+/// Parses `<row> <material name>`
+fn parse_material(value: &str) -> Result<(usize, Material), String> {
+    let [row, name] = value.split_whitespace().collect::<Vec<_>>()[..] else {
+        return Err(format!(
+            "expected `material = <row> <name>`, found `material = {value}`"
+        ));
+    };
+    let material = Material::ALL
+        .into_iter()
+        .find(|material| material.name() == name)
+        .ok_or_else(|| {
+            let names = Material::ALL.map(Material::name).join(", ");
+            format!("unknown material `{name}`, expected one of {names}")
+        })?;
+    Ok((parse_number(row)?, material))
+}
+
+/// Parses `<from> <to> <multiplier>`
+fn parse_score_zone(value: &str) -> Result<ScoreZone, String> {
+    let [from, to, multiplier] = value.split_whitespace().collect::<Vec<_>>()[..] else {
+        return Err(format!(
+            "expected `score_zone = <from> <to> <multiplier>`, found `score_zone = {value}`"
+        ));
+    };
+    let from = parse_in_range(from, 0.0, 1.0)?;
+    let to = parse_in_range(to, 0.0, 1.0)?;
+    if from >= to {
+        return Err(format!("the zone ends at {to}, before it starts at {from}"));
+    }
+    let multiplier = parse_number(multiplier)?;
+    if !(1..=MAX_SCORE_MULTIPLIER).contains(&multiplier) {
+        return Err(format!(
+            "expected a multiplier from 1 to {MAX_SCORE_MULTIPLIER}, found {multiplier}"
+        ));
+    }
+    Ok(ScoreZone {
+        from,
+        to,
+        multiplier,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LEVEL: &str = "\
+name = Scripted
+par_score = 100
+par_time = 60
+row = FF0000 70 #.#
+row = 0000FF 30 #M#
+script = 1 3 power_up multi_ball
+script = 2 1 message  Mind the  gap
+";
+
+    #[test]
+    fn scripts_are_attached_to_their_bricks() {
+        let level = parse(LEVEL).unwrap();
+        // The top row is row 1 in the file and the last row in the level
+        assert!(matches!(
+            level.brick_action(1, 2),
+            Some(BrickAction::GivePowerUp(PowerUp::MultiBall))
+        ));
+        assert!(matches!(
+            level.brick_action(0, 0),
+            Some(BrickAction::ShowMessage("Mind the  gap"))
+        ));
+        assert!(level.brick_action(1, 0).is_none());
+    }
+
+    #[test]
+    fn scripts_need_a_brick_that_breaks() {
+        for script in [
+            "1 2 message Nothing here",
+            "2 2 message Metal",
+            "3 1 message Off",
+        ] {
+            let text = format!("{LEVEL}script = {script}\n");
+            let err = parse(&text).err().expect("parsed a script on no brick");
+            assert!(err.contains("isn't on a brick that breaks"), "{err}");
+        }
+    }
+
+    #[test]
+    fn bad_scripts_say_what_is_wrong() {
+        let cases = [
+            ("1 1 portal", "expected `script ="),
+            ("1 1 explode now", "unknown action `explode`"),
+            ("1 1 power_up rocket", "unknown power-up `rocket`"),
+        ];
+        for (script, expected) in cases {
+            let text = format!("{LEVEL}script = {script}\n");
+            let err = parse(&text).err().expect("parsed a bad script");
+            assert!(err.contains(expected), "{err}");
+        }
+    }
+
+    #[test]
+    fn boss_steps_are_read_in_order() {
+        let text = format!(
+            "{LEVEL}\
+boss_health = 20
+boss = move 0.25
+boss = fire
+boss = health 10
+boss = wait 0.5
+"
+        );
+        let boss = parse(&text).unwrap().boss.expect("no boss");
+        assert_eq!(boss.health, 20);
+        assert_eq!(
+            boss.steps,
+            [
+                BossStep::MoveTo(0.25),
+                BossStep::Fire,
+                BossStep::SetHealth(10),
+                BossStep::Wait(TICKS_PER_SECOND / 2),
+            ]
+        );
+        assert!(parse(LEVEL).unwrap().boss.is_none());
+    }
+
+    #[test]
+    fn bad_bosses_say_what_is_wrong() {
+        let cases = [
+            ("boss = jump", "unknown boss step `jump`"),
+            ("boss = move 2", "expected 0 to 1"),
+            ("boss = health 0", "at least 1"),
+            ("boss_health = 5", "needs `boss` steps"),
+            (
+                "boss_health = 5\nboss = health 6\nboss = wait 1",
+                "more than its",
+            ),
+            ("boss = fire", "needs a `move` or `wait` step"),
+        ];
+        for (lines, expected) in cases {
+            let text = format!("{LEVEL}{lines}\n");
+            let err = parse(&text).err().expect("parsed a bad boss");
+            assert!(err.contains(expected), "{err}");
+        }
+    }
+}
@@ -0,0 +1,208 @@
+use crate::clock::format_ticks;
+use crate::daily;
+use crate::level::{Cell, Layout, Level};
+use crate::progress::Progress;
+use crate::{
+    compute_multiline_text_data, draw_focus_outline, draw_rect, draw_subcanvas,
+    draw_subcanvas_scaled, Canvas, PixelRect, ScaleFilter, METAL_BRICK_COLOR, ROW_STYLES,
+    STANDARD_COLUMNS,
+};
+use minifb::Key;
+use rusttype::Font;
+
+const COLUMNS: usize = 2;
+// Rows past this many scroll to keep the cursor in view
+const VISIBLE_ROWS: usize = 3;
+const CELL_WIDTH: usize = 220;
+const CELL_HEIGHT: usize = 90;
+const CELL_GAP: usize = 20;
+const CURSOR_BORDER: usize = 4;
+const TEXT_HEIGHT: f32 = 20.0;
+// Each brick of a level's thumbnail is this many pixels across and down
+const THUMBNAIL_BRICK_WIDTH: usize = 6;
+const THUMBNAIL_BRICK_HEIGHT: usize = 3;
+
+const UNLOCKED_COLOR: u32 = 0xFFFFFF;
+const LOCKED_COLOR: u32 = 0x808080;
+
+/// A level is playable once the level before it has been cleared
+pub fn is_unlocked(progress: &Progress, index: usize) -> bool {
+    index == 0 || progress.level(index - 1).stars > 0
+}
+
+pub enum Selection {
+    None,
+    Start(usize),
+    StartModifierRun,
+    StartEndless,
+    StartTimeAttack(usize),
+    StartDaily,
+    OpenChallenges,
+    OpenSettings,
+    OpenStats,
+}
+
+pub struct LevelSelect {
+    pub cursor: usize,
+}
+
+impl LevelSelect {
+    pub fn key_pressed(&mut self, key: Key, progress: &Progress, level_count: usize) -> Selection {
+        let last = level_count - 1;
+        match key {
+            Key::Left | Key::A => self.cursor = self.cursor.saturating_sub(1),
+            Key::Right | Key::D => self.cursor = (self.cursor + 1).min(last),
+            Key::Up | Key::W => self.cursor = self.cursor.saturating_sub(COLUMNS),
+            Key::Down | Key::S => self.cursor = (self.cursor + COLUMNS).min(last),
+            Key::Enter | Key::Space if is_unlocked(progress, self.cursor) => {
+                return Selection::Start(self.cursor);
+            }
+            Key::M => return Selection::StartModifierRun,
+            Key::E => return Selection::StartEndless,
+            Key::T if is_unlocked(progress, self.cursor) => {
+                return Selection::StartTimeAttack(self.cursor);
+            }
+            Key::Y => return Selection::StartDaily,
+            Key::C => return Selection::OpenChallenges,
+            Key::O => return Selection::OpenSettings,
+            Key::I => return Selection::OpenStats,
+            _ => (),
+        }
+        Selection::None
+    }
+
+    /// Draws the grid of `levels`, each with its picture from `thumbnails`
+    pub fn draw(
+        &self,
+        canvas: &mut Canvas,
+        font: Option<&Font>,
+        levels: &[&Level],
+        thumbnails: &[Option<Canvas>],
+        progress: &Progress,
+        focus: f32,
+    ) {
+        let rows = levels.len().div_ceil(COLUMNS).min(VISIBLE_ROWS);
+        let first_row = (self.cursor / COLUMNS + 1).saturating_sub(VISIBLE_ROWS);
+        let grid_width = COLUMNS * CELL_WIDTH + (COLUMNS - 1) * CELL_GAP;
+        let grid_height = rows * CELL_HEIGHT + (rows - 1) * CELL_GAP;
+        let left = canvas.width().saturating_sub(grid_width) / 2;
+        let top = canvas.height().saturating_sub(grid_height) / 2;
+
+        if let Some(font) = font {
+            let title = compute_multiline_text_data(font, TEXT_HEIGHT * 1.5, &["SELECT LEVEL"]);
+            let x = canvas.width().saturating_sub(title.width()) / 2;
+            let y = top.saturating_sub(CELL_GAP + title.height());
+            draw_subcanvas(canvas, &title, x, y);
+
+            let endless = format!(
+                "[M] modifier run  [E] endless (best {})",
+                progress.endless_best
+            );
+            let today = daily::today();
+            let daily = match progress.daily_best(today) {
+                Some(best) => format!("[Y] daily {} (best {best})", daily::format_day(today)),
+                None => format!("[Y] daily {}", daily::format_day(today)),
+            };
+            let hint = compute_multiline_text_data(
+                font,
+                TEXT_HEIGHT,
+                &[
+                    &endless,
+                    &daily,
+                    "[T] time attack  [C] challenges",
+                    "[O] settings  [I] stats",
+                ],
+            );
+            let x = canvas.width().saturating_sub(hint.width()) / 2;
+            draw_subcanvas(canvas, &hint, x, top + grid_height + CELL_GAP);
+        }
+
+        let shown = levels
+            .iter()
+            .enumerate()
+            .skip(first_row * COLUMNS)
+            .take(rows * COLUMNS);
+        for (index, level) in shown {
+            let x = left + (index % COLUMNS) * (CELL_WIDTH + CELL_GAP);
+            let y = top + (index / COLUMNS - first_row) * (CELL_HEIGHT + CELL_GAP);
+            let unlocked = is_unlocked(progress, index);
+
+            if index == self.cursor {
+                draw_focus_outline(canvas, x, y, CELL_WIDTH, CELL_HEIGHT, CURSOR_BORDER, focus);
+            }
+            let cell_color = if unlocked {
+                UNLOCKED_COLOR
+            } else {
+                LOCKED_COLOR
+            };
+            draw_rect(canvas, x, y, CELL_WIDTH, CELL_HEIGHT, cell_color);
+
+            if let (Some(font), true) = (font, unlocked) {
+                let level_progress = progress.level(index);
+                let name = format!("{} {}", index + 1, level.name);
+                let stars = format!(
+                    "{}{}",
+                    "*".repeat(level_progress.stars as usize),
+                    "-".repeat(3 - level_progress.stars as usize)
+                );
+                let mut best = format!("best {}", level_progress.best_score);
+                if let Some(ticks) = level_progress.best_ticks {
+                    best += &format!("  {}", format_ticks(ticks));
+                }
+                let text = compute_multiline_text_data(font, TEXT_HEIGHT, &[&name, &stars, &best]);
+                draw_subcanvas(canvas, &text, x + CURSOR_BORDER * 2, y + CURSOR_BORDER * 2);
+            }
+            // The level's bricks, in the top right corner of its cell
+            if let (Some(Some(bricks)), true) = (thumbnails.get(index), unlocked) {
+                let width = bricks.width() * THUMBNAIL_BRICK_WIDTH;
+                let dest = PixelRect {
+                    x: x + CELL_WIDTH - CURSOR_BORDER * 2 - width,
+                    y: y + CURSOR_BORDER * 2,
+                    width,
+                    height: bricks.height() * THUMBNAIL_BRICK_HEIGHT,
+                };
+                draw_subcanvas_scaled(canvas, bricks, dest, ScaleFilter::Nearest);
+            }
+        }
+    }
+}
+
+/// The thumbnail of each of `levels`. Levels don't change once loaded, so
+/// these are built once rather than every frame.
+pub fn thumbnails(levels: &[&Level]) -> Vec<Option<Canvas>> {
+    levels.iter().map(|level| thumbnail(level)).collect()
+}
+
+/// A picture of the bricks `level` starts with, one pixel per brick, with
+/// gaps and hidden secret bricks left the color of an unlocked cell, or
+/// `None` if it has no bricks. Locked cells show no thumbnail.
+fn thumbnail(level: &Level) -> Option<Canvas> {
+    let layout = level.layout.as_ref();
+    let columns = layout.map_or(STANDARD_COLUMNS, Layout::columns);
+    if columns == 0 {
+        return None;
+    }
+    let styles = layout.map_or(&ROW_STYLES[..], |layout| layout.styles);
+    let rows = level.rows.min(styles.len());
+    let mut buffer = Vec::with_capacity(columns * rows);
+    // Rows are numbered from the bottom up, but pictures go top down
+    for row in (0..rows).rev() {
+        for column in 0..columns {
+            let cell = layout.map(|layout| layout.cells[row][column]);
+            let color = if level.secret(row, column).is_some()
+                || matches!(cell, Some(Cell::Empty | Cell::Secret))
+            {
+                UNLOCKED_COLOR
+            } else if level.is_metal(row, column) || cell == Some(Cell::Metal) {
+                METAL_BRICK_COLOR
+            } else {
+                styles[row].0
+            };
+            buffer.push(color);
+        }
+    }
+    Some(Canvas {
+        buffer,
+        stride: columns,
+    })
+}
@@ -0,0 +1,153 @@
+//! Startup loading. Everything read from disk before the game can start is
+//! loaded on a background thread while the window shows a splash screen, so
+//! the window stays responsive however long loading takes.
+
+use crate::assets::{Assets, Handle};
+use crate::audio::SoundBank;
+use crate::challenges::{self, Leaderboards, Playlist};
+use crate::config::Config;
+use crate::level::{Level, LEVELS};
+use crate::level_file;
+use crate::paths::{self, Paths};
+use crate::progress::Progress;
+use crate::settings::Settings;
+use crate::stats::Stats;
+use crate::{draw_rect, Canvas};
+use rusttype::Font;
+use std::sync::mpsc::{self, Receiver};
+
+const FONT_PATH: &str = "fonts/RobotoMono/RobotoMono-VariableFont_wght.ttf";
+const CHALLENGES_DIR: &str = "assets/challenges";
+
+const BRICK_WIDTH: usize = 60;
+const BRICK_HEIGHT: usize = 24;
+const BRICK_GAP: usize = 8;
+const BRICK_BORDER: usize = 2;
+const BRICK_COLORS: [u32; 6] = [0xFF0000, 0xFF8000, 0xFFFF00, 0x00C000, 0x0000FF, 0x8000FF];
+
+/// Everything loaded at startup
+pub struct Loaded {
+    pub font: Handle<Font<'static>>,
+    pub config: Config,
+    pub settings: Settings,
+    pub progress: Progress,
+    pub stats: Stats,
+    pub leaderboards: Leaderboards,
+    pub sounds: SoundBank,
+    // Levels from level files, played after the built in ones
+    pub levels: Vec<&'static Level>,
+    // The game's challenge playlists, then the player's
+    pub playlists: Vec<&'static Playlist>,
+    // There was no config file, so the game hasn't been set up yet
+    pub first_run: bool,
+}
+
+pub enum LoadMessage {
+    // The number of loading steps finished so far
+    Progress(usize),
+    Finished(Result<Box<Loaded>, String>),
+}
+
+/// Each step loads one kind of asset
+pub const LOAD_STEPS: usize = 9;
+
+/// Starts loading on a new thread, which reports back through the returned
+/// channel
+pub fn spawn_loader(paths: Paths) -> Receiver<LoadMessage> {
+    let (sender, receiver) = mpsc::channel();
+    let spawned = std::thread::Builder::new()
+        .name(String::from("loader"))
+        .spawn({
+            let sender = sender.clone();
+            move || {
+                let mut step = 0;
+                let mut report = || {
+                    step += 1;
+                    let _ = sender.send(LoadMessage::Progress(step));
+                };
+                let loaded = (|| {
+                    let font = load_font()?;
+                    report();
+                    let first_run = !paths.config().exists();
+                    let config = Config::load(&paths.config());
+                    report();
+                    let settings = Settings::load(&paths.settings());
+                    report();
+                    let progress = Progress::load(&paths.progress());
+                    report();
+                    let stats = Stats::load(&paths.stats());
+                    report();
+                    let leaderboards = Leaderboards::load(&paths.leaderboards());
+                    report();
+                    let sounds = SoundBank::load(config.sound_pack.as_deref());
+                    report();
+                    let levels = level_file::load_dir(&paths.levels());
+                    report();
+                    let level_count = LEVELS.len() + levels.len();
+                    let mut playlists =
+                        challenges::load_dir(&paths::resource(CHALLENGES_DIR), level_count);
+                    for playlist in challenges::load_dir(&paths.challenges(), level_count) {
+                        if playlists.iter().any(|known| known.id == playlist.id) {
+                            eprintln!(
+                                "ERROR! Skipping challenge `{}`, there is already one by that name",
+                                playlist.id
+                            );
+                        } else {
+                            playlists.push(playlist);
+                        }
+                    }
+                    report();
+                    Ok(Box::new(Loaded {
+                        font,
+                        config,
+                        settings,
+                        progress,
+                        stats,
+                        leaderboards,
+                        sounds,
+                        levels,
+                        playlists,
+                        first_run,
+                    }))
+                })();
+                let _ = sender.send(LoadMessage::Finished(loaded));
+            }
+        });
+    if let Err(err) = spawned {
+        let _ = sender.send(LoadMessage::Finished(Err(format!(
+            "could not start the loading thread: {err}"
+        ))));
+    }
+    receiver
+}
+
+pub fn load_font() -> Result<Handle<Font<'static>>, String> {
+    Assets::default()
+        .load(&paths::resource(FONT_PATH))
+        .map_err(|err| err.to_string())
+}
+
+/// Shows loading progress as a row of bricks, one per step, that fill in as
+/// the steps finish. The font isn't available yet, so there's no text.
+pub fn draw_splash(canvas: &mut Canvas, steps_done: usize, background_color: u32) {
+    canvas.buffer.fill(background_color);
+    let row_width = LOAD_STEPS * (BRICK_WIDTH + BRICK_GAP) - BRICK_GAP;
+    let left = canvas.width().saturating_sub(row_width) / 2;
+    let top = canvas.height().saturating_sub(BRICK_HEIGHT) / 2;
+    for step in 0..LOAD_STEPS {
+        let x = left + step * (BRICK_WIDTH + BRICK_GAP);
+        let color = BRICK_COLORS[step % BRICK_COLORS.len()];
+        draw_rect(canvas, x, top, BRICK_WIDTH, BRICK_HEIGHT, color);
+        if step >= steps_done {
+            // Unfinished steps are drawn as hollow bricks
+            draw_rect(
+                canvas,
+                x + BRICK_BORDER,
+                top + BRICK_BORDER,
+                BRICK_WIDTH - 2 * BRICK_BORDER,
+                BRICK_HEIGHT - 2 * BRICK_BORDER,
+                background_color,
+            );
+        }
+    }
+}
@@ -1,12 +1,310 @@
+use assets::Handle;
+use audio::{Audio, Sfx};
+use autopilot::Autopilot;
+use boss::{Boss, BOSS_INTERVAL};
+use challenges::{ChallengeSelect, ChallengeSelection, Leaderboards, Playlist};
+use cheats::{Cheat, CheatCodes};
+use clock::{format_ticks, seconds, GameClock};
+use config::{Config, Hitboxes, PaddleShape};
+use daily::Daily;
+use gamepad::{GamepadInput, Gamepads, Pulse};
+use input::{AutoMove, KeyPresses};
+use level::{BrickAction, Cell, Layout, Level, Material, ENDLESS, LEVELS};
+use level_select::{LevelSelect, Selection};
+use loading::{draw_splash, spawn_loader, LoadMessage, Loaded};
 use minifb::{Key, Window, WindowOptions};
+use modifiers::{Modifier, ModifierEffects};
+use paths::Paths;
+use pause::{PauseAction, PauseMenu};
+use physics::constants::{
+    BALL_DIAMETER, BALL_SERVE_SPEED, CAPSULE_FALL_SPEED, FIELD_TOP, GRAVITY_MAX_SPEED,
+    GRAVITY_RISE, LASER_BOLT_SPEED, LEVEL_SERVE_SPEED_UP, MAX_LEVEL_SERVE_SPEED_UP, PADDLE_ENGLISH,
+    PADDLE_MAX_DEFLECTION, PADDLE_MIN_EXIT_ELEVATION, PADDLE_MOVEMENT_SPEED, PADDLE_NOTCH_WIDTH,
+    PADDLE_WIDTH, PADDLE_ZONES, SPEED_RAMP_STEP, SPLIT_BALL_ANGLE,
+};
+use physics::{predict_landing_x, while_non_negative, GapKind, PlayField, Side};
+use progress::Progress;
+use replay::{Recorder, RenderFrame, Replay};
+use rng::Rng;
 use rusttype::{point, Font, Scale};
+use saved_run::{SavedBrick, SavedRun};
+use settings::{Settings, SettingsAction, SettingsMenu, Slider};
+use setup::{SetupAction, SetupWizard};
+use stats::Stats;
+use std::sync::mpsc::TryRecvError;
+use timed::{EffectHandle, TimedEffects};
+use tricks::{Trick, TrickDetector};
+use tween::{Easing, Sequence, Tween};
+use units::{ScreenPos, Viewport, WorldPos, WorldVec};
+
+mod assets;
+mod audio;
+mod autopilot;
+mod bench;
+mod boss;
+mod challenges;
+mod cheats;
+mod clock;
+mod config;
+mod daily;
+mod demo;
+mod determinism;
+mod gamepad;
+mod gif;
+mod input;
+mod latency;
+mod level;
+mod level_file;
+mod level_select;
+mod loading;
+mod modifiers;
+mod paths;
+mod pause;
+mod physics;
+mod progress;
+mod replay;
+mod rng;
+mod saved_run;
+mod settings;
+mod setup;
+mod soak;
+mod stats;
+mod timed;
+mod tricks;
+mod tween;
+mod units;
+mod versioned;
 
 const WIDTH: usize = 600;
 const HEIGHT: usize = 600;
 
+const WINDOW_TITLE: &str = "BREAKRS";
+const RESET_WINDOW_FLAG: &str = "--reset-window";
+// With the speedrun timer on, the run time in the window title is updated
+// this often, which is as often as its tenths of a second change
+const TITLE_UPDATE_FRAMES: u32 = clock::TICKS_PER_SECOND / 10;
+// A failed window update is tried again this many times, waiting this long
+// before the first retry and twice as long before each one after
+const PRESENT_RETRIES: u32 = 4;
+const PRESENT_RETRY_WAIT: std::time::Duration = std::time::Duration::from_millis(10);
+// How often a window in the background checks whether it is back
+const INACTIVE_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(100);
+
 const MAGENTA: u32 = 0xFF00FF;
 const CYAN: u32 = 0x00FFFF;
 const YELLOW: u32 = 0xFFFF00;
+const GREEN: u32 = 0x20C020;
+const ORANGE: u32 = 0xFF8000;
+const PURPLE: u32 = 0x8040FF;
+const BLUE: u32 = 0x2060FF;
+
+const SECOND_PADDLE_TICKS: u32 = seconds(10);
+const MEGA_BALL_TICKS: u32 = seconds(10);
+const MEGA_BALL_SCALE: f32 = 2.0;
+const PADDLE_RESIZE_TICKS: u32 = seconds(10);
+const STICKY_PADDLE_TICKS: u32 = seconds(15);
+// While slow motion lasts, balls move this much of the way they would in a
+// tick
+const SLOW_MOTION_TICKS: u32 = seconds(8);
+const SLOW_MOTION_SCALE: f32 = 0.5;
+const FIREBALL_TICKS: u32 = seconds(6);
+// Fireballs leave a trail of this many fading copies of themselves, each
+// where the ball was this many ticks before the last
+const FIREBALL_TRAIL: usize = 4;
+const FIREBALL_TRAIL_SPACING: f32 = 2.0;
+const FIREBALL_COLOR: u32 = 0xFF5010;
+// A ball caught by the sticky paddle launches itself after this long
+const STICKY_HOLD_TICKS: u32 = seconds(3);
+// Dots drawn from a held ball along the way it will launch, this far apart
+const AIM_DOTS: usize = 4;
+const AIM_DOT_SPACING: f32 = 0.05;
+// A wide and a narrow paddle can be running at once, and then both apply
+const WIDE_PADDLE_SCALE: f32 = 1.5;
+const NARROW_PADDLE_SCALE: f32 = 0.6;
+// Debuffs, the narrow paddle included, wear off sooner than power-ups
+const DEBUFF_TICKS: u32 = seconds(6);
+// While a speed-up lasts, balls move this much further in a tick
+const SPEED_UP_SCALE: f32 = 1.5;
+// The ball light reaches this many pixels from the middle of the ball, and
+// adds this much of the ball color to the pixels right next to it
+const BALL_LIGHT_RADIUS: usize = 72;
+const BALL_LIGHT_STRENGTH: f32 = 0.35;
+// The replay zoom blows up the square this many pixels across around the
+// ball this many times, in the top right corner of the field
+const REPLAY_ZOOM_SOURCE: usize = 80;
+const REPLAY_ZOOM_SCALE: usize = 2;
+const REPLAY_ZOOM_MARGIN: usize = 10;
+const REPLAY_ZOOM_BORDER: usize = 2;
+// Balls smaller than this many pixels across are drawn at this size
+const MIN_BALL_PIXELS: usize = 8;
+
+const LASER_AMMO_PER_PICKUP: u32 = 12;
+const LASER_MAX_AMMO: u32 = 36;
+// Heat is a fraction of the overheat threshold; the gun locks out at 1.0
+// and only unlocks again once it has fully cooled down
+const LASER_HEAT_PER_SHOT: f32 = 0.3;
+const LASER_COOLING_PER_TICK: f32 = 0.008;
+const LASER_BOLT_WIDTH: f32 = 0.008;
+const LASER_BOLT_HEIGHT: f32 = 0.04;
+const LASER_BOLT_COLOR: u32 = 0xFF2020;
+// Power-up bricks drop a capsule that has to be caught with a paddle
+const CAPSULE_WIDTH: f32 = 0.08;
+const CAPSULE_HEIGHT: f32 = 0.03;
+const CAPSULE_COLOR: u32 = 0xC0C0C0;
+const DEBUFF_CAPSULE_COLOR: u32 = 0xA02020;
+// Only one in this many barrier capsules an endless wave draws is kept
+const BARRIER_RARITY: usize = 4;
+const BARRIER_HITS: u32 = 3;
+const BARRIER_TICKS: u32 = seconds(20);
+// The barrier's top is this far below the bottom of the paddle
+const BARRIER_GAP: f32 = 0.03;
+const BARRIER_HEIGHT: f32 = 0.012;
+const BARRIER_COLOR: u32 = 0x40C0FF;
+const BARRIER_CRACK_COLOR: u32 = 0x103040;
+// Where each crack runs across the barrier, as fractions of its width, in
+// the order they appear
+const BARRIER_CRACKS: [f32; 2] = [0.62, 0.27];
+// It blinks for this long before its time runs out
+const BARRIER_WARNING_TICKS: u32 = seconds(3);
+// Bosses drop hazards that stun a paddle they land on
+const HAZARD_SIZE: f32 = 0.04;
+const HAZARD_FALL_SPEED: f32 = 0.012;
+const HAZARD_COLOR: u32 = 0xFF3030;
+const STUN_TICKS: u32 = seconds(1);
+const BOSS_COLOR: u32 = 0x6A0DAD;
+const BOSS_FLASH_COLOR: u32 = 0xFFFFFF;
+const BOSS_HEALTH_COLOR: u32 = 0xE02020;
+const BOSS_HEALTH_BACKGROUND: u32 = 0x303030;
+// The health bar floats this far above the boss
+const BOSS_HEALTH_GAP: f32 = 0.02;
+const BOSS_HEALTH_HEIGHT: f32 = 0.015;
+const BOSS_HIT_POINTS: u32 = 50;
+const BOSS_DEFEAT_POINTS: u32 = 1000;
+
+// Freshly served balls can't be lost for this long and blink meanwhile
+const RESPAWN_INVULNERABLE_TICKS: u32 = seconds(1);
+const STARTING_LIVES: u32 = 3;
+// Bonus lives never take the count past this
+const MAX_LIVES: u32 = 5;
+// Secret bricks hold the letters of this word, and finding them all in one
+// run is worth a life
+const BONUS_WORD: &str = "EXTRA";
+
+const MAX_BALLS: usize = 8;
+// How far the upper paddle sits above the lower one with elevator paddles
+const ELEVATOR_PADDLE_SPACING: f32 = 0.25;
+const SPLIT_BALL_TICKS: u32 = seconds(5);
+
+// The first time in a level that a ball goes out of an open top
+const TOP_EXIT_POINTS: u32 = 100;
+// Each brick caught in a chain of explosions is worth this much extra for
+// every explosion in the chain, on top of its usual points
+const CHAIN_BONUS_POINTS: u32 = 5;
+const CHAIN_BANNER_TICKS: u32 = seconds(2);
+const MESSAGE_TICKS: u32 = seconds(3);
+// Score zones are tinted this much towards the tint for each step of their
+// multiplier, so double scoring is a faint wash
+const SCORE_ZONE_TINT: u32 = 0xFFD700;
+const SCORE_ZONE_TINT_AMOUNT: f32 = 0.08;
+const TOAST_TICKS: u32 = seconds(3);
+// Level select left alone this long starts a demo game
+const DEMO_IDLE_TICKS: u32 = seconds(30);
+// Sound cues grow and fade out over this long, and the oldest are dropped
+// past this many at once
+const SOUND_CUE_TICKS: u32 = seconds(1) / 2;
+const MAX_SOUND_CUES: usize = 24;
+// World units across a sound cue at its biggest
+const SOUND_CUE_SIZE: f32 = 0.05;
+const SOUND_CUE_COLOR: u32 = 0xFFFFFF;
+// Broken bricks tumble out of the field for this long, fading as they go,
+// and the oldest are dropped past this many at once
+const TUMBLE_TICKS: u32 = seconds(1);
+const MAX_TUMBLING_BRICKS: usize = 32;
+// World units per tick they pop up by, drift outwards by for each world
+// unit they are from the middle, and speed up falling by each tick
+const TUMBLE_POP: f32 = 0.008;
+const TUMBLE_DRIFT: f32 = 0.006;
+const TUMBLE_GRAVITY: f32 = 0.0008;
+// Radians a tick they spin by
+const TUMBLE_SPIN: f32 = 0.12;
+// Capsules turn this many radians for each world unit they fall
+const CAPSULE_SPIN: f32 = 12.0;
+// Stands for the see-through parts of a capsule's picture
+const CAPSULE_COLOR_KEY: u32 = 0x010101;
+// Predictions past this many bounces are too unreliable to show
+const LANDING_MARKER_MAX_BOUNCES: u32 = 3;
+const EXPLOSIVE_MARKER_COLOR: u32 = 0xFF4000;
+// Bricks with hit points to spare are drawn this much closer to steel grey
+// for each extra hit point
+const ARMORED_BRICK_COLOR: u32 = 0xA0A0A0;
+const ARMOR_TINT_PER_HIT_POINT: f32 = 0.3;
+const METAL_BRICK_COLOR: u32 = 0x8890A0;
+const METAL_SHINE_COLOR: u32 = 0xD8E0F0;
+// Bricks are lit from the top left: their top and left edges are mixed this
+// much towards white and their bottom and right edges towards black
+const BRICK_BEVEL_LIGHT: f32 = 0.4;
+const BRICK_BEVEL_DARK: f32 = 0.35;
+// Each brick casts a shadow down and to the right, darkest nearest the brick
+const BRICK_SHADOW_DARKNESS: f32 = 0.3;
+// Fills the screen outside a level's play field
+const WALL_COLOR: u32 = 0x404040;
+// Marks openings in the walls, a few pixels into the wall
+const WALL_GAP_COLOR: u32 = 0xFF2020;
+const WALL_WRAP_COLOR: u32 = 0x20A0FF;
+const OPEN_TOP_COLOR: u32 = 0xFFD700;
+const WALL_GAP_MARKER_WIDTH: usize = 4;
+// The bank multiplier grows by one step for every few seconds the ball
+// survives, up to a cap, and resets when a ball is missed
+const BANK_MULTIPLIER_STEP: f32 = 0.25;
+const BANK_MULTIPLIER_STEP_TICKS: u32 = seconds(5);
+const BANK_MAX_MULTIPLIER: f32 = 4.0;
+// The music intensity is only sent to the audio thread when it has moved
+// at least this far
+const MUSIC_INTENSITY_STEP: f32 = 0.05;
+const HUD_TEXT_HEIGHT: f32 = 20.0;
+// The HUD gets a strip of its own above the play field so the score never
+// covers the bricks
+const HUD_STRIP_HEIGHT: usize = 28;
+const HUD_STRIP_COLOR: u32 = 0xE0E0E0;
+const HUD_STRIP_MARGIN: usize = 8;
+const PANEL_BORDER: usize = 3;
+const PANEL_PADDING: usize = 8;
+
+const BRICK_DROP_INTERVAL_TICKS: u32 = seconds(30);
+const BRICK_DROP_COUNT: u32 = 4;
+// Endless runs add a row of bricks this often at first, a little more often
+// with every wave, down to the minimum
+const ENDLESS_FIRST_WAVE_TICKS: u32 = seconds(15);
+const ENDLESS_WAVE_SPEED_UP_TICKS: u32 = clock::TICKS_PER_SECOND / 2;
+const ENDLESS_MIN_WAVE_TICKS: u32 = seconds(6);
+// Bricks in endless runs score one more time their points for every this
+// many waves survived
+const ENDLESS_WAVES_PER_MULTIPLIER: u32 = 5;
+// Every this many waves, a new row has an explosive brick in it
+const ENDLESS_EXPLOSIVE_EVERY: u32 = 3;
+const RUMBLE_TICKS: u32 = 30;
+const RUMBLE_AMPLITUDE: f32 = 5.0;
+// The paddle flattens briefly when the ball bounces off it
+const PADDLE_SQUASH: f32 = 0.5;
+const PADDLE_SQUASH_TICKS: u32 = 4;
+const PADDLE_RECOVER_TICKS: u32 = 12;
+// The game over panel slides down from the top of the window after a
+// short pause
+const PANEL_DROP_DELAY_TICKS: u32 = 20;
+const PANEL_DROP_TICKS: u32 = 30;
+const FOCUS_TICKS: u32 = 8;
+// The level intro card is shown for a while before wiping away to reveal
+// the level
+const INTRO_CARD_TICKS: u32 = seconds(3) / 2;
+const INTRO_WIPE_TICKS: u32 = 24;
+// How long the level complete panel stays up before the next level loads
+const LEVEL_COMPLETE_TICKS: u32 = seconds(3);
+// Bricks stop dropping this close to the top of the paddle. In endless
+// runs, bricks pushed down this far end the run.
+const BRICK_DROP_FLOOR_MARGIN: f32 = 0.2;
+// A checkpoint is recorded each time the campaign reaches a multiple of
+// this many levels
+const CHECKPOINT_INTERVAL: usize = 2;
 
 type Res<T> = Result<T, ()>;
 
@@ -35,39 +333,161 @@ fn reflect(x: f32, y: f32, x_norm: f32, y_norm: f32) -> (f32, f32) {
     (x_reflect * mag, y_reflect * mag)
 }
 
-fn to_screen_coords(
-    world_x: f32,
-    world_y: f32,
-    screen_width: usize,
-    screen_height: usize,
-) -> (usize, usize) {
-    let x = (screen_width as f32 * (1.0 + world_x) / 2.0).ceil() as usize;
-    let y = screen_height - (screen_height as f32 * (1.0 + world_y) / 2.0).ceil() as usize;
-    (x, y)
-}
-
-fn draw_circle(canvas: &mut Canvas, x: usize, y: usize, diameter: usize, color: u32) {
-    let radius = diameter / 2;
-    let center_x = x + radius;
-    let center_y = y + radius;
-    for row in y..y + diameter {
-        for col in x..x + diameter {
-            let delta_x = center_x.abs_diff(col);
-            let delta_y = center_y.abs_diff(row);
-            if delta_x * delta_x + delta_y * delta_y <= radius * radius {
+fn rotate(x: f32, y: f32, angle: f32) -> (f32, f32) {
+    let (sin, cos) = angle.sin_cos();
+    (x * cos - y * sin, x * sin + y * cos)
+}
+
+/// Fills the ellipse inscribed in the `width` by `height` box at `(x, y)`,
+/// clipped to the canvas
+fn draw_ellipse(canvas: &mut Canvas, x: usize, y: usize, width: usize, height: usize, color: u32) {
+    let radius_x = width as f32 / 2.0;
+    let radius_y = height as f32 / 2.0;
+    let bottom = (y + height).min(canvas.height());
+    let right = (x + width).min(canvas.width());
+    for row in y..bottom {
+        for col in x..right {
+            // Sample each pixel at its center
+            let delta_x = (col - x) as f32 + 0.5 - radius_x;
+            let delta_y = (row - y) as f32 + 0.5 - radius_y;
+            let dist = (delta_x / radius_x).powi(2) + (delta_y / radius_y).powi(2);
+            if dist <= 1.0 {
                 canvas.buffer[row * canvas.stride + col] = color;
             }
         }
     }
 }
 
+/// Fills the `width` by `height` box at `(x, y)`, clipped to the canvas
 fn draw_rect(canvas: &mut Canvas, x: usize, y: usize, width: usize, height: usize, color: u32) {
-    for row in y..y + height {
-        let start = x + canvas.stride * row;
-        canvas.buffer[start..start + width].fill(color);
+    let bottom = (y + height).min(canvas.height());
+    let right = (x + width).min(canvas.width());
+    for row in y..bottom {
+        let start = row * canvas.stride;
+        canvas.buffer[start + x.min(right)..start + right].fill(color);
+    }
+}
+
+/// Fills a `width` by `height` box turned `angle` radians clockwise about
+/// its middle at `(center_x, center_y)`, clipped to the canvas. Each row is
+/// filled as one span, from where the row crosses into the box to where it
+/// crosses out, so it costs little more than an unturned box.
+fn draw_rotated_rect(
+    canvas: &mut Canvas,
+    center_x: f32,
+    center_y: f32,
+    width: f32,
+    height: f32,
+    angle: f32,
+    color: u32,
+) {
+    let (sin, cos) = angle.sin_cos();
+    let (half_width, half_height) = (width / 2.0, height / 2.0);
+    let reach_y = half_width * sin.abs() + half_height * cos.abs();
+    let top = (center_y - reach_y).floor().max(0.0) as usize;
+    let bottom = ((center_y + reach_y).ceil().max(0.0) as usize).min(canvas.height());
+
+    for row in top..bottom {
+        // Rows and columns are sampled at their middles
+        let delta_y = row as f32 + 0.5 - center_y;
+        // Along the box's width and then its height, how far a point in
+        // this row is from the middle is a straight line in its x offset
+        let (from_a, to_a) = slab(cos, delta_y * sin, half_width);
+        let (from_b, to_b) = slab(-sin, delta_y * cos, half_height);
+        let (from, to) = (from_a.max(from_b), to_a.min(to_b));
+        if from > to {
+            continue;
+        }
+        let left = (center_x + from - 0.5).ceil().max(0.0) as usize;
+        let right = ((center_x + to - 0.5).floor() + 1.0).max(0.0) as usize;
+        let right = right.min(canvas.width());
+        if left < right {
+            let start = row * canvas.stride;
+            canvas.buffer[start + left..start + right].fill(color);
+        }
+    }
+}
+
+/// The range of `t` where `|t * slope + offset| <= half`
+fn slab(slope: f32, offset: f32, half: f32) -> (f32, f32) {
+    if slope.abs() < f32::EPSILON {
+        return if offset.abs() <= half {
+            (f32::NEG_INFINITY, f32::INFINITY)
+        } else {
+            (f32::INFINITY, f32::NEG_INFINITY)
+        };
+    }
+    let (a, b) = ((-half - offset) / slope, (half - offset) / slope);
+    (a.min(b), a.max(b))
+}
+
+/// Draws `subcanvas` turned `angle` radians clockwise about its middle,
+/// which lands at `(center_x, center_y)`. Every pixel of the turned
+/// picture's bounding box looks up the nearest source pixel, skipping those
+/// outside the source and those equal to `color_key`.
+fn draw_rotated_subcanvas(
+    canvas: &mut Canvas,
+    subcanvas: &Canvas,
+    center_x: f32,
+    center_y: f32,
+    angle: f32,
+    color_key: Option<u32>,
+) {
+    let (sin, cos) = angle.sin_cos();
+    let (source_width, source_height) = (subcanvas.width(), subcanvas.height());
+    let (half_width, half_height) = (source_width as f32 / 2.0, source_height as f32 / 2.0);
+    let reach_x = half_width * cos.abs() + half_height * sin.abs();
+    let reach_y = half_width * sin.abs() + half_height * cos.abs();
+    let left = (center_x - reach_x).floor().max(0.0) as usize;
+    let right = ((center_x + reach_x).ceil().max(0.0) as usize).min(canvas.width());
+    let top = (center_y - reach_y).floor().max(0.0) as usize;
+    let bottom = ((center_y + reach_y).ceil().max(0.0) as usize).min(canvas.height());
+
+    for row in top..bottom {
+        let delta_y = row as f32 + 0.5 - center_y;
+        for col in left..right {
+            let delta_x = col as f32 + 0.5 - center_x;
+            // Turning back the other way finds the source pixel
+            let source_x = delta_x * cos + delta_y * sin + half_width;
+            let source_y = -delta_x * sin + delta_y * cos + half_height;
+            if source_x < 0.0 || source_y < 0.0 {
+                continue;
+            }
+            let (source_x, source_y) = (source_x as usize, source_y as usize);
+            if source_x >= source_width || source_y >= source_height {
+                continue;
+            }
+            let pixel = subcanvas.buffer[source_y * subcanvas.stride + source_x];
+            if color_key != Some(pixel) {
+                canvas.buffer[row * canvas.stride + col] = pixel;
+            }
+        }
     }
 }
 
+/// Lightens the top and left edges of the box at `(x, y)` and darkens its
+/// bottom and right edges, `size` pixels deep, so that a box of `color`
+/// looks raised
+fn draw_bevel(
+    canvas: &mut Canvas,
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+    size: usize,
+    color: u32,
+) {
+    let size = size.min(width / 2).min(height / 2);
+    let light = mix_colors(color, 0xFFFFFF, BRICK_BEVEL_LIGHT);
+    let dark = mix_colors(color, 0x000000, BRICK_BEVEL_DARK);
+    draw_rect(canvas, x, y + height - size, width, size, dark);
+    draw_rect(canvas, x + width - size, y, size, height, dark);
+    // The light edges stop short of the far corners, leaving the dark
+    // edges a mitre there
+    draw_rect(canvas, x, y, width - size, size, light);
+    draw_rect(canvas, x, y, size, height - size, light);
+}
+
 fn compute_text_data(font: &Font, text_height: f32, text: &str) -> Canvas {
     let height = text_height.ceil() as usize;
     let scale = Scale::uniform(text_height);
@@ -136,58 +556,434 @@ fn compute_multiline_text_data(font: &Font, text_height: f32, text: &[&str]) ->
 }
 
 fn draw_subcanvas(canvas: &mut Canvas, subcanvas: &Canvas, x: usize, y: usize) {
-    let pixel_height = subcanvas.buffer.len() / subcanvas.stride;
-    for row in 0..pixel_height {
-        let scanline_start = row * subcanvas.stride;
-        let canvas_start = x + (row + y) * canvas.stride;
-        canvas.buffer[canvas_start..canvas_start + subcanvas.stride]
-            .copy_from_slice(&subcanvas.buffer[scanline_start..scanline_start + subcanvas.stride]);
+    blit(
+        canvas,
+        subcanvas,
+        subcanvas.bounds(),
+        x as i32,
+        y as i32,
+        None,
+    );
+}
+
+/// Copies the `source` area of `subcanvas` onto `canvas` with its top left
+/// corner at `(x, y)`. Whatever falls outside either canvas is clipped, and
+/// source pixels equal to `color_key` are skipped so irregular shapes can be
+/// drawn from rectangular sprites.
+fn blit(
+    canvas: &mut Canvas,
+    subcanvas: &Canvas,
+    source: PixelRect,
+    x: i32,
+    y: i32,
+    color_key: Option<u32>,
+) {
+    let source = source.intersect(subcanvas.bounds());
+    // Skip the source columns and rows that land left of or above the canvas
+    let skip_x = x.min(0).unsigned_abs() as usize;
+    let skip_y = y.min(0).unsigned_abs() as usize;
+    let x = x.max(0) as usize;
+    let y = y.max(0) as usize;
+    let width = source
+        .width
+        .saturating_sub(skip_x)
+        .min(canvas.width().saturating_sub(x));
+    let height = source
+        .height
+        .saturating_sub(skip_y)
+        .min(canvas.height().saturating_sub(y));
+
+    for row in 0..height {
+        let source_start = (source.y + skip_y + row) * subcanvas.stride + source.x + skip_x;
+        let canvas_start = (y + row) * canvas.stride + x;
+        let source_line = &subcanvas.buffer[source_start..source_start + width];
+        let canvas_line = &mut canvas.buffer[canvas_start..canvas_start + width];
+        match color_key {
+            None => canvas_line.copy_from_slice(source_line),
+            Some(key) => {
+                for (pixel, &source_pixel) in canvas_line.iter_mut().zip(source_line) {
+                    if source_pixel != key {
+                        *pixel = source_pixel;
+                    }
+                }
+            }
+        }
     }
 }
 
-fn game_loop(window: &mut Window, game_state: &mut GameState, canvas: &mut Canvas) -> Res<()> {
-    while window.is_open() && !window.is_key_down(Key::Escape) {
-        game_state.tick();
-        game_state.draw_all(canvas);
+/// How `draw_subcanvas_scaled` picks a color for each pixel it draws
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ScaleFilter {
+    // The nearest source pixel, which keeps hard edges
+    Nearest,
+    // A blend of the four nearest source pixels, which is smoother
+    Bilinear,
+}
 
-        window
-            .update_with_buffer(&canvas.buffer, WIDTH, HEIGHT)
-            .map_err(|err| {
-                eprintln!("ERROR! Failed to update window: {err}");
-            })?;
+/// Draws all of `subcanvas` stretched or shrunk to fill `dest`. Whatever
+/// falls outside the canvas is clipped.
+fn draw_subcanvas_scaled(
+    canvas: &mut Canvas,
+    subcanvas: &Canvas,
+    dest: PixelRect,
+    filter: ScaleFilter,
+) {
+    let (source_width, source_height) = (subcanvas.width(), subcanvas.height());
+    if source_width == 0 || source_height == 0 || dest.width == 0 || dest.height == 0 {
+        return;
+    }
+    let x_scale = source_width as f32 / dest.width as f32;
+    let y_scale = source_height as f32 / dest.height as f32;
+    let source_pixel = |x: usize, y: usize| subcanvas.buffer[y * subcanvas.stride + x];
+    let shown = dest.intersect(canvas.bounds());
+
+    for y in shown.y..shown.y + shown.height {
+        // Where the middle of this row lands in the source
+        let source_y = ((y - dest.y) as f32 + 0.5) * y_scale;
+        let row = y * canvas.stride;
+        for x in shown.x..shown.x + shown.width {
+            let source_x = ((x - dest.x) as f32 + 0.5) * x_scale;
+            canvas.buffer[row + x] = match filter {
+                ScaleFilter::Nearest => source_pixel(
+                    (source_x as usize).min(source_width - 1),
+                    (source_y as usize).min(source_height - 1),
+                ),
+                ScaleFilter::Bilinear => {
+                    let (left, across) = bilinear_axis(source_x, source_width);
+                    let (top, down) = bilinear_axis(source_y, source_height);
+                    let right = (left + 1).min(source_width - 1);
+                    let bottom = (top + 1).min(source_height - 1);
+                    let upper =
+                        mix_colors(source_pixel(left, top), source_pixel(right, top), across);
+                    let lower = mix_colors(
+                        source_pixel(left, bottom),
+                        source_pixel(right, bottom),
+                        across,
+                    );
+                    mix_colors(upper, lower, down)
+                }
+            };
+        }
+    }
+}
+
+/// The source pixel whose middle is before `position` along one axis and
+/// how far past that middle `position` is, kept inside the `len` pixels
+/// there are
+fn bilinear_axis(position: f32, len: usize) -> (usize, f32) {
+    let position = (position - 0.5).clamp(0.0, (len - 1) as f32);
+    let before = position.floor();
+    (before as usize, position - before)
+}
+
+/// Frames the `width` by `height` box at `(x, y)` with an outline that
+/// marks it as the focused menu item. The outline grows out to `thickness`
+/// as `focus` goes from `0.0` to `1.0`, so newly focused items draw the eye.
+fn draw_focus_outline(
+    canvas: &mut Canvas,
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+    thickness: usize,
+    focus: f32,
+) {
+    let thickness = ((thickness as f32 * focus).round() as usize).max(1);
+    let left = x.saturating_sub(thickness);
+    let top = y.saturating_sub(thickness);
+    let outer_width = x + width + thickness - left;
+    draw_rect(canvas, left, top, outer_width, y - top, YELLOW);
+    draw_rect(canvas, left, y + height, outer_width, thickness, YELLOW);
+    draw_rect(canvas, left, y, x - left, height, YELLOW);
+    draw_rect(canvas, x + width, y, thickness, height, YELLOW);
+}
+
+/// Draws a one pixel line around the inside of the `width` by `height` box
+/// at `(x, y)`
+fn draw_outline(canvas: &mut Canvas, x: usize, y: usize, width: usize, height: usize, color: u32) {
+    if width == 0 || height == 0 {
+        return;
+    }
+    draw_rect(canvas, x, y, width, 1, color);
+    draw_rect(canvas, x, y + height - 1, width, 1, color);
+    draw_rect(canvas, x, y, 1, height, color);
+    draw_rect(canvas, x + width - 1, y, 1, height, color);
+}
+
+/// Draws `source` stretched over `dest` as a nine-slice: the `border` pixels
+/// on each side of the source keep their size, the edges between them
+/// stretch along one axis and the middle stretches along both, so one small
+/// image can frame a box of any size
+fn draw_nine_slice(canvas: &mut Canvas, source: &Canvas, border: usize, dest: PixelRect) {
+    // Maps a destination offset along one axis to a source offset
+    fn slice(offset: usize, dest_len: usize, source_len: usize, border: usize) -> usize {
+        let border = border.min(source_len / 2).min(dest_len / 2);
+        if offset < border {
+            offset
+        } else if offset >= dest_len - border {
+            source_len - (dest_len - offset)
+        } else {
+            let source_middle = source_len - 2 * border;
+            let dest_middle = dest_len - 2 * border;
+            border + (offset - border) * source_middle / dest_middle
+        }
+    }
+
+    let visible = dest.intersect(canvas.bounds());
+    for row in visible.y..visible.y + visible.height {
+        let source_y = slice(row - dest.y, dest.height, source.height(), border);
+        for col in visible.x..visible.x + visible.width {
+            let source_x = slice(col - dest.x, dest.width, source.width(), border);
+            canvas.buffer[row * canvas.stride + col] =
+                source.buffer[source_y * source.stride + source_x];
+        }
+    }
+}
+
+/// The border image that menu panels are nine-sliced from: a dark outline
+/// and a bevel around a white middle that matches the text background
+fn panel_image() -> Canvas {
+    const SIZE: usize = 2 * PANEL_BORDER + 1;
+    const RINGS: [u32; PANEL_BORDER] = [0x000000, 0x404040, 0xC0C0C0];
+    let mut buffer = vec![0xFFFFFF; SIZE * SIZE];
+    for (index, pixel) in buffer.iter_mut().enumerate() {
+        let (x, y) = (index % SIZE, index / SIZE);
+        let ring = x.min(y).min(SIZE - 1 - x).min(SIZE - 1 - y);
+        if let Some(&color) = RINGS.get(ring) {
+            *pixel = color;
+        }
+    }
+    Canvas {
+        buffer,
+        stride: SIZE,
+    }
+}
+
+/// Frames a `width` by `height` area at `(x, y)` in a panel, with padding
+/// between the frame and the content
+fn draw_panel(canvas: &mut Canvas, x: usize, y: usize, width: usize, height: usize) {
+    let inset = PANEL_BORDER + PANEL_PADDING;
+    let dest = PixelRect {
+        x: x.saturating_sub(inset),
+        y: y.saturating_sub(inset),
+        width: width + 2 * inset,
+        height: height + 2 * inset,
+    };
+    draw_nine_slice(canvas, &panel_image(), PANEL_BORDER, dest);
+}
+
+/// Darkens what has been drawn inside `rect` to half brightness, so an
+/// overlay stands out from the frame behind it
+fn dim_rect(canvas: &mut Canvas, rect: PixelRect) {
+    let rect = rect.intersect(canvas.bounds());
+    for row in rect.y..rect.y + rect.height {
+        let start = row * canvas.stride + rect.x;
+        for pixel in &mut canvas.buffer[start..start + rect.width] {
+            *pixel = (*pixel >> 1) & 0x7F7F7F;
+        }
+    }
+}
 
-        window.get_keys().iter().for_each(|key| match key {
-            Key::LeftShift | Key::RightShift => {
-                if window.is_key_down(Key::Equal) {
-                    game_state.update_ball_speed(1.05);
+/// Mixes `amount` of `to` into `from`, channel by channel
+fn mix_colors(from: u32, to: u32, amount: f32) -> u32 {
+    let amount = amount.clamp(0.0, 1.0);
+    [16, 8, 0].iter().fold(0, |mixed, shift| {
+        let a = ((from >> shift) & 0xFF) as f32;
+        let b = ((to >> shift) & 0xFF) as f32;
+        mixed | (((a + (b - a) * amount).round() as u32) << shift)
+    })
+}
+
+/// Shifts the play field `dx` pixels right and `dy` down, leaving the HUD
+/// strip where it is. Whatever goes past the edges is lost, and the band
+/// left behind is filled with `background`.
+fn shake_canvas(canvas: &mut Canvas, dx: i32, dy: i32, background: u32) {
+    let viewport = canvas.viewport();
+    let width = viewport.width as i32;
+    let (top, bottom) = (viewport.top as i32, (viewport.top + viewport.height) as i32);
+    let dx = dx.clamp(-width, width);
+    // Rows are moved in the order that reads each one before it is written
+    let mut rows = (top..bottom).collect::<Vec<_>>();
+    if dy > 0 {
+        rows.reverse();
+    }
+    for y in rows {
+        let start = y as usize * canvas.stride;
+        let from_y = y - dy;
+        if !(top..bottom).contains(&from_y) {
+            canvas.buffer[start..start + width as usize].fill(background);
+            continue;
+        }
+        // The part of the row it comes from that stays in the field
+        let from = from_y as usize * canvas.stride;
+        let (left, right) = ((-dx).max(0), (width - dx).min(width));
+        canvas.buffer.copy_within(
+            from + left as usize..from + right as usize,
+            start + (left + dx) as usize,
+        );
+        let band = if dx > 0 { 0..dx } else { width + dx..width };
+        canvas.buffer[start + band.start as usize..start + band.end as usize].fill(background);
+    }
+}
+
+/// Shows the splash screen until loading finishes. Returns `None` if the
+/// window is closed first.
+fn load_with_splash(
+    window: &mut Window,
+    canvas: &mut Canvas,
+    paths: &Paths,
+) -> Res<Option<Box<Loaded>>> {
+    let loader = spawn_loader(paths.clone());
+    let mut steps_done = 0;
+    while window.is_open() && !window.is_key_down(Key::Escape) {
+        loop {
+            match loader.try_recv() {
+                Ok(LoadMessage::Progress(steps)) => steps_done = steps,
+                Ok(LoadMessage::Finished(Ok(loaded))) => return Ok(Some(loaded)),
+                Ok(LoadMessage::Finished(Err(err))) => {
+                    eprintln!("ERROR! Could not load the game: {err}");
+                    return Err(());
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    eprintln!("ERROR! Loading stopped before it finished");
+                    return Err(());
                 }
             }
+        }
+
+        draw_splash(canvas, steps_done, CYAN);
+        present(window, canvas, WINDOW_TITLE)?;
+    }
+    Ok(None)
+}
 
-            Key::Minus => {
-                game_state.update_ball_speed(0.95);
+fn game_loop(window: &mut Window, game_state: &mut GameState, canvas: &mut Canvas) -> Res<()> {
+    let mut key_presses = KeyPresses::default();
+    let mut title = String::from(WINDOW_TITLE);
+    let mut frames = 0_u32;
+    let mut active = true;
+    while window.is_open() && !game_state.quit {
+        // A minimized or unfocused window gets no new frames, only enough
+        // updates to notice when it comes back
+        if !window.is_active() {
+            if active {
+                active = false;
+                game_state.window_deactivated();
             }
+            window.update();
+            std::thread::sleep(INACTIVE_POLL_INTERVAL);
+            continue;
+        }
+        if !active {
+            active = true;
+            game_state.audio.set_paused(false);
+        }
 
-            Key::A => {
-                game_state.paddle_vel_x = -game_state.paddle_movement_speed;
+        game_state.tick();
+        game_state.draw_all(canvas);
+
+        // Setting the title is a call into the OS, so it only happens a few
+        // times a second and when the title has changed
+        if frames.is_multiple_of(TITLE_UPDATE_FRAMES) {
+            let new_title = game_state.window_title();
+            if new_title != title {
+                window.set_title(&new_title);
+                title = new_title;
             }
+            // Kept up to date while the window is open, since it may not be
+            // possible to ask once it has closed
+            game_state.config.window_position = Some(window.get_position());
+        }
+        frames = frames.wrapping_add(1);
+
+        if present(window, canvas, &title).is_err() {
+            game_state.autosave_run();
+            return Err(());
+        }
 
-            Key::D => {
+        let (left, right) = game_state.config.controls.keys();
+        let keys = window.get_keys();
+        keys.iter().for_each(|&key| {
+            if key == left {
+                game_state.paddle_vel_x = -game_state.paddle_movement_speed;
+            } else if key == right {
                 game_state.paddle_vel_x = game_state.paddle_movement_speed;
             }
-
-            _ => (),
         });
 
-        window.get_keys_released().iter().for_each(|key| match key {
-            Key::A | Key::D => {
+        key_presses
+            .update(&keys)
+            .into_iter()
+            .for_each(|key| game_state.key_pressed(key));
+
+        let gamepad_inputs = game_state
+            .gamepads
+            .as_mut()
+            .map(Gamepads::poll)
+            .unwrap_or_default();
+        for input in gamepad_inputs {
+            match input {
+                GamepadInput::Press(key) => game_state.key_pressed(key),
+                GamepadInput::Move(amount) => {
+                    game_state.paddle_vel_x = amount * game_state.paddle_movement_speed;
+                }
+                GamepadInput::Connected { name, returning } => {
+                    game_state.gamepad_connected(&name, returning);
+                }
+                GamepadInput::Disconnected { name, active } => {
+                    game_state.gamepad_disconnected(&name, active);
+                }
+            }
+        }
+
+        window.get_keys_released().iter().for_each(|&key| {
+            if key == left || key == right {
                 game_state.paddle_vel_x = 0.0;
             }
-            _ => (),
         });
     }
     Ok(())
 }
 
+fn create_window() -> Res<Window> {
+    let mut window =
+        Window::new(WINDOW_TITLE, WIDTH, HEIGHT, WindowOptions::default()).map_err(|err| {
+            eprintln!("ERROR! Could not create window: {err}");
+        })?;
+    // Limit to max ~60 fps update rate
+    window.limit_update_rate(Some(std::time::Duration::from_micros(16600)));
+    Ok(window)
+}
+
+/// Shows the canvas in the window. A failed update is retried a few times,
+/// waiting longer each time, in case the failure passes. If it doesn't, the
+/// window is recreated with `title` once before giving up.
+fn present(window: &mut Window, canvas: &Canvas, title: &str) -> Res<()> {
+    let update = |window: &mut Window| {
+        let mut wait = PRESENT_RETRY_WAIT;
+        for attempt in 1..=PRESENT_RETRIES + 1 {
+            match window.update_with_buffer(&canvas.buffer, WIDTH, HEIGHT) {
+                Ok(()) => return Ok(()),
+                Err(err) => eprintln!("ERROR! Failed to update window (attempt {attempt}): {err}"),
+            }
+            // There's no point waiting after the last attempt
+            if attempt <= PRESENT_RETRIES {
+                std::thread::sleep(wait);
+                wait *= 2;
+            }
+        }
+        Err(())
+    };
+    if update(window).is_ok() {
+        return Ok(());
+    }
+    eprintln!("ERROR! Recreating the window");
+    *window = create_window()?;
+    window.set_title(title);
+    update(window)
+}
+
 struct Canvas {
     buffer: Vec<u32>,
     stride: usize,
@@ -201,334 +997,5119 @@ impl Canvas {
     fn height(&self) -> usize {
         self.buffer.len() / self.stride
     }
-}
-
-struct Bricks {
-    x_positions: Vec<f32>,
-    y_positions: Vec<f32>,
-    is_destroyed: Vec<bool>,
-    colors: Vec<u32>,
-    width: f32,
-    height: f32,
-}
-
-impl Bricks {
-    fn new() -> Self {
-        let mut x_positions = Vec::new();
-        let mut y_positions = Vec::new();
-        let mut colors = Vec::new();
-        let num_rows = 6;
-        let row_colors = [
-            0xFF0000_u32, // RED
-            0xFFA500_u32, // ORANGE
-            0x008000_u32, // GREEN
-            0x0000FF_u32, // BLUE
-            0x4B0082_u32, // INDIGO
-            0xEE82EE_u32, // VIOLET
-        ];
-        let brick_count = 13;
-        let width = 0.1385;
-        let gap_count = 14;
-        let gap_width = (2.0 - (width * brick_count as f32)) / gap_count as f32;
-        let height = width / 3.0;
-        let brick_y_pos = 0.60;
-        for (row, color) in row_colors.iter().enumerate().take(num_rows) {
-            let row_y = brick_y_pos + (row as f32 * (height + gap_width));
-            for b in 0..brick_count {
-                let brick_x_pos = -1.0 + ((b + 1) as f32 * gap_width) + (b as f32 * width);
 
-                x_positions.push(brick_x_pos);
-                y_positions.push(row_y);
-                colors.push(*color);
-            }
+    /// The play field: everything below the HUD strip
+    fn viewport(&self) -> Viewport {
+        Viewport {
+            width: self.width(),
+            height: self.height().saturating_sub(HUD_STRIP_HEIGHT),
+            top: HUD_STRIP_HEIGHT,
         }
-        Bricks {
-            x_positions,
-            y_positions,
-            is_destroyed: vec![false; brick_count * num_rows],
-            colors,
-            width,
-            height,
+    }
+
+    fn bounds(&self) -> PixelRect {
+        PixelRect {
+            x: 0,
+            y: 0,
+            width: self.width(),
+            height: self.height(),
         }
     }
 }
 
-struct GameState {
-    debug_stats: bool,
-    debug_stats_height: f32,
-    font: Option<Font<'static>>,
-    ball_pos_x: f32,
-    ball_pos_y: f32,
-    ball_vel_x: f32,
-    ball_vel_y: f32,
-    ball_diameter: f32,
-    ball_color: u32,
-    background_color: u32,
-    paddle_pos_x: f32,
-    paddle_pos_y: f32,
-    paddle_width: f32,
-    paddle_height: f32,
-    paddle_vel_x: f32,
-    paddle_movement_speed: f32,
-    paddle_color: u32,
-    bricks: Bricks,
+/// A rectangular area of a canvas, in pixels
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct PixelRect {
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
 }
 
-impl GameState {
-    fn paddle_collision(&self) -> Option<f32> {
-        let dx = self.ball_pos_x + self.ball_vel_x;
-        let dy = self.ball_pos_y + self.ball_vel_y;
-
-        if self.ball_vel_y < 0.0
-            && dx + self.ball_diameter >= self.paddle_pos_x
-            && dx < self.paddle_pos_x + self.paddle_width
-            && dy - self.ball_diameter <= self.paddle_pos_y
-            && dy >= self.paddle_pos_y - self.paddle_height
-        {
-            let extreme_left = self.paddle_pos_x - self.ball_diameter;
-            let extreme_right = self.paddle_pos_x + self.paddle_width;
-            let hit_location = (dx - extreme_left) / (extreme_right - extreme_left);
-            Some(hit_location)
-        } else {
-            None
+impl PixelRect {
+    /// The part of this rectangle that is also inside `other`
+    fn intersect(self, other: PixelRect) -> PixelRect {
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+        let right = (self.x + self.width).min(other.x + other.width);
+        let bottom = (self.y + self.height).min(other.y + other.height);
+        PixelRect {
+            x,
+            y,
+            width: right.saturating_sub(x),
+            height: bottom.saturating_sub(y),
         }
     }
+}
 
-    fn brick_collision(&self) -> Option<usize> {
-        let dx = self.ball_pos_x + self.ball_vel_x;
-        let dy = self.ball_pos_y + self.ball_vel_y;
-
-        self.bricks
-            .x_positions
-            .iter()
-            .enumerate()
-            .filter(|&(n, _)| !self.bricks.is_destroyed[n])
-            .filter(|&(_, x)| dx + self.ball_diameter >= *x && dx <= *x + self.bricks.width)
-            .find(|&(n, _)| {
-                let y = self.bricks.y_positions[n];
-                dy >= y - self.bricks.height && dy - self.ball_diameter <= y
-            })
-            .map(|(n, _)| n)
-    }
-
-    fn update_ball_pos(&mut self) {
-        let max_x = 1.0 - self.ball_diameter;
-        let min_y = -1.0 + self.ball_diameter;
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PowerUp {
+    SecondPaddle,
+    Laser,
+    SplitBall,
+    MegaBall,
+    MultiBall,
+    WidePaddle,
+    NarrowPaddle,
+    StickyPaddle,
+    SlowMotion,
+    Fireball,
+    // Debuffs, which get in the player's way
+    ReverseControls,
+    SpeedUp,
+    // Rare, so kept last for `spawn_wave` to leave out
+    Barrier,
+}
 
-        let dx = self.ball_pos_x + self.ball_vel_x;
-        let dy = self.ball_pos_y + self.ball_vel_y;
+impl PowerUp {
+    const ALL: [PowerUp; 13] = [
+        PowerUp::SecondPaddle,
+        PowerUp::Laser,
+        PowerUp::SplitBall,
+        PowerUp::MegaBall,
+        PowerUp::MultiBall,
+        PowerUp::WidePaddle,
+        PowerUp::NarrowPaddle,
+        PowerUp::StickyPaddle,
+        PowerUp::SlowMotion,
+        PowerUp::Fireball,
+        PowerUp::ReverseControls,
+        PowerUp::SpeedUp,
+        PowerUp::Barrier,
+    ];
 
-        // Check for paddle collision
-        let sqrt_3 = 3.0_f32.sqrt();
-        if let Some(location) = self.paddle_collision() {
-            // The angle of reflection is determined by
-            // where on the paddle the ball hits.
-            // We divide the paddle into thirds. The
-            // first third reflects in the negative x direction
-            // the middle third reflects about a vertical line
-            // the last third reflects in the positive x direction.
-            const PADDLE_DIV: f32 = 1.0 / 3.0;
-            let (rx, ry) = if location < PADDLE_DIV {
-                (-1.0, sqrt_3)
-            } else if location < PADDLE_DIV * 2.0 {
-                (0.0, 1.0)
-            } else {
-                (1.0, sqrt_3)
-            };
-            let (vx, vy) = reflect(self.ball_vel_x, self.ball_vel_y, rx, ry);
-            self.ball_vel_x = vx;
-            self.ball_vel_y = vy;
+    /// The name level files use for the power-up
+    fn name(self) -> &'static str {
+        match self {
+            PowerUp::SecondPaddle => "second_paddle",
+            PowerUp::Laser => "laser",
+            PowerUp::SplitBall => "split_ball",
+            PowerUp::MegaBall => "mega_ball",
+            PowerUp::MultiBall => "multi_ball",
+            PowerUp::WidePaddle => "wide_paddle",
+            PowerUp::NarrowPaddle => "narrow_paddle",
+            PowerUp::StickyPaddle => "sticky_paddle",
+            PowerUp::SlowMotion => "slow_motion",
+            PowerUp::Fireball => "fireball",
+            PowerUp::ReverseControls => "reverse_controls",
+            PowerUp::SpeedUp => "speed_up",
+            PowerUp::Barrier => "barrier",
         }
+    }
 
-        // Check for brick collision
-        if let Some(index) = self.brick_collision() {
-            self.ball_vel_y *= -1.0;
-            self.bricks.is_destroyed[index] = true;
-        }
+    /// Whether catching the capsule hurts rather than helps
+    fn is_debuff(self) -> bool {
+        matches!(
+            self,
+            PowerUp::NarrowPaddle | PowerUp::ReverseControls | PowerUp::SpeedUp
+        )
+    }
 
-        // Check for side walls collision
-        if dx <= -1.0 || dx >= max_x {
-            self.ball_vel_x = -self.ball_vel_x;
+    /// Debuff capsules are a warning red instead of silver
+    fn capsule_color(self) -> u32 {
+        if self.is_debuff() {
+            DEBUFF_CAPSULE_COLOR
+        } else {
+            CAPSULE_COLOR
         }
+    }
 
-        // Check for top and bottom wall collision
-        if dy <= min_y || dy >= 1.0 {
-            self.ball_vel_y = -self.ball_vel_y;
+    fn marker_color(self) -> u32 {
+        match self {
+            PowerUp::SecondPaddle => 0xFFFFFF,
+            PowerUp::Laser => 0x000000,
+            PowerUp::SplitBall => MAGENTA,
+            PowerUp::MegaBall => YELLOW,
+            PowerUp::MultiBall => CYAN,
+            PowerUp::WidePaddle => GREEN,
+            PowerUp::NarrowPaddle => ORANGE,
+            PowerUp::StickyPaddle => PURPLE,
+            PowerUp::SlowMotion => BLUE,
+            PowerUp::Fireball => FIREBALL_COLOR,
+            PowerUp::ReverseControls => 0xFF40A0,
+            PowerUp::SpeedUp => 0xFFFF80,
+            PowerUp::Barrier => BARRIER_COLOR,
         }
+    }
+}
 
-        self.ball_pos_x = if dx > max_x {
-            max_x - (dx - max_x)
-        } else if dx < -1.0 {
-            -1.0 + (-1.0 - dx)
-        } else {
-            dx
-        };
+#[derive(Default)]
+struct LaserGun {
+    ammo: u32,
+    heat: f32,
+    overheated: bool,
+}
 
-        self.ball_pos_y = if dy > 1.0 {
-            1.0 - (dy - 1.0)
-        } else if dy < min_y {
-            min_y + (min_y - dy)
-        } else {
-            dy
-        };
+impl LaserGun {
+    fn reload(&mut self) {
+        self.add_ammo(LASER_AMMO_PER_PICKUP);
     }
 
-    fn update_paddle_pos(&mut self) {
-        let max_x = 1.0 - self.paddle_width;
-        self.paddle_pos_x = (self.paddle_pos_x + self.paddle_vel_x).clamp(-1.0, max_x);
+    fn add_ammo(&mut self, ammo: u32) {
+        self.ammo = (self.ammo + ammo).min(LASER_MAX_AMMO);
     }
 
-    fn tick(&mut self) {
-        self.update_ball_pos();
-        self.update_paddle_pos();
+    fn can_fire(&self) -> bool {
+        self.ammo > 0 && !self.overheated
     }
 
-    fn update_ball_speed(&mut self, factor: f32) {
-        self.ball_vel_x *= factor;
-        self.ball_vel_y *= factor;
+    fn fire(&mut self) {
+        self.ammo -= 1;
+        self.heat += LASER_HEAT_PER_SHOT;
+        if self.heat >= 1.0 {
+            self.heat = 1.0;
+            self.overheated = true;
+        }
     }
 
-    fn draw_ball(&self, canvas: &mut Canvas) {
-        let (x, y) = to_screen_coords(
-            self.ball_pos_x,
-            self.ball_pos_y,
-            canvas.width(),
-            canvas.height(),
-        );
-        let screen_diameter = (self.ball_diameter * canvas.stride as f32 / 2.0) as usize;
-        draw_circle(canvas, x, y, screen_diameter, self.ball_color);
+    fn cool(&mut self) {
+        self.heat = (self.heat - LASER_COOLING_PER_TICK).max(0.0);
+        if self.heat == 0.0 {
+            self.overheated = false;
+        }
     }
+}
 
-    fn draw_paddle(&self, canvas: &mut Canvas) {
-        let (x, y) = to_screen_coords(
-            self.paddle_pos_x,
-            self.paddle_pos_y,
-            canvas.width(),
-            canvas.height(),
-        );
-        let screen_height = canvas.buffer.len() / canvas.stride;
-        let width = (self.paddle_width / 2.0 * canvas.stride as f32) as usize;
-        let height = (self.paddle_height / 2.0 * screen_height as f32) as usize;
-        draw_rect(canvas, x, y, width, height, self.paddle_color);
+struct LaserBolt {
+    pos: WorldPos,
+}
+
+/// A wall across the field just below the paddle, which bounces balls the
+/// paddle misses until it has taken a few hits or its time runs out
+#[derive(Clone, Copy)]
+struct Barrier {
+    top: f32,
+    hits_left: u32,
+    // The effect timing how long it lasts
+    expires: EffectHandle,
+}
+
+impl Barrier {
+    /// Whether a ball whose bottom moves from `from_y` to `to_y` hits the
+    /// barrier on the way down
+    fn catches(&self, from_y: f32, to_y: f32) -> bool {
+        from_y >= self.top && to_y < self.top
     }
 
-    fn draw_debug_stats(&self, canvas: &mut Canvas) {
-        let ball_position = format!(
-            "{pos:<12} ({pos_x:+.3}, {pos_y:+.3})",
-            pos = "pos:",
-            pos_x = self.ball_pos_x,
-            pos_y = self.ball_pos_y
-        );
-        let ball_velocity = format!(
-            "{vel:<12} ({vel_x:+.3}, {vel_y:+.3})",
-            vel = "vel:",
-            vel_x = self.ball_vel_x,
-            vel_y = self.ball_vel_y
-        );
-        let paddle_pos = format!(
-            "{pos:<12} ({pos_x:+.3}, {pos_y:+.3})",
-            pos = "paddle_pos:",
-            pos_x = self.paddle_pos_x,
-            pos_y = self.paddle_pos_y
-        );
-        let text_canvas = compute_multiline_text_data(
-            self.font
-                .as_ref()
-                .expect("Method is only called if font.is_some()"),
-            self.debug_stats_height,
-            &[&ball_position, &ball_velocity, &paddle_pos],
-        );
-        draw_subcanvas(canvas, &text_canvas, 0, 0);
+    /// Takes a hit, returning whether that broke the barrier
+    fn hit(&mut self) -> bool {
+        self.hits_left = self.hits_left.saturating_sub(1);
+        self.hits_left == 0
     }
 
-    fn draw_bricks(&self, canvas: &mut Canvas) {
-        let width = (self.bricks.width / 2.0 * canvas.width() as f32).ceil() as usize;
-        let height = (self.bricks.height / 2.0 * canvas.height() as f32).ceil() as usize;
-        for (((brick_x, brick_y), color), destroyed) in self
-            .bricks
-            .x_positions
-            .iter()
-            .zip(self.bricks.y_positions.iter())
-            .zip(self.bricks.colors.iter())
-            .zip(self.bricks.is_destroyed.iter())
-        {
-            if !destroyed {
-                let (x, y) = to_screen_coords(*brick_x, *brick_y, canvas.width(), canvas.height());
-                draw_rect(canvas, x, y, width, height, *color);
-            }
-        }
+    /// How many hits the barrier has taken, each one a crack in it
+    fn cracks(&self) -> u32 {
+        BARRIER_HITS - self.hits_left
     }
+}
 
-    fn draw_all(&self, canvas: &mut Canvas) {
-        canvas.buffer.fill(self.background_color);
-        self.draw_ball(canvas);
-        self.draw_paddle(canvas);
-        self.draw_bricks(canvas);
+/// A power-up falling from a broken brick, which takes effect if a paddle
+/// catches it
+#[derive(Clone, Copy)]
+struct Capsule {
+    pos: WorldPos,
+    power_up: PowerUp,
+}
 
-        if self.debug_stats && self.font.is_some() {
-            self.draw_debug_stats(canvas);
-        }
+#[derive(Clone, Copy)]
+struct Ball {
+    pos: WorldPos,
+    // World units per tick
+    vel: WorldVec,
+    // Balls created by a split only live until this effect expires
+    split: Option<EffectHandle>,
+    invulnerable: Option<EffectHandle>,
+    // Waiting to be served, or caught by a sticky paddle, and riding on
+    // the paddle until launched
+    held: Option<Hold>,
+}
+
+/// Where a ball meets a paddle during a tick
+struct PaddleContact {
+    paddle: PaddleId,
+    // How far across the paddle the ball hits
+    location: f32,
+    // The ball had already gone past the paddle when they met, and was saved
+    // by the grace window
+    late: bool,
+}
+
+/// Where a held ball sits on the paddle that caught it
+#[derive(Clone, Copy)]
+struct Hold {
+    paddle: PaddleId,
+    // How far across the paddle it was caught, as for `paddle_bounce`
+    location: f32,
+    // A caught ball launches itself when this effect expires. A serve has
+    // none, and waits for the player.
+    release: Option<EffectHandle>,
+}
+
+impl Hold {
+    fn is_serve(&self) -> bool {
+        self.release.is_none()
+    }
+}
+
+impl Ball {
+    /// A ball at the center of the field heading off at `angle` radians
+    /// counterclockwise from the positive x axis
+    fn serve(speed: f32, angle: f32) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Ball {
+            pos: WorldPos::new(0.0, 0.0),
+            vel: WorldVec::new(speed * cos, speed * sin),
+            split: None,
+            invulnerable: None,
+            held: None,
+        }
+    }
+}
+
+/// Everything that lasts for a limited time, tracked by
+/// [`GameState::timed_effects`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum TimedEffect {
+    SecondPaddle,
+    Invulnerable,
+    SplitBall,
+    MegaBall,
+    WidePaddle,
+    NarrowPaddle,
+    StickyPaddle,
+    Hold,
+    SlowMotion,
+    Fireball,
+    ReverseControls,
+    SpeedUp,
+    Barrier,
+    // A hazard hit the paddle, which can't move until this runs out
+    Stunned,
+}
+
+/// Which paddle is which, staying the same as other paddles come and go
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct PaddleId {
+    // Index into `GameState::paddle_levels`
+    level: usize,
+    // The second paddle's mirrored partner of the paddle at that level
+    mirrored: bool,
+}
+
+impl PaddleId {
+    /// The paddle the player always has
+    const LOWEST: PaddleId = PaddleId {
+        level: 0,
+        mirrored: false,
+    };
+}
+
+/// A paddle's top left corner and its size
+#[derive(Clone, Copy)]
+struct PaddleRect {
+    id: PaddleId,
+    pos: WorldPos,
+    size: WorldVec,
+    // How far it moved across this tick
+    moved_x: f32,
+}
+
+enum Scene {
+    LevelSelect(LevelSelect),
+    Playing,
+    // Shows the level about to be played. The value is how far the card
+    // has wiped away.
+    Intro(Sequence),
+    LevelComplete(LevelComplete),
+    // Plays back the last moments before a ball was lost, with the next
+    // ball already served and waiting
+    Replay(Replay),
+    Paused(PauseMenu),
+    GameOver,
+    PickModifier(ModifierPick),
+    Challenges(ChallengeSelect),
+    Settings(SettingsMenu),
+    Setup(SetupWizard),
+    // Holds the level select to go back to
+    Stats(LevelSelect),
+}
+
+impl Scene {
+    /// The focused item of a menu, or `None` outside of menus
+    fn focus(&self) -> Option<usize> {
+        match self {
+            Scene::LevelSelect(level_select) => Some(level_select.cursor),
+            Scene::PickModifier(pick) => Some(pick.cursor),
+            Scene::Challenges(challenge_select) => Some(challenge_select.cursor),
+            Scene::Settings(menu) => Some(menu.cursor),
+            Scene::Setup(wizard) => Some(wizard.focus()),
+            Scene::Paused(menu) => Some(menu.cursor),
+            Scene::Playing
+            | Scene::Intro(_)
+            | Scene::LevelComplete(_)
+            | Scene::Replay(_)
+            | Scene::GameOver
+            | Scene::Stats(_) => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum RunMode {
+    Campaign,
+    Modifiers,
+    Challenge { playlist: usize, position: usize },
+    // One level that never ends, with bricks coming in from the top
+    Endless,
+    // One level against the clock
+    TimeAttack,
+    // Today's made up level, scored against the day's best
+    Daily,
+    // Played by the autopilot when nobody is at the menu, keeping nothing
+    Demo,
+}
+
+/// Shown over the cleared level before moving on to the next one
+struct LevelComplete {
+    next: usize,
+    name: &'static str,
+    score: u32,
+    // Only campaign levels award stars
+    stars: Option<u8>,
+    ticks_left: u32,
+}
+
+struct ModifierPick {
+    choices: [Modifier; 3],
+    cursor: usize,
+}
+
+/// A brick a ball is about to hit, and which parts of the ball's velocity
+/// the hit reverses
+struct BrickHit {
+    brick: usize,
+    flip_x: bool,
+    flip_y: bool,
+}
+
+/// What destroyed a brick
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Cause {
+    Ball,
+    Laser,
+    Explosion,
+    // A ball with the fireball power-up, which burns through bricks
+    Fireball,
+}
+
+/// The bricks destroyed by explosions set off by a single hit
+#[derive(Clone, Copy, Debug)]
+struct Chain {
+    // The brick whose destruction started the chain
+    trigger: usize,
+    // Bricks destroyed by explosions, not counting the trigger
+    bricks: u32,
+    explosions: u32,
+}
+
+impl Chain {
+    fn bonus(&self) -> u32 {
+        CHAIN_BONUS_POINTS * self.bricks * self.explosions
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum GameEvent {
+    // A ball hit a brick, coming down onto its top or otherwise. Sent before
+    // whatever the hit does to the brick.
+    BallHitBrick { from_above: bool },
+    // A brick of the material took a hit and has hit points left, or is
+    // metal
+    BrickDamaged(Material),
+    // With the points the brick was worth, after any score zone it was in,
+    // and what it was made of
+    BrickDestroyed(Cause, u32, Material),
+    // A secret brick next to a broken one came out of hiding
+    BrickRevealed,
+    LetterFound(char),
+    // Sent after the `BrickDestroyed` events of the bricks in the chain
+    Chain(Chain),
+    PaddleHit,
+    // A ball bounced off the barrier, and whether that broke it
+    BarrierHit { broken: bool },
+    // A ball came off a side wall or the top
+    WallBounce,
+    // A ball hit the boss and it has health left
+    BossHit,
+    BossDefeated,
+    PaddleStunned,
+    BallMissed,
+    // A ball went out of an open top
+    TopExit,
+}
+
+/// Chains set off during the current run
+#[derive(Default)]
+struct ChainStats {
+    count: u32,
+    longest: u32,
+    bonus: u32,
+}
+
+/// A picture of a game sound, drawn where the sound came from for players
+/// who can't hear it
+struct SoundCue {
+    sfx: Sfx,
+    pos: WorldPos,
+    // Goes from 0.0 to 1.0 while the cue grows and fades
+    age: Tween,
+}
+
+/// A broken brick falling out of the field, spinning as it goes
+struct TumblingBrick {
+    // Its middle
+    pos: WorldPos,
+    vel: WorldVec,
+    angle: f32,
+    spin: f32,
+    color: u32,
+    ticks_left: u32,
+}
+
+/// Shows the bonus for the latest chain next to the brick that set it off
+struct ChainBanner {
+    pos: WorldPos,
+    bricks: u32,
+    points: u32,
+    // Rises from 0.0 to 1.0 while the banner floats up
+    rise: Tween,
+}
+
+#[derive(Default)]
+struct ScoreBank {
+    points: u32,
+    alive_ticks: u32,
+}
+
+impl ScoreBank {
+    fn multiplier(&self) -> f32 {
+        let steps = (self.alive_ticks / BANK_MULTIPLIER_STEP_TICKS) as f32;
+        (1.0 + steps * BANK_MULTIPLIER_STEP).min(BANK_MAX_MULTIPLIER)
+    }
+
+    fn deposit(&mut self, points: u32) {
+        self.points += points;
+    }
+
+    fn commit(&mut self) -> u32 {
+        let committed = (self.points as f32 * self.multiplier()).round() as u32;
+        self.points = 0;
+        committed
+    }
+
+    fn forfeit(&mut self) {
+        self.points = 0;
+        self.alive_ticks = 0;
+    }
+}
+
+enum LevelEventKind {
+    BrickDrop,
+    // A new row of bricks in an endless run
+    Wave,
+}
+
+struct LevelEvent {
+    at_tick: u32,
+    kind: LevelEventKind,
+}
+
+fn default_level_events() -> Vec<LevelEvent> {
+    (1..=BRICK_DROP_COUNT)
+        .map(|n| LevelEvent {
+            at_tick: n * BRICK_DROP_INTERVAL_TICKS,
+            kind: LevelEventKind::BrickDrop,
+        })
+        .collect()
+}
+
+// The color of each row of bricks from the bottom up and the points its
+// bricks are worth. Like the original Breakout, the higher rows are worth
+// more, topped by the red row.
+const ROW_STYLES: [(u32, u32); 6] = [
+    (0xEE82EE, 10), // VIOLET
+    (0x4B0082, 20), // INDIGO
+    (0x0000FF, 30), // BLUE
+    (0x008000, 40), // GREEN
+    (0xFFA500, 50), // ORANGE
+    (0xFF0000, 70), // RED
+];
+const STANDARD_COLUMNS: usize = 13;
+// The top of the lowest row of bricks, with the top of the field at the top
+// of the screen
+const BRICKS_BOTTOM: f32 = 0.60;
+
+struct Bricks {
+    x_positions: Vec<f32>,
+    y_positions: Vec<f32>,
+    is_destroyed: Vec<bool>,
+    colors: Vec<u32>,
+    // What breaking each brick scores
+    points: Vec<u32>,
+    power_ups: Vec<Option<PowerUp>>,
+    // Explosive bricks destroy the bricks around them
+    explosive: Vec<bool>,
+    // Balls and lasers take one hit point a hit, the brick goes at zero
+    hit_points: Vec<u8>,
+    // Metal bricks bounce balls but never break, and don't need to be
+    // destroyed to clear the level
+    metal: Vec<bool>,
+    // Secret bricks start out hidden, neither drawn nor hit, until a brick
+    // next to them breaks. They are a bonus, so the level clears without
+    // them.
+    secret: Vec<bool>,
+    hidden: Vec<bool>,
+    // The bonus letter in each brick, only ever in secret ones
+    letters: Vec<Option<char>>,
+    // What each brick sounds like when hit
+    materials: Vec<Material>,
+    // Bricks are stored row by row, this many to a row
+    columns: usize,
+    width: f32,
+    height: f32,
+    row_pitch: f32,
+}
+
+impl Bricks {
+    fn new(level: &Level, field: &PlayField) -> Self {
+        let num_rows = level.rows;
+        let mut x_positions = Vec::new();
+        let mut y_positions = Vec::new();
+        let mut colors = Vec::new();
+        let mut points = Vec::new();
+        let mut power_ups = Vec::new();
+        let mut explosive = Vec::new();
+        let mut hit_points = Vec::new();
+        let mut metal = Vec::new();
+        let mut secret = Vec::new();
+        let mut letters = Vec::new();
+        let mut materials = Vec::new();
+        let mut is_destroyed = Vec::new();
+        let layout = level.layout.as_ref();
+        let row_styles = layout.map_or(&ROW_STYLES[..], |layout| layout.styles);
+        let brick_count = layout.map_or(STANDARD_COLUMNS, Layout::columns);
+        let (width, gap_width) = Bricks::spacing(brick_count, field);
+        let height = width / 3.0;
+        let brick_y_pos = BRICKS_BOTTOM - (FIELD_TOP - field.top);
+        for (row, &(color, row_points)) in row_styles.iter().enumerate().take(num_rows) {
+            let row_y = brick_y_pos + (row as f32 * (height + gap_width));
+            for b in 0..brick_count {
+                let brick_x_pos = field.left + ((b + 1) as f32 * gap_width) + (b as f32 * width);
+
+                x_positions.push(brick_x_pos);
+                y_positions.push(row_y);
+                points.push(row_points);
+                let level_secret = level.secret(row, b);
+                secret.push(level_secret.is_some());
+                letters.push(level_secret.and_then(|secret| secret.letter));
+                if let Some(layout) = layout {
+                    let cell = layout.cells[row][b];
+                    let is_metal = cell == Cell::Metal;
+                    metal.push(is_metal);
+                    materials.push(if is_metal {
+                        Material::Metal
+                    } else {
+                        level.row_material(row)
+                    });
+                    colors.push(if is_metal { METAL_BRICK_COLOR } else { color });
+                    // Gaps in the grid are bricks that start out destroyed
+                    is_destroyed.push(cell == Cell::Empty);
+                    power_ups.push(
+                        layout
+                            .power_ups
+                            .iter()
+                            .find(|&&(r, c, _)| (r, c) == (row, b))
+                            .map(|&(_, _, power_up)| power_up),
+                    );
+                    explosive.push(cell == Cell::Explosive);
+                    hit_points.push(match cell {
+                        Cell::Brick { hit_points } => hit_points,
+                        _ => 1,
+                    });
+                    continue;
+                }
+
+                is_destroyed.push(false);
+                let is_metal = level.is_metal(row, b);
+                metal.push(is_metal);
+                materials.push(if is_metal {
+                    Material::Metal
+                } else {
+                    level.row_material(row)
+                });
+                colors.push(if is_metal { METAL_BRICK_COLOR } else { color });
+                // Every odd row carries a power-up in its center brick,
+                // every even row carries one in its outermost bricks, the
+                // fifth row has a pair of split-ball bricks, the third a
+                // pair of mega-ball bricks and the first a pair of
+                // multi-ball bricks. The second row widens the paddle and
+                // the sixth narrows it. The fifth also has a pair of sticky
+                // paddle bricks, the third a pair of slow motion bricks and
+                // the sixth a pair of fireball bricks. The fourth row has a
+                // pair of reversed control bricks and the first a pair of
+                // speed-up bricks, to keep the player on their toes. A
+                // single barrier brick sits in the middle of the third.
+                let power_up = if is_metal {
+                    None
+                } else if row % 2 == 1 && b == brick_count / 2 {
+                    Some(PowerUp::SecondPaddle)
+                } else if row % 2 == 0 && (b == 0 || b == brick_count - 1) {
+                    Some(PowerUp::Laser)
+                } else if row == 4 && (b == 3 || b == brick_count - 4) {
+                    Some(PowerUp::SplitBall)
+                } else if row == 2 && (b == 3 || b == brick_count - 4) {
+                    Some(PowerUp::MegaBall)
+                } else if row == 0 && (b == 3 || b == brick_count - 4) {
+                    Some(PowerUp::MultiBall)
+                } else if row == 1 && (b == 2 || b == brick_count - 3) {
+                    Some(PowerUp::WidePaddle)
+                } else if row == 5 && (b == 2 || b == brick_count - 3) {
+                    Some(PowerUp::NarrowPaddle)
+                } else if row == 4 && (b == 1 || b == brick_count - 2) {
+                    Some(PowerUp::StickyPaddle)
+                } else if row == 2 && (b == 1 || b == brick_count - 2) {
+                    Some(PowerUp::SlowMotion)
+                } else if row == 5 && (b == 1 || b == brick_count - 2) {
+                    Some(PowerUp::Fireball)
+                } else if row == 3 && (b == 2 || b == brick_count - 3) {
+                    Some(PowerUp::ReverseControls)
+                } else if row == 0 && (b == 1 || b == brick_count - 2) {
+                    Some(PowerUp::SpeedUp)
+                } else if row == 2 && b == brick_count / 2 {
+                    Some(PowerUp::Barrier)
+                } else {
+                    None
+                };
+                power_ups.push(power_up);
+                // Two pairs of explosive bricks sit diagonally next to each
+                // other in the second and third rows, so each one sets off
+                // the other
+                explosive.push(!is_metal && matches!((row, b), (1, 4) | (2, 5) | (1, 8) | (2, 7)));
+                hit_points.push(if is_metal {
+                    1
+                } else {
+                    level.row_hit_points(row)
+                });
+            }
+        }
+        Bricks {
+            x_positions,
+            y_positions,
+            is_destroyed,
+            colors,
+            points,
+            power_ups,
+            explosive,
+            hit_points,
+            metal,
+            hidden: secret.clone(),
+            secret,
+            letters,
+            materials,
+            columns: brick_count,
+            width,
+            height,
+            row_pitch: height + gap_width,
+        }
+    }
+
+    /// The width of each brick and of the gaps between them with `columns`
+    /// bricks to a row. Bricks shrink to keep thirteen to a row in narrower
+    /// fields, or as many as a level file lays out.
+    fn spacing(columns: usize, field: &PlayField) -> (f32, f32) {
+        let width = 0.1385 * field.width() / 2.0 * (STANDARD_COLUMNS as f32 / columns as f32);
+        let gap_count = columns + 1;
+        let gap_width = (field.width() - (width * columns as f32)) / gap_count as f32;
+        (width, gap_width)
+    }
+
+    /// How many rows of `columns` bricks fit between where the bricks start
+    /// and the top of the field
+    fn max_rows(columns: usize, field: &PlayField) -> usize {
+        let (width, gap_width) = Bricks::spacing(columns, field);
+        let row_pitch = width / 3.0 + gap_width;
+        ((FIELD_TOP - BRICKS_BOTTOM) / row_pitch) as usize + 1
+    }
+
+    /// The up to eight bricks touching a brick, destroyed or not
+    fn neighbors(&self, index: usize) -> impl Iterator<Item = usize> {
+        let columns = self.columns as isize;
+        let rows = (self.is_destroyed.len() / self.columns) as isize;
+        let (row, column) = (index as isize / columns, index as isize % columns);
+        (-1..=1)
+            .flat_map(move |dr| (-1..=1).map(move |dc| (row + dr, column + dc)))
+            .filter(move |&(r, c)| {
+                (r, c) != (row, column) && (0..rows).contains(&r) && (0..columns).contains(&c)
+            })
+            .map(move |(r, c)| (r * columns + c) as usize)
+    }
+
+    /// Whether balls and lasers hit the brick
+    fn is_solid(&self, index: usize) -> bool {
+        !self.is_destroyed[index] && !self.hidden[index]
+    }
+
+    fn all_destroyed(&self) -> bool {
+        (0..self.is_destroyed.len())
+            .all(|index| self.is_destroyed[index] || self.metal[index] || self.secret[index])
+    }
+
+    fn lowest_surviving_y(&self) -> Option<f32> {
+        (0..self.y_positions.len())
+            .filter(|&index| self.is_solid(index))
+            .map(|index| self.y_positions[index])
+            .reduce(f32::min)
+    }
+
+    fn drop_one_row(&mut self) {
+        for y in self.y_positions.iter_mut() {
+            *y -= self.row_pitch;
+        }
+    }
+
+    /// Lowers every row and adds a row of one hit bricks where the top row
+    /// was, with a power-up and an explosive brick in the given columns.
+    /// Rows at the bottom with nothing left in them are dropped, so the
+    /// bricks only ever hold as many rows as fit on screen.
+    fn add_top_row(
+        &mut self,
+        color: u32,
+        points: u32,
+        power_up: (usize, PowerUp),
+        explosive: Option<usize>,
+    ) {
+        let columns = self.columns;
+        let top = self.y_positions[self.y_positions.len() - columns];
+        let row_x = self.x_positions[..columns].to_vec();
+        self.drop_one_row();
+        for (column, x) in row_x.into_iter().enumerate() {
+            self.x_positions.push(x);
+            self.y_positions.push(top);
+            self.is_destroyed.push(false);
+            self.colors.push(color);
+            self.points.push(points);
+            self.power_ups
+                .push((column == power_up.0).then_some(power_up.1));
+            self.explosive.push(explosive == Some(column));
+            self.hit_points.push(1);
+            self.metal.push(false);
+            self.secret.push(false);
+            self.hidden.push(false);
+            self.letters.push(None);
+            self.materials.push(Material::Plain);
+        }
+        while self.is_destroyed.len() > columns
+            && self.is_destroyed[..columns]
+                .iter()
+                .all(|&destroyed| destroyed)
+        {
+            self.remove_bottom_row();
+        }
+    }
+
+    fn remove_bottom_row(&mut self) {
+        let row = ..self.columns;
+        self.x_positions.drain(row);
+        self.y_positions.drain(row);
+        self.is_destroyed.drain(row);
+        self.colors.drain(row);
+        self.points.drain(row);
+        self.power_ups.drain(row);
+        self.explosive.drain(row);
+        self.hit_points.drain(row);
+        self.metal.drain(row);
+        self.secret.drain(row);
+        self.hidden.drain(row);
+        self.letters.drain(row);
+        self.materials.drain(row);
+    }
+}
+
+struct GameState {
+    scene: Scene,
+    debug_stats: bool,
+    debug_stats_height: f32,
+    font: Option<Handle<Font<'static>>>,
+    balls: Vec<Ball>,
+    // Ball width in world units, its height follows from the viewport so
+    // that it is round on screen
+    ball_diameter: f32,
+    ball_color: u32,
+    background_color: u32,
+    // The lowest paddle. The player moves every paddle together.
+    paddle_pos: WorldPos,
+    // Heights of the paddles above the lowest one
+    paddle_levels: Vec<f32>,
+    paddle_width: f32,
+    paddle_height: f32,
+    paddle_vel_x: f32,
+    // How far the paddle actually moved this tick, stuns, walls and
+    // auto-move included
+    paddle_moved_x: f32,
+    auto_move: AutoMove,
+    paddle_movement_speed: f32,
+    paddle_color: u32,
+    timed_effects: TimedEffects<TimedEffect>,
+    laser_gun: LaserGun,
+    laser_bolts: Vec<LaserBolt>,
+    capsules: Vec<Capsule>,
+    // Top left corners of the hazards falling from the boss
+    hazards: Vec<WorldPos>,
+    barrier: Option<Barrier>,
+    boss: Option<Boss>,
+    // Once the boss is beaten the level clears as usual
+    boss_defeated: bool,
+    // The open top has already paid out its bonus this level
+    top_exit_paid: bool,
+    bricks: Bricks,
+    field: PlayField,
+    clock: GameClock,
+    level_events: Vec<LevelEvent>,
+    rumble: Option<Tween>,
+    // Drawn paddle height as a fraction of the real one
+    paddle_squash: Option<Sequence>,
+    // How far the game over panel has dropped in, from 0.0 to 1.0
+    panel_drop: Option<Tween>,
+    focus_change: Option<Tween>,
+    // Escape outside of play asks before quitting
+    confirm_quit: bool,
+    quit: bool,
+    // Losing the last ball in play costs a life, and the run ends when
+    // none are left
+    lives: u32,
+    // Letters of the bonus word found so far this run
+    bonus_letters: Vec<char>,
+    // Counts towards the serve speed up
+    levels_cleared: u32,
+    // Rows added so far in an endless run
+    waves: u32,
+    // How far the ball has sped up since it was served, and the paddle hits
+    // and broken bricks counted towards the next step
+    speed_ramp: f32,
+    speed_ramp_hits: u32,
+    score: u32,
+    score_bank: ScoreBank,
+    chain_stats: ChainStats,
+    chain_banner: Option<ChainBanner>,
+    // A message from a level script and how long it has left on screen
+    message: Option<(&'static str, u32)>,
+    // A note about a gamepad coming or going or a trick shot, shown over
+    // any scene, and how long it has left on screen
+    toast: Option<(String, u32)>,
+    events: Vec<GameEvent>,
+    // Where each of `events` happened
+    event_positions: Vec<WorldPos>,
+    sound_cues: Vec<SoundCue>,
+    tumbling_bricks: Vec<TumblingBrick>,
+    tricks: TrickDetector,
+    // The music intensity last sent to the audio thread
+    music_intensity: f32,
+    recorder: Recorder,
+    // The pixel area the play field is drawn into
+    viewport: Viewport,
+    // The built in levels followed by any read from level files
+    levels: Vec<&'static Level>,
+    // A picture of each level's bricks for level select
+    level_thumbnails: Vec<Option<Canvas>>,
+    // Challenge playlists, from files
+    playlists: Vec<&'static Playlist>,
+    level_index: usize,
+    level_start_score: u32,
+    progress: Progress,
+    // Totals across every session, for the stats screen
+    stats: Stats,
+    // The daily challenge level, once one has been played
+    daily: Option<Daily>,
+    run_mode: RunMode,
+    // Steers the paddle during a demo game
+    autopilot: Option<Autopilot>,
+    // How long level select has gone without a key press
+    idle_ticks: u32,
+    cheat_codes: CheatCodes,
+    // How far past what is drawn the paddle and capsules collide, for the
+    // difficulty the level started on
+    hitboxes: Hitboxes,
+    // Whether a cheat has been used this run, which keeps its scores out of
+    // progress, records and leaderboards
+    cheated: bool,
+    modifiers: Vec<Modifier>,
+    modifier_effects: ModifierEffects,
+    rng: Rng,
+    leaderboards: Leaderboards,
+    config: Config,
+    audio: Audio,
+    gamepads: Option<Gamepads>,
+    settings: Settings,
+    paths: Paths,
+}
+
+impl GameState {
+    /// Every paddle in play: one at each of the paddle levels, each with a
+    /// mirrored partner while the second paddle power-up lasts
+    fn paddle_rects(&self) -> Vec<PaddleRect> {
+        let mut rects = Vec::new();
+        for (index, level) in self.paddle_levels.iter().enumerate() {
+            let y = self.paddle_pos.y + level;
+            rects.push(PaddleRect {
+                id: PaddleId {
+                    level: index,
+                    mirrored: false,
+                },
+                pos: WorldPos::new(self.paddle_pos.x, y),
+                size: WorldVec::new(self.paddle_width, self.paddle_height),
+                moved_x: self.paddle_moved_x,
+            });
+
+            if self.timed_effects.is_running(TimedEffect::SecondPaddle) {
+                let x = self.mirrored_paddle_x(self.paddle_pos.x);
+                let was_x = self.mirrored_paddle_x(self.paddle_pos.x - self.paddle_moved_x);
+                rects.push(PaddleRect {
+                    id: PaddleId {
+                        level: index,
+                        mirrored: true,
+                    },
+                    pos: WorldPos::new(x, y),
+                    size: WorldVec::new(self.paddle_width / 2.0, self.paddle_height),
+                    moved_x: x - was_x,
+                });
+            }
+        }
+        rects
+    }
+
+    /// The left edge of the secondary paddle when the primary paddle's left
+    /// edge is at `x`. It is half size and mirrors the primary paddle about
+    /// the vertical center line of the field, kept inside the walls.
+    fn mirrored_paddle_x(&self, x: f32) -> f32 {
+        let width = self.paddle_width / 2.0;
+        let mirrored_center = 2.0 * self.field.center_x() - (x + self.paddle_width / 2.0);
+        (mirrored_center - width / 2.0).clamp(self.field.left, self.field.right - width)
+    }
+
+    /// Where each paddle meets the ball, reaching a little past its ends so
+    /// that a ball drawn just off the end still counts
+    fn paddle_hitboxes(&self) -> Vec<PaddleRect> {
+        let reach = self.hitboxes.paddle_reach;
+        self.paddle_rects()
+            .into_iter()
+            .map(|paddle| PaddleRect {
+                pos: WorldPos::new(paddle.pos.x - reach, paddle.pos.y),
+                size: WorldVec::new(paddle.size.x + 2.0 * reach, paddle.size.y),
+                ..paddle
+            })
+            .collect()
+    }
+
+    /// Where a capsule can be caught, as its top left corner and size
+    fn capsule_hitbox(&self, capsule: &Capsule) -> (WorldPos, WorldVec) {
+        let reach = self.hitboxes.capsule_reach;
+        (
+            WorldPos::new(capsule.pos.x - reach, capsule.pos.y + reach),
+            WorldVec::new(CAPSULE_WIDTH + 2.0 * reach, CAPSULE_HEIGHT + 2.0 * reach),
+        )
+    }
+
+    /// World-space extent of a ball, shared by collision and drawing
+    fn ball_size(&self) -> WorldVec {
+        let scale = if self.timed_effects.is_running(TimedEffect::MegaBall) {
+            MEGA_BALL_SCALE
+        } else {
+            1.0
+        };
+        self.viewport.round_size(self.ball_diameter * scale)
+    }
+
+    /// Where the ball meets a paddle this tick, if it does. The paddles have
+    /// already moved this tick, so both the ball and the paddles are swept
+    /// over it: a fast paddle can't pass through the ball, and a paddle that
+    /// reaches the ball in time saves it. A paddle that gets there within
+    /// the difficulty's grace window after the ball has gone past saves it
+    /// too.
+    fn paddle_collision(&self, ball: &Ball) -> Option<PaddleContact> {
+        if ball.vel.y >= 0.0 {
+            return None;
+        }
+        let size = self.ball_size();
+        let fall = -ball.vel.y;
+        let grace = self.hitboxes.save_grace_ms * clock::TICKS_PER_SECOND as f32 / 1000.0;
+
+        self.paddle_hitboxes().iter().find_map(|paddle| {
+            // Vertically the ball overlaps the paddle from when its
+            // bottom reaches the paddle's top until its top passes the
+            // paddle's bottom, as fractions of the tick. A ball that
+            // left during the last few ticks can still be in the grace
+            // window.
+            let enter = (ball.pos.y - size.y - paddle.pos.y) / fall;
+            let leave = (ball.pos.y - (paddle.pos.y - paddle.size.y)) / fall;
+            // Across, where the ball is from the paddle's left edge as
+            // the tick starts, and how much closer it gets over the tick
+            let start_x = ball.pos.x - (paddle.pos.x - paddle.moved_x);
+            let closing_x = ball.vel.x - paddle.moved_x;
+            // The ball's right edge is right of the paddle's left edge,
+            // and its left edge left of the paddle's right edge
+            let (from, to) = while_non_negative(
+                start_x + size.x,
+                closing_x,
+                enter.max(0.0),
+                (leave + grace).min(1.0),
+            )?;
+            let (from, _) = while_non_negative(paddle.size.x - start_x, -closing_x, from, to)?;
+            // Measured where the two first touch
+            let x = start_x + closing_x * from;
+            Some(PaddleContact {
+                paddle: paddle.id,
+                location: (x + size.x) / (paddle.size.x + size.x),
+                late: from > leave,
+            })
+        })
+    }
+
+    /// How much of a tick balls move each tick
+    fn ball_time_scale(&self) -> f32 {
+        let mut scale = 1.0;
+        if self.timed_effects.is_running(TimedEffect::SlowMotion) {
+            scale *= SLOW_MOTION_SCALE;
+        }
+        if self.timed_effects.is_running(TimedEffect::SpeedUp) {
+            scale *= SPEED_UP_SCALE;
+        }
+        scale
+    }
+
+    /// Where a held ball sits, on top of its paddle at the place it was
+    /// caught, or `None` if the paddle is gone
+    fn held_ball_pos(&self, hold: Hold) -> Option<WorldPos> {
+        let size = self.ball_size();
+        let paddle = self
+            .paddle_rects()
+            .into_iter()
+            .find(|paddle| paddle.id == hold.paddle)?;
+        let x = paddle.pos.x - size.x + hold.location * (paddle.size.x + size.x);
+        Some(WorldPos::new(
+            x.clamp(self.field.left, self.field.right - size.x),
+            paddle.pos.y + size.y,
+        ))
+    }
+
+    /// Lets go of every held ball, returning whether there were any.
+    /// Served balls can't be lost for a moment after they launch.
+    fn launch_held_balls(&mut self) -> bool {
+        let mut launched = false;
+        for ball in self.balls.iter_mut() {
+            let Some(hold) = ball.held.take() else {
+                continue;
+            };
+            if hold.is_serve() {
+                ball.invulnerable = Some(
+                    self.timed_effects
+                        .start(TimedEffect::Invulnerable, RESPAWN_INVULNERABLE_TICKS),
+                );
+            }
+            launched = true;
+        }
+        launched
+    }
+
+    /// The velocity of a ball leaving the paddle, for a hit `location` of
+    /// the way across it
+    fn paddle_bounce(&self, vel: WorldVec, location: f32) -> WorldVec {
+        let speed = magnitude(vel.x, vel.y);
+        let edge_angle = self.config.paddle_edge_angle;
+        match self.config.paddle_shape {
+            PaddleShape::Notched if (location - 0.5).abs() < PADDLE_NOTCH_WIDTH / 2.0 => {
+                WorldVec::new(0.0, speed)
+            }
+            PaddleShape::Flat | PaddleShape::Notched if !self.config.classic_paddle => {
+                // The exit angle leans further from vertical the further
+                // from the middle the ball hits, whatever angle it came in at
+                let deflection = (location.clamp(0.0, 1.0) * 2.0 - 1.0) * PADDLE_MAX_DEFLECTION;
+                let (sin, cos) = deflection.sin_cos();
+                WorldVec::new(speed * sin, speed * cos)
+            }
+            PaddleShape::Flat | PaddleShape::Notched => {
+                // The angle of reflection is determined by
+                // where on the paddle the ball hits.
+                // We divide the paddle into zones. The
+                // first zone reflects in the negative x direction
+                // the middle zone reflects about a vertical line
+                // the last zone reflects in the positive x direction.
+                // `reflect` scales by the length of the normal it is given, so
+                // the outer normals keep the length of 2 they had as `(±1, √3)`
+                let (sin, cos) = edge_angle.sin_cos();
+                let zone = (location * PADDLE_ZONES).floor();
+                let (rx, ry) = if zone < 1.0 {
+                    (-2.0 * sin, 2.0 * cos)
+                } else if zone < PADDLE_ZONES - 1.0 {
+                    (0.0, 1.0)
+                } else {
+                    (2.0 * sin, 2.0 * cos)
+                };
+                let (vx, vy) = reflect(vel.x, vel.y, rx, ry);
+                WorldVec::new(vx, vy)
+            }
+            PaddleShape::Convex => {
+                // The normal tilts smoothly from the left edge angle to the
+                // right one across the paddle
+                let tilt = (location.clamp(0.0, 1.0) * 2.0 - 1.0) * edge_angle;
+                let (sin, cos) = tilt.sin_cos();
+                let (vx, vy) = reflect(vel.x, vel.y, sin, cos);
+                let elevation = vy.atan2(vx);
+                let min = PADDLE_MIN_EXIT_ELEVATION;
+                let elevation = elevation.clamp(min, std::f32::consts::PI - min);
+                let (sin, cos) = elevation.sin_cos();
+                WorldVec::new(speed * cos, speed * sin)
+            }
+        }
+    }
+
+    /// Bends a ball leaving the paddle at `vel` the way the paddle is
+    /// moving, keeping its speed
+    fn paddle_english(&self, vel: WorldVec, time_scale: f32) -> WorldVec {
+        if self.paddle_moved_x == 0.0 {
+            return vel;
+        }
+        let speed = magnitude(vel.x, vel.y);
+        let vel_x = vel.x + self.paddle_moved_x * PADDLE_ENGLISH * time_scale;
+        let min = PADDLE_MIN_EXIT_ELEVATION;
+        let elevation = vel.y.atan2(vel_x).clamp(min, std::f32::consts::PI - min);
+        let (sin, cos) = elevation.sin_cos();
+        WorldVec::new(speed * cos, speed * sin)
+    }
+
+    /// Sends a copy of a broken brick tumbling out of the field, away from
+    /// the middle. It is only for show, so it takes nothing from the rng.
+    fn tumble_brick(&mut self, index: usize) {
+        if self.tumbling_bricks.len() >= MAX_TUMBLING_BRICKS {
+            self.tumbling_bricks.remove(0);
+        }
+        let pos = self.brick_center(index);
+        let outwards = if pos.x < 0.0 { -1.0 } else { 1.0 };
+        self.tumbling_bricks.push(TumblingBrick {
+            pos,
+            vel: WorldVec::new(pos.x * TUMBLE_DRIFT, TUMBLE_POP),
+            angle: 0.0,
+            spin: outwards * TUMBLE_SPIN,
+            color: self.bricks.colors[index],
+            ticks_left: TUMBLE_TICKS,
+        });
+    }
+
+    fn ball_center(&self, ball: &Ball) -> WorldPos {
+        let size = self.ball_size();
+        ball.pos + WorldVec::new(size.x / 2.0, -size.y / 2.0)
+    }
+
+    fn brick_center(&self, index: usize) -> WorldPos {
+        WorldPos::new(
+            self.bricks.x_positions[index] + self.bricks.width / 2.0,
+            self.bricks.y_positions[index] - self.bricks.height / 2.0,
+        )
+    }
+
+    fn paddle_center(&self) -> WorldPos {
+        self.paddle_pos + WorldVec::new(self.paddle_width / 2.0, -self.paddle_height / 2.0)
+    }
+
+    fn brick_collision(&self, ball: &Ball) -> Option<BrickHit> {
+        let size = self.ball_size();
+        let dx = ball.pos.x + ball.vel.x;
+        let dy = ball.pos.y + ball.vel.y;
+        let overlaps_x =
+            |ball_x: f32, x: f32| ball_x + size.x >= x && ball_x <= x + self.bricks.width;
+        let overlaps_y =
+            |ball_y: f32, y: f32| ball_y >= y - self.bricks.height && ball_y - size.y <= y;
+
+        let brick = (0..self.bricks.x_positions.len())
+            .filter(|&n| self.bricks.is_solid(n))
+            .find(|&n| {
+                overlaps_x(dx, self.bricks.x_positions[n])
+                    && overlaps_y(dy, self.bricks.y_positions[n])
+            })?;
+
+        // A ball that was already level with the brick before moving hit
+        // its side, one that was already above or below it hit its top or
+        // bottom, and one that was neither hit a corner
+        let x = self.bricks.x_positions[brick];
+        let y = self.bricks.y_positions[brick];
+        let was_above_or_below = overlaps_x(ball.pos.x, x);
+        let was_level = overlaps_y(ball.pos.y, y);
+        Some(BrickHit {
+            brick,
+            flip_x: !was_above_or_below,
+            flip_y: was_above_or_below || !was_level,
+        })
+    }
+
+    /// Moves a ball on by a tick, returning false if it fell out of the
+    /// bottom of the field
+    fn update_ball_pos(&mut self, index: usize) -> bool {
+        let mut ball = self.balls[index];
+        let size = self.ball_size();
+        let field = self.field;
+        let max_x = field.right - size.x;
+        let min_y = field.bottom + size.y;
+
+        // Held balls ride along with their paddle, already moving the way
+        // they will launch
+        if let Some(hold) = ball.held {
+            match self.held_ball_pos(hold) {
+                Some(pos) => {
+                    self.balls[index].pos = pos;
+                    if hold.is_serve() {
+                        let speed = magnitude(ball.vel.x, ball.vel.y);
+                        self.balls[index].vel = self.serve_aim(speed);
+                    }
+                    return true;
+                }
+                None => ball.held = None,
+            }
+        }
+
+        // The ball moves as if its velocity were scaled for this tick only,
+        // so it is back to its own speed once slow motion ends
+        let time_scale = self.ball_time_scale();
+        // Gravity bends the ball's path into an arc. Falling balls pick up
+        // speed, up to a cap.
+        let gravity = self.gravity();
+        if gravity > 0.0 {
+            ball.vel.y -= gravity * time_scale;
+            let max_speed = self.falling_speed_cap();
+            let speed = magnitude(ball.vel.x, ball.vel.y);
+            if speed > max_speed {
+                ball.vel = ball.vel * (max_speed / speed);
+            }
+        }
+        ball.vel = ball.vel * time_scale;
+
+        let dx = ball.pos.x + ball.vel.x;
+        let mut dy = ball.pos.y + ball.vel.y;
+
+        // Check for paddle collision
+        if let Some(PaddleContact {
+            paddle,
+            location,
+            late,
+        }) = self.paddle_collision(&ball)
+        {
+            // A late save lifts the ball back out from under the paddle, so
+            // it isn't lost on its way back up
+            if late {
+                if let Some(rect) = self.paddle_rects().iter().find(|rect| rect.id == paddle) {
+                    dy = dy.max(rect.pos.y + size.y);
+                }
+            }
+            ball.vel = self.paddle_bounce(ball.vel, location);
+            ball.vel = self.paddle_english(ball.vel, time_scale);
+            if gravity > 0.0 {
+                ball.vel = self.gravity_launch(ball.vel, ball.pos.y) * time_scale;
+            }
+            self.emit(GameEvent::PaddleHit, self.ball_center(&ball));
+            if self.timed_effects.is_running(TimedEffect::StickyPaddle) {
+                let hold = Hold {
+                    paddle,
+                    location,
+                    release: Some(
+                        self.timed_effects
+                            .start(TimedEffect::Hold, STICKY_HOLD_TICKS),
+                    ),
+                };
+                ball.held = Some(hold);
+                ball.pos = self.held_ball_pos(hold).unwrap_or(ball.pos);
+                ball.vel = ball.vel * time_scale.recip();
+                self.balls[index] = ball;
+                return true;
+            }
+        }
+
+        // The barrier bounces back balls the paddle missed
+        let hit_barrier = self.barrier.is_some_and(|barrier| {
+            ball.vel.y < 0.0 && barrier.catches(ball.pos.y - size.y, dy - size.y)
+        });
+        if hit_barrier {
+            ball.vel.y = -ball.vel.y;
+        }
+
+        // Check for brick collision
+        let hit_brick = self.brick_collision(&ball);
+        let from_above = hit_brick
+            .as_ref()
+            .is_some_and(|hit| hit.flip_y && ball.vel.y < 0.0);
+        let fireball = self.timed_effects.is_running(TimedEffect::Fireball);
+        // Fireballs carry straight on through the bricks they destroy, and
+        // only metal bricks turn them
+        let reflects = |hit: &&BrickHit| !fireball || self.bricks.metal[hit.brick];
+        if let Some(hit) = hit_brick.as_ref().filter(reflects) {
+            if hit.flip_x {
+                ball.vel.x *= -1.0;
+            }
+            if hit.flip_y {
+                ball.vel.y *= -1.0;
+            }
+        }
+        // The boss bounces balls like a metal brick, unless a brick is hit
+        // first
+        let boss_hit = self
+            .boss
+            .as_ref()
+            .filter(|_| hit_brick.is_none())
+            .and_then(|boss| boss.collision(ball.pos, ball.vel, size));
+        if let Some(hit) = &boss_hit {
+            if hit.flip_x {
+                ball.vel.x *= -1.0;
+            }
+            if hit.flip_y {
+                ball.vel.y *= -1.0;
+            }
+        }
+
+        let gap = if dx <= field.left {
+            field.gap_at(Side::Left, dy, size.y)
+        } else if dx >= max_x {
+            field.gap_at(Side::Right, dy, size.y)
+        } else {
+            None
+        };
+        let wrapped = gap == Some(GapKind::Wrap);
+
+        // The bottom and exit gaps in the side walls are only walls while
+        // the ball is invulnerable, otherwise the ball is lost
+        let missed = (dy <= min_y || gap == Some(GapKind::Exit)) && ball.invulnerable.is_none();
+        if missed {
+            self.emit(GameEvent::BallMissed, self.ball_center(&ball));
+        } else {
+            // Check for side walls collision
+            if (dx <= field.left || dx >= max_x) && !wrapped {
+                ball.vel.x = -ball.vel.x;
+                self.emit(GameEvent::WallBounce, self.ball_center(&ball));
+            }
+            // Check for top and bottom wall collision
+            if dy <= min_y || dy >= field.top {
+                ball.vel.y = -ball.vel.y;
+            }
+            if dy >= field.top {
+                let event = if field.open_top {
+                    GameEvent::TopExit
+                } else {
+                    GameEvent::WallBounce
+                };
+                self.emit(event, self.ball_center(&ball));
+            }
+        }
+
+        ball.pos.x = if missed {
+            dx
+        } else if wrapped && dx <= field.left {
+            max_x - (field.left - dx)
+        } else if wrapped {
+            field.left + (dx - max_x)
+        } else if dx > max_x {
+            max_x - (dx - max_x)
+        } else if dx < field.left {
+            field.left + (field.left - dx)
+        } else {
+            dx
+        };
+
+        ball.pos.y = if dy > field.top {
+            field.top - (dy - field.top)
+        } else if dy < min_y && !missed {
+            min_y + (min_y - dy)
+        } else {
+            dy
+        };
+
+        ball.vel = ball.vel * time_scale.recip();
+        self.balls[index] = ball;
+        if let Some(hit) = hit_brick {
+            let cause = if fireball {
+                Cause::Fireball
+            } else {
+                Cause::Ball
+            };
+            let at = self.brick_center(hit.brick);
+            self.emit(GameEvent::BallHitBrick { from_above }, at);
+            self.hit_brick(hit.brick, cause);
+        }
+        if boss_hit.is_some() {
+            self.hit_boss();
+        }
+        if hit_barrier {
+            self.hit_barrier(self.ball_center(&ball));
+        }
+        !missed
+    }
+
+    fn update_balls(&mut self) {
+        // Balls split off during this pass start moving on the next tick
+        let mut lost = Vec::new();
+        for index in 0..self.balls.len() {
+            if !self.update_ball_pos(index) {
+                lost.push(index);
+            }
+        }
+        // Splits only ever add balls at the end, so these indices still hold
+        for index in lost.into_iter().rev() {
+            self.balls.remove(index);
+        }
+
+        if self.balls.is_empty() {
+            self.lives = self.lives.saturating_sub(1);
+            if self.lives == 0 {
+                self.game_over();
+            } else {
+                self.balls = vec![self.serve_from_paddle()];
+                if self.config.instant_replay {
+                    if let Some(replay) = self.recorder.replay() {
+                        self.scene = Scene::Replay(replay);
+                    }
+                }
+            }
+        }
+    }
+
+    fn split_balls(&mut self) {
+        // Each split pairs a ball with a copy of itself whose velocity is
+        // rotated away from the original, until the ball cap is reached
+        let room = MAX_BALLS.saturating_sub(self.balls.len());
+        let splits = self
+            .balls
+            .iter()
+            .take(room)
+            .map(|ball| {
+                let (vel_x, vel_y) = rotate(ball.vel.x, ball.vel.y, SPLIT_BALL_ANGLE);
+                Ball {
+                    vel: WorldVec::new(vel_x, vel_y),
+                    split: Some(
+                        self.timed_effects
+                            .start(TimedEffect::SplitBall, SPLIT_BALL_TICKS),
+                    ),
+                    held: None,
+                    ..*ball
+                }
+            })
+            .collect::<Vec<_>>();
+        self.balls.extend(splits);
+    }
+
+    fn multiply_balls(&mut self) {
+        // Each ball gains two lasting copies of itself, one turned either
+        // way, until the ball cap is reached
+        let mut room = MAX_BALLS.saturating_sub(self.balls.len());
+        let mut copies = Vec::new();
+        for ball in &self.balls {
+            for angle in [SPLIT_BALL_ANGLE, -SPLIT_BALL_ANGLE] {
+                if room == 0 {
+                    break;
+                }
+                room -= 1;
+                let (vel_x, vel_y) = rotate(ball.vel.x, ball.vel.y, angle);
+                copies.push(Ball {
+                    vel: WorldVec::new(vel_x, vel_y),
+                    split: None,
+                    held: None,
+                    ..*ball
+                });
+            }
+        }
+        self.balls.extend(copies);
+    }
+
+    fn update_paddle_pos(&mut self) {
+        let input_x = if self.timed_effects.is_running(TimedEffect::ReverseControls) {
+            -self.paddle_vel_x
+        } else {
+            self.paddle_vel_x
+        };
+        let vel_x = if self.timed_effects.is_running(TimedEffect::Stunned) {
+            0.0
+        } else if self.config.auto_move {
+            let center = self.paddle_pos.x + self.paddle_width / 2.0;
+            let target = self.auto_move_target();
+            let speed = self.paddle_movement_speed;
+            self.auto_move.velocity(input_x, center, target, speed)
+        } else {
+            input_x
+        };
+        let max_x = self.field.right - self.paddle_width;
+        let x = (self.paddle_pos.x + vel_x).clamp(self.field.left, max_x);
+        self.paddle_moved_x = x - self.paddle_pos.x;
+        self.paddle_pos.x = x;
+    }
+
+    /// Where auto-move steers the middle of the paddle to: under the next
+    /// ball to come down, where it will land if that can be worked out
+    fn auto_move_target(&self) -> Option<f32> {
+        let ball = self
+            .balls
+            .iter()
+            .filter(|ball| ball.held.is_none() && ball.vel.y < 0.0)
+            .min_by(|a, b| a.pos.y.total_cmp(&b.pos.y))?;
+        let x = self.landing_x(ball).unwrap_or(ball.pos.x);
+        Some(x + self.ball_size().x / 2.0)
+    }
+
+    /// Where `ball` will come down to the paddle's height, if that can be
+    /// worked out
+    fn landing_x(&self, ball: &Ball) -> Option<f32> {
+        let size = self.ball_size();
+        let walls = self.field.walls(size, self.paddle_pos.y + size.y);
+        predict_landing_x(
+            ball.pos,
+            ball.vel,
+            self.gravity(),
+            self.falling_speed_cap(),
+            walls,
+            LANDING_MARKER_MAX_BOUNCES,
+        )
+    }
+
+    /// Knocks a hit point off a brick, destroying it when none are left.
+    /// Explosions and fireballs destroy bricks outright. Metal bricks shrug
+    /// off hits.
+    fn hit_brick(&mut self, index: usize, cause: Cause) {
+        let at = self.brick_center(index);
+        let material = self.bricks.materials[index];
+        let hit_points = &mut self.bricks.hit_points[index];
+        if self.bricks.metal[index] {
+            self.emit(GameEvent::BrickDamaged(material), at);
+        } else if matches!(cause, Cause::Ball | Cause::Laser) && *hit_points > 1 {
+            *hit_points -= 1;
+            self.emit(GameEvent::BrickDamaged(material), at);
+        } else {
+            self.destroy_brick(index, cause);
+        }
+    }
+
+    /// The multiplier of the score zone the middle of a brick is in
+    fn brick_score_multiplier(&self, index: usize) -> u32 {
+        let middle = self.bricks.x_positions[index] + self.bricks.width / 2.0;
+        let across = (middle - self.field.left) / self.field.width();
+        self.level().score_multiplier(across)
+    }
+
+    /// Destroys a brick along with everything caught in the explosions it
+    /// sets off
+    fn destroy_brick(&mut self, index: usize, cause: Cause) {
+        let mut chain = Chain {
+            trigger: index,
+            bricks: 0,
+            explosions: 0,
+        };
+        let mut pending = vec![(index, cause)];
+        while let Some((index, cause)) = pending.pop() {
+            if self.bricks.is_destroyed[index] || self.bricks.metal[index] {
+                continue;
+            }
+            self.bricks.is_destroyed[index] = true;
+            self.bricks.hidden[index] = false;
+            self.tumble_brick(index);
+            self.reveal_neighbors(index);
+            if let Some(letter) = self.bricks.letters[index] {
+                self.emit(GameEvent::LetterFound(letter), self.brick_center(index));
+            }
+            let points = self.bricks.points[index]
+                * self.brick_score_multiplier(index)
+                * self.endless_multiplier();
+            self.emit(
+                GameEvent::BrickDestroyed(cause, points, self.bricks.materials[index]),
+                self.brick_center(index),
+            );
+            if cause == Cause::Explosion {
+                chain.bricks += 1;
+            }
+            if self.bricks.explosive[index] {
+                chain.explosions += 1;
+                let neighbors = self.bricks.neighbors(index);
+                pending.extend(neighbors.map(|neighbor| (neighbor, Cause::Explosion)));
+            }
+            if let Some(power_up) = self.bricks.power_ups[index] {
+                let x = self.bricks.x_positions[index] + (self.bricks.width - CAPSULE_WIDTH) / 2.0;
+                self.capsules.push(Capsule {
+                    pos: WorldPos::new(x, self.bricks.y_positions[index]),
+                    power_up,
+                });
+            }
+            let (row, column) = (index / self.bricks.columns, index % self.bricks.columns);
+            match self.level().brick_action(row, column) {
+                Some(BrickAction::GivePowerUp(power_up)) => self.activate_power_up(power_up),
+                Some(BrickAction::ShowMessage(text)) => self.message = Some((text, MESSAGE_TICKS)),
+                None => (),
+            }
+        }
+        if chain.bricks > 0 {
+            self.emit(GameEvent::Chain(chain), self.brick_center(chain.trigger));
+        }
+    }
+
+    /// Brings the secret bricks around a broken one out of hiding
+    fn reveal_neighbors(&mut self, index: usize) {
+        let neighbors = self.bricks.neighbors(index).collect::<Vec<_>>();
+        for neighbor in neighbors {
+            if self.bricks.hidden[neighbor] && !self.bricks.is_destroyed[neighbor] {
+                self.bricks.hidden[neighbor] = false;
+                self.emit(GameEvent::BrickRevealed, self.brick_center(neighbor));
+            }
+        }
+    }
+
+    /// Adds a letter of the bonus word, giving a life once it is spelled out
+    fn collect_letter(&mut self, letter: char) {
+        if !BONUS_WORD.contains(letter) || self.bonus_letters.contains(&letter) {
+            return;
+        }
+        self.bonus_letters.push(letter);
+        if BONUS_WORD
+            .chars()
+            .all(|letter| self.bonus_letters.contains(&letter))
+        {
+            self.bonus_letters.clear();
+            self.lives = (self.lives + 1).min(MAX_LIVES);
+            self.message = Some(("Extra life!", MESSAGE_TICKS));
+        }
+    }
+
+    fn activate_power_up(&mut self, power_up: PowerUp) {
+        self.play_at(Sfx::PowerUp, self.paddle_center());
+        match power_up {
+            PowerUp::SecondPaddle => {
+                self.timed_effects
+                    .refresh(TimedEffect::SecondPaddle, SECOND_PADDLE_TICKS);
+            }
+            PowerUp::Laser => self.laser_gun.reload(),
+            PowerUp::SplitBall => self.split_balls(),
+            PowerUp::MultiBall => self.multiply_balls(),
+            PowerUp::StickyPaddle => {
+                self.timed_effects
+                    .refresh(TimedEffect::StickyPaddle, STICKY_PADDLE_TICKS);
+            }
+            PowerUp::SlowMotion => {
+                self.timed_effects
+                    .refresh(TimedEffect::SlowMotion, SLOW_MOTION_TICKS);
+            }
+            PowerUp::Fireball => {
+                self.timed_effects
+                    .refresh(TimedEffect::Fireball, FIREBALL_TICKS);
+            }
+            PowerUp::WidePaddle => {
+                self.timed_effects
+                    .refresh(TimedEffect::WidePaddle, PADDLE_RESIZE_TICKS);
+                self.resize_paddle();
+            }
+            PowerUp::NarrowPaddle => {
+                self.timed_effects
+                    .refresh(TimedEffect::NarrowPaddle, DEBUFF_TICKS);
+                self.resize_paddle();
+            }
+            PowerUp::ReverseControls => {
+                self.timed_effects
+                    .refresh(TimedEffect::ReverseControls, DEBUFF_TICKS);
+            }
+            PowerUp::SpeedUp => {
+                self.timed_effects
+                    .refresh(TimedEffect::SpeedUp, DEBUFF_TICKS);
+            }
+            // Another barrier capsule mends the barrier as well as giving
+            // it more time
+            PowerUp::Barrier => {
+                let expires = self
+                    .timed_effects
+                    .refresh(TimedEffect::Barrier, BARRIER_TICKS);
+                self.barrier = Some(Barrier {
+                    top: self.paddle_pos.y - self.paddle_height - BARRIER_GAP,
+                    hits_left: BARRIER_HITS,
+                    expires,
+                });
+            }
+            PowerUp::MegaBall => {
+                self.timed_effects
+                    .refresh(TimedEffect::MegaBall, MEGA_BALL_TICKS);
+                self.keep_balls_in_field();
+            }
+        }
+    }
+
+    /// Moves balls that have grown past the right wall or the bottom back
+    /// inside. Otherwise the wall checks would see them as already through
+    /// the wall and turn them around, or lose them off the bottom.
+    fn keep_balls_in_field(&mut self) {
+        let size = self.ball_size();
+        let max_x = self.field.right - size.x;
+        let min_y = self.field.bottom + size.y;
+        for ball in self.balls.iter_mut() {
+            ball.pos.x = ball.pos.x.min(max_x);
+            ball.pos.y = ball.pos.y.max(min_y);
+        }
+    }
+
+    fn update_capsules(&mut self) {
+        let mut capsules = std::mem::take(&mut self.capsules);
+        for capsule in capsules.iter_mut() {
+            capsule.pos.y -= CAPSULE_FALL_SPEED;
+        }
+        let paddles = self.paddle_rects();
+        capsules.retain(|capsule| {
+            let (pos, size) = self.capsule_hitbox(capsule);
+            let caught = paddles.iter().any(|paddle| {
+                pos.x + size.x >= paddle.pos.x
+                    && pos.x <= paddle.pos.x + paddle.size.x
+                    && pos.y - size.y <= paddle.pos.y
+                    && pos.y >= paddle.pos.y - paddle.size.y
+            });
+            if caught {
+                self.activate_power_up(capsule.power_up);
+                false
+            } else {
+                capsule.pos.y - CAPSULE_HEIGHT > self.field.bottom
+            }
+        });
+        self.capsules = capsules;
+    }
+
+    fn update_boss(&mut self) {
+        let Some(boss) = &mut self.boss else {
+            return;
+        };
+        if let Some(drop) = boss.update(&self.field, &mut self.rng) {
+            self.hazards
+                .push(WorldPos::new(drop.x - HAZARD_SIZE / 2.0, drop.y));
+        }
+    }
+
+    fn hit_boss(&mut self) {
+        let Some(boss) = self.boss.as_mut().filter(|boss| boss.is_vulnerable()) else {
+            return;
+        };
+        let at = boss.pos + WorldVec::new(boss::SIZE.x / 2.0, -boss::SIZE.y / 2.0);
+        if boss.damage() {
+            self.boss = None;
+            self.boss_defeated = true;
+            self.hazards.clear();
+            self.emit(GameEvent::BossDefeated, at);
+        } else {
+            self.emit(GameEvent::BossHit, at);
+        }
+    }
+
+    fn hit_barrier(&mut self, at: WorldPos) {
+        let Some(barrier) = &mut self.barrier else {
+            return;
+        };
+        let broken = barrier.hit();
+        if broken {
+            self.barrier = None;
+        }
+        self.emit(GameEvent::BarrierHit { broken }, at);
+    }
+
+    /// Hazards fall like capsules, but stun a paddle that catches one
+    fn update_hazards(&mut self) {
+        let mut hazards = std::mem::take(&mut self.hazards);
+        for hazard in hazards.iter_mut() {
+            hazard.y -= HAZARD_FALL_SPEED;
+        }
+        let paddles = self.paddle_rects();
+        let mut stuns = Vec::new();
+        hazards.retain(|hazard| {
+            let caught = paddles.iter().any(|paddle| {
+                hazard.x + HAZARD_SIZE >= paddle.pos.x
+                    && hazard.x <= paddle.pos.x + paddle.size.x
+                    && hazard.y - HAZARD_SIZE <= paddle.pos.y
+                    && hazard.y >= paddle.pos.y - paddle.size.y
+            });
+            if caught {
+                stuns.push(*hazard);
+                false
+            } else {
+                hazard.y - HAZARD_SIZE > self.field.bottom
+            }
+        });
+        self.hazards = hazards;
+        for at in stuns {
+            self.emit(GameEvent::PaddleStunned, at);
+        }
+    }
+
+    /// Every few campaign levels end with a boss, and so do levels that
+    /// script their own
+    fn is_boss_level(&self) -> bool {
+        match self.run_mode {
+            RunMode::Campaign if (self.level_index + 1).is_multiple_of(BOSS_INTERVAL) => true,
+            RunMode::Endless => false,
+            _ => self.level().boss.is_some(),
+        }
+    }
+
+    fn update_power_ups(&mut self) {
+        self.laser_gun.cool();
+    }
+
+    fn update_timed_effects(&mut self) {
+        for (handle, kind) in self.timed_effects.update() {
+            match kind {
+                // The second paddle, mega ball, sticky paddle, slow motion,
+                // fireball and debuffs only change what is drawn, collided
+                // with and how balls and the paddle move while their effects
+                // are running. Balls already held stay held until launched.
+                TimedEffect::SecondPaddle
+                | TimedEffect::MegaBall
+                | TimedEffect::StickyPaddle
+                | TimedEffect::SlowMotion
+                | TimedEffect::Fireball
+                | TimedEffect::ReverseControls
+                | TimedEffect::SpeedUp
+                | TimedEffect::Stunned => (),
+                TimedEffect::Hold => {
+                    for ball in self.balls.iter_mut() {
+                        if ball.held.is_some_and(|hold| hold.release == Some(handle)) {
+                            ball.held = None;
+                        }
+                    }
+                }
+                TimedEffect::Invulnerable => {
+                    for ball in self.balls.iter_mut() {
+                        if ball.invulnerable == Some(handle) {
+                            ball.invulnerable = None;
+                        }
+                    }
+                }
+                TimedEffect::SplitBall => self.balls.retain(|ball| ball.split != Some(handle)),
+                TimedEffect::Barrier => {
+                    if self
+                        .barrier
+                        .is_some_and(|barrier| barrier.expires == handle)
+                    {
+                        self.barrier = None;
+                    }
+                }
+                TimedEffect::WidePaddle | TimedEffect::NarrowPaddle => self.resize_paddle(),
+            }
+        }
+    }
+
+    fn fire_laser(&mut self) {
+        if !self.laser_gun.can_fire() {
+            return;
+        }
+        self.laser_gun.fire();
+        self.play_at(Sfx::LaserFire, self.paddle_center());
+        let offset = WorldVec::new(
+            (self.paddle_width - LASER_BOLT_WIDTH) / 2.0,
+            LASER_BOLT_HEIGHT,
+        );
+        self.laser_bolts.push(LaserBolt {
+            pos: self.paddle_pos + offset,
+        });
+    }
+
+    fn laser_bolt_collision(&self, bolt: &LaserBolt) -> Option<usize> {
+        (0..self.bricks.is_destroyed.len())
+            .filter(|&n| self.bricks.is_solid(n))
+            .find(|&n| {
+                let x = self.bricks.x_positions[n];
+                let y = self.bricks.y_positions[n];
+                bolt.pos.x + LASER_BOLT_WIDTH >= x
+                    && bolt.pos.x <= x + self.bricks.width
+                    && bolt.pos.y >= y - self.bricks.height
+                    && bolt.pos.y - LASER_BOLT_HEIGHT <= y
+            })
+    }
+
+    fn update_laser_bolts(&mut self) {
+        let mut bolts = std::mem::take(&mut self.laser_bolts);
+        for bolt in bolts.iter_mut() {
+            bolt.pos += WorldVec::new(0.0, LASER_BOLT_SPEED);
+        }
+        bolts.retain(|bolt| {
+            if let Some(index) = self.laser_bolt_collision(bolt) {
+                self.hit_brick(index, Cause::Laser);
+                false
+            } else {
+                bolt.pos.y - LASER_BOLT_HEIGHT < self.field.top
+            }
+        });
+        self.laser_bolts = bolts;
+    }
+
+    fn run_level_event(&mut self, kind: LevelEventKind) {
+        match kind {
+            LevelEventKind::BrickDrop => {
+                // The bricks only drop while there's room for another row
+                // above the paddle
+                let floor = self.brick_floor();
+                if self
+                    .lowest_brick_bottom()
+                    .is_some_and(|bottom| bottom - self.bricks.row_pitch > floor)
+                {
+                    self.bricks.drop_one_row();
+                    self.rumble = Some(Tween::new(RUMBLE_AMPLITUDE, 1.0, RUMBLE_TICKS));
+                }
+            }
+            LevelEventKind::Wave => {
+                self.spawn_wave();
+                self.level_events.push(LevelEvent {
+                    at_tick: self.clock.level_ticks() + self.wave_interval(),
+                    kind: LevelEventKind::Wave,
+                });
+            }
+        }
+    }
+
+    /// How far down the bricks may come, just above the highest paddle
+    fn brick_floor(&self) -> f32 {
+        let highest = self.paddle_levels.iter().copied().fold(0.0, f32::max);
+        self.paddle_pos.y + highest + BRICK_DROP_FLOOR_MARGIN
+    }
+
+    fn lowest_brick_bottom(&self) -> Option<f32> {
+        self.bricks
+            .lowest_surviving_y()
+            .map(|y| y - self.bricks.height)
+    }
+
+    fn bricks_reached_paddle(&self) -> bool {
+        let floor = self.brick_floor();
+        self.lowest_brick_bottom()
+            .is_some_and(|bottom| bottom <= floor)
+    }
+
+    /// Pushes the bricks down a row to make room for a new one at the top.
+    /// The run ends if that brings them down to the paddle.
+    fn spawn_wave(&mut self) {
+        self.waves += 1;
+        let style = (ENDLESS.rows + self.waves as usize - 1) % ROW_STYLES.len();
+        let (color, points) = ROW_STYLES[style];
+        let columns = self.bricks.columns;
+        let mut power_up = PowerUp::ALL[self.rng.below(PowerUp::ALL.len())];
+        // Most barriers drawn are swapped for one of the other power-ups
+        if power_up == PowerUp::Barrier && self.rng.below(BARRIER_RARITY) != 0 {
+            power_up = PowerUp::ALL[self.rng.below(PowerUp::ALL.len() - 1)];
+        }
+        let power_up_column = self.rng.below(columns);
+        let explosive = self
+            .waves
+            .is_multiple_of(ENDLESS_EXPLOSIVE_EVERY)
+            .then(|| self.rng.below(columns));
+        self.bricks
+            .add_top_row(color, points, (power_up_column, power_up), explosive);
+        // The recording has the bricks where they were
+        self.recorder.clear();
+        self.rumble = Some(Tween::new(RUMBLE_AMPLITUDE, 1.0, RUMBLE_TICKS));
+        if self.bricks_reached_paddle() {
+            self.game_over();
+        }
+    }
+
+    /// Ticks until the next wave of an endless run
+    fn wave_interval(&self) -> u32 {
+        ENDLESS_FIRST_WAVE_TICKS
+            .saturating_sub(self.waves * ENDLESS_WAVE_SPEED_UP_TICKS)
+            .max(ENDLESS_MIN_WAVE_TICKS)
+    }
+
+    /// Endless runs score more the longer they last
+    fn endless_multiplier(&self) -> u32 {
+        match self.run_mode {
+            RunMode::Endless => 1 + self.waves / ENDLESS_WAVES_PER_MULTIPLIER,
+            _ => 1,
+        }
+    }
+
+    fn update_level_events(&mut self) {
+        let (due, pending) = std::mem::take(&mut self.level_events)
+            .into_iter()
+            .partition(|event| event.at_tick <= self.clock.level_ticks());
+        self.level_events = pending;
+        for event in due {
+            self.run_level_event(event.kind);
+        }
+    }
+
+    fn update_tweens(&mut self) {
+        if let Some(rumble) = &mut self.rumble {
+            rumble.advance();
+            if rumble.is_finished() {
+                self.rumble = None;
+            }
+        }
+        if let Some(squash) = &mut self.paddle_squash {
+            squash.advance();
+            if squash.is_finished() {
+                self.paddle_squash = None;
+            }
+        }
+        if let Some((_, ticks)) = &mut self.message {
+            *ticks -= 1;
+            if *ticks == 0 {
+                self.message = None;
+            }
+        }
+        if let Some(banner) = &mut self.chain_banner {
+            banner.rise.advance();
+            if banner.rise.is_finished() {
+                self.chain_banner = None;
+            }
+        }
+        for cue in &mut self.sound_cues {
+            cue.age.advance();
+        }
+        self.sound_cues.retain(|cue| !cue.age.is_finished());
+        for brick in &mut self.tumbling_bricks {
+            brick.vel.y -= TUMBLE_GRAVITY;
+            brick.pos += brick.vel;
+            brick.angle += brick.spin;
+            brick.ticks_left -= 1;
+        }
+        self.tumbling_bricks.retain(|brick| brick.ticks_left > 0);
+    }
+
+    fn rumble(&mut self, pulse: Pulse) {
+        if let Some(gamepads) = self.gamepads.as_mut() {
+            gamepads.rumble(pulse, self.settings.rumble);
+        }
+    }
+
+    fn process_events(&mut self) {
+        self.score_bank.alive_ticks += 1;
+        let events = std::mem::take(&mut self.events);
+        let positions = std::mem::take(&mut self.event_positions);
+        // Only the ball's own doing counts towards its speed ramp
+        let ramp_hits = events
+            .iter()
+            .filter(|event| {
+                matches!(
+                    event,
+                    GameEvent::PaddleHit
+                        | GameEvent::BrickDestroyed(Cause::Ball | Cause::Fireball, ..)
+                )
+            })
+            .count();
+        for _ in 0..ramp_hits {
+            self.count_speed_ramp_hit();
+        }
+        for (event, &at) in events.iter().zip(&positions) {
+            if self.counts_towards_stats() {
+                self.stats.record(event);
+            }
+            match event {
+                GameEvent::BrickDamaged(material) => {
+                    self.play_brick_hit(*material, Sfx::PaddleHit, at);
+                    self.rumble(Pulse::PaddleHit);
+                }
+                GameEvent::BrickDestroyed(cause, points, material) => {
+                    self.play_brick_hit(*material, Sfx::BrickBreak, at);
+                    // A chain rumbles once for the whole chain
+                    if *cause != Cause::Explosion {
+                        self.rumble(Pulse::BrickBreak);
+                    }
+                    self.score_bank.deposit(*points);
+                }
+                GameEvent::BrickRevealed => self.play_at(Sfx::PowerUp, at),
+                GameEvent::LetterFound(letter) => {
+                    self.play_at(Sfx::PowerUp, at);
+                    self.collect_letter(*letter);
+                }
+                GameEvent::Chain(chain) => {
+                    // Chain bonuses are scored straight away rather than
+                    // banked, so they show up apart from the bank
+                    let points =
+                        (chain.bonus() as f32 * self.modifier_effects.points).round() as u32;
+                    self.score += points;
+                    self.chain_stats.count += 1;
+                    self.chain_stats.longest = self.chain_stats.longest.max(chain.bricks);
+                    self.chain_stats.bonus += points;
+                    self.chain_banner = Some(ChainBanner {
+                        pos: WorldPos::new(
+                            self.bricks.x_positions[chain.trigger],
+                            self.bricks.y_positions[chain.trigger],
+                        ),
+                        bricks: chain.bricks,
+                        points,
+                        rise: Tween::new(0.0, 1.0, CHAIN_BANNER_TICKS).with_easing(Easing::EaseOut),
+                    });
+                    self.rumble(Pulse::BallLost);
+                    self.rumble = Some(Tween::new(RUMBLE_AMPLITUDE, 1.0, RUMBLE_TICKS));
+                }
+                GameEvent::PaddleHit => {
+                    self.play_at(Sfx::PaddleHit, at);
+                    self.rumble(Pulse::PaddleHit);
+                    self.paddle_squash = Some(
+                        Tween::new(1.0, PADDLE_SQUASH, PADDLE_SQUASH_TICKS)
+                            .with_easing(Easing::EaseIn)
+                            .then(
+                                Tween::new(PADDLE_SQUASH, 1.0, PADDLE_RECOVER_TICKS)
+                                    .with_easing(Easing::EaseOut),
+                            ),
+                    );
+                    let committed = self.score_bank.commit() as f32;
+                    self.score += (committed * self.modifier_effects.points).round() as u32;
+                }
+                GameEvent::BarrierHit { broken } => {
+                    let sfx = if *broken {
+                        Sfx::BrickBreak
+                    } else {
+                        Sfx::PaddleHit
+                    };
+                    self.play_at(sfx, at);
+                    self.rumble(Pulse::PaddleHit);
+                }
+                GameEvent::BallMissed => {
+                    self.play_at(Sfx::BallLost, at);
+                    self.rumble(Pulse::BallLost);
+                    self.score_bank.forfeit();
+                }
+                // Only the first ball out pays, so a ball trapped above the
+                // bricks can't rack up points on its own
+                GameEvent::TopExit if self.top_exit_paid => (),
+                GameEvent::TopExit => {
+                    self.top_exit_paid = true;
+                    self.play_at(Sfx::PowerUp, at);
+                    let points = TOP_EXIT_POINTS as f32 * self.modifier_effects.points;
+                    self.score += points.round() as u32;
+                    self.message = Some(("Top bonus!", MESSAGE_TICKS));
+                }
+                GameEvent::BossHit => {
+                    self.play_at(Sfx::BrickBreak, at);
+                    self.rumble(Pulse::BrickBreak);
+                    self.score_bank.deposit(BOSS_HIT_POINTS);
+                }
+                GameEvent::BossDefeated => {
+                    self.play_at(Sfx::PowerUp, at);
+                    self.rumble(Pulse::BallLost);
+                    self.rumble = Some(Tween::new(RUMBLE_AMPLITUDE, 1.0, RUMBLE_TICKS));
+                    let points = BOSS_DEFEAT_POINTS as f32 * self.modifier_effects.points;
+                    self.score += points.round() as u32;
+                    self.message = Some(("Boss defeated!", MESSAGE_TICKS));
+                }
+                GameEvent::PaddleStunned => {
+                    self.play_at(Sfx::BallLost, at);
+                    self.rumble(Pulse::PaddleHit);
+                    self.timed_effects.refresh(TimedEffect::Stunned, STUN_TICKS);
+                }
+                GameEvent::BallHitBrick { .. } | GameEvent::WallBounce => (),
+            }
+        }
+        if self.balls.len() == 1 {
+            for trick in self.tricks.observe(&events) {
+                self.award_trick(trick);
+            }
+        } else {
+            self.tricks.reset();
+        }
+        self.events = events;
+        self.event_positions = positions;
+    }
+
+    /// Queues an event for `process_events`, with where it happened
+    fn emit(&mut self, event: GameEvent, at: WorldPos) {
+        self.events.push(event);
+        self.event_positions.push(at);
+    }
+
+    /// Plays a game sound, and with sound cues on shows a cue for it at
+    /// `at`
+    fn play_at(&mut self, sfx: Sfx, at: WorldPos) {
+        self.audio.play(sfx);
+        self.show_sound_cue(sfx, at);
+    }
+
+    /// Plays the sound of a brick of `material` being hit, varying its
+    /// pitch since bricks are hit so often. Plain bricks play `usual`.
+    fn play_brick_hit(&mut self, material: Material, usual: Sfx, at: WorldPos) {
+        match material.sound() {
+            Some(sfx) => {
+                self.audio.play_varied(sfx);
+                self.show_sound_cue(sfx, at);
+            }
+            None => self.play_at(usual, at),
+        }
+    }
+
+    fn show_sound_cue(&mut self, sfx: Sfx, at: WorldPos) {
+        if !self.config.sound_cues {
+            return;
+        }
+        if self.sound_cues.len() >= MAX_SOUND_CUES {
+            self.sound_cues.remove(0);
+        }
+        self.sound_cues.push(SoundCue {
+            sfx,
+            pos: at,
+            age: Tween::new(0.0, 1.0, SOUND_CUE_TICKS).with_easing(Easing::EaseOut),
+        });
+    }
+
+    fn award_trick(&mut self, trick: &Trick) {
+        // No sound cue, the toast already says what happened
+        self.audio.play(Sfx::PowerUp);
+        let points = (trick.points as f32 * self.modifier_effects.points).round() as u32;
+        self.score += points;
+        self.toast = Some((format!("{}! +{points}", trick.name), TOAST_TICKS));
+    }
+
+    /// Handles a key press, with sounds and a focus animation for menu
+    /// navigation
+    fn key_pressed(&mut self, key: Key) {
+        self.idle_ticks = 0;
+        // Any key hands the game back to the player
+        if self.autopilot.is_some() {
+            self.end_demo();
+            return;
+        }
+        if self.confirm_quit {
+            match key {
+                Key::Y | Key::Enter => self.quit = true,
+                Key::N | Key::Escape | Key::Backspace => self.confirm_quit = false,
+                _ => (),
+            }
+            return;
+        }
+        if key == Key::Escape {
+            // Quitting from level select loses nothing, so it doesn't ask
+            match self.scene {
+                Scene::LevelSelect(_) => self.quit = true,
+                Scene::Playing | Scene::Intro(_) | Scene::Replay(_) => self.pause(),
+                _ => self.confirm_quit = true,
+            }
+            return;
+        }
+
+        if let Scene::Playing = self.scene {
+            if let Some(cheat) = self.cheat_codes.key_pressed(key) {
+                self.apply_cheat(cheat);
+            }
+        }
+
+        let scene = std::mem::discriminant(&self.scene);
+        let focus = self.scene.focus();
+        self.handle_key(key);
+        if focus.is_none() {
+            return;
+        }
+        if std::mem::discriminant(&self.scene) != scene {
+            self.audio.play(Sfx::MenuConfirm);
+        } else if self.scene.focus() != focus {
+            self.audio.play(Sfx::MenuMove);
+        } else {
+            return;
+        }
+        self.focus_change = Some(Tween::new(0.0, 1.0, FOCUS_TICKS).with_easing(Easing::EaseOut));
+    }
+
+    fn apply_cheat(&mut self, cheat: Cheat) {
+        match cheat {
+            Cheat::MultiBall => self.multiply_balls(),
+            Cheat::FullLives => self.lives = MAX_LIVES,
+        }
+        self.cheated = true;
+        let toast = format!("Cheat: {}. Scores won't be kept", cheat.name());
+        self.toast = Some((toast, TOAST_TICKS));
+    }
+
+    /// How far the focus outline has grown in around the focused menu item
+    fn focus(&self) -> f32 {
+        self.focus_change.as_ref().map_or(1.0, Tween::value)
+    }
+
+    pub fn handle_key(&mut self, key: Key) {
+        match &mut self.scene {
+            Scene::LevelSelect(level_select) => {
+                match level_select.key_pressed(key, &self.progress, self.levels.len()) {
+                    Selection::Start(index) => self.start_level(index),
+                    Selection::StartModifierRun => self.start_run(RunMode::Modifiers, 0),
+                    Selection::StartEndless => self.start_run(RunMode::Endless, 0),
+                    Selection::StartTimeAttack(index) => self.start_run(RunMode::TimeAttack, index),
+                    Selection::StartDaily => self.start_daily(),
+                    Selection::OpenChallenges => {
+                        self.scene = Scene::Challenges(ChallengeSelect { cursor: 0 });
+                    }
+                    Selection::OpenSettings => {
+                        self.scene = Scene::Settings(SettingsMenu { cursor: 0 });
+                    }
+                    Selection::OpenStats => {
+                        let cursor = level_select.cursor;
+                        self.scene = Scene::Stats(LevelSelect { cursor });
+                    }
+                    Selection::None => (),
+                }
+            }
+            Scene::Playing => match key {
+                // Space launches held balls, or fires if there are none
+                Key::Space if !self.clock.is_paused() => {
+                    if self.config.auto_move {
+                        self.auto_move.nudge();
+                    }
+                    if !self.launch_held_balls() {
+                        self.fire_laser();
+                    }
+                }
+                Key::P => self.pause(),
+                Key::Equal | Key::NumPadPlus => {
+                    self.update_ball_speed(1.0 + self.config.ball_speed_step);
+                }
+                Key::Minus | Key::NumPadMinus => {
+                    self.update_ball_speed(1.0 - self.config.ball_speed_step);
+                }
+                _ => (),
+            },
+            Scene::Intro(_) => {
+                if matches!(key, Key::Enter | Key::Space) {
+                    self.scene = Scene::Playing;
+                }
+            }
+            Scene::LevelComplete(complete) => {
+                if matches!(key, Key::Enter | Key::Space) {
+                    let next = complete.next;
+                    self.load_level(next);
+                }
+            }
+            Scene::Replay(_) => {
+                if matches!(key, Key::Enter | Key::Space) {
+                    self.scene = Scene::Playing;
+                }
+            }
+            Scene::Paused(menu) => match menu.key_pressed(key) {
+                PauseAction::Resume => {
+                    self.clock.set_paused(false);
+                    self.scene = Scene::Playing;
+                }
+                PauseAction::Restart => {
+                    self.clock.set_paused(false);
+                    self.scene = Scene::Playing;
+                    self.score = self.level_start_score;
+                    self.load_level(self.level_index);
+                }
+                PauseAction::OpenSettings => {
+                    self.scene = Scene::Settings(SettingsMenu { cursor: 0 });
+                }
+                PauseAction::Quit => {
+                    self.clock.set_paused(false);
+                    self.scene = Scene::LevelSelect(LevelSelect { cursor: 0 });
+                }
+                PauseAction::None => (),
+            },
+            Scene::GameOver => match key {
+                Key::C if self.run_mode == RunMode::Campaign => {
+                    self.start_level(self.checkpoint());
+                }
+                Key::R if self.run_mode == RunMode::Endless => {
+                    self.start_run(RunMode::Endless, 0);
+                }
+                Key::R if self.run_mode == RunMode::TimeAttack => {
+                    self.start_run(RunMode::TimeAttack, self.level_index);
+                }
+                Key::R if self.run_mode == RunMode::Daily => self.start_daily(),
+                Key::R => self.start_level(0),
+                _ => (),
+            },
+            Scene::Challenges(challenge_select) => {
+                match challenge_select.key_pressed(key, self.playlists.len()) {
+                    ChallengeSelection::Start(playlist) => {
+                        let first_level = self.playlists[playlist].levels[0];
+                        self.start_run(
+                            RunMode::Challenge {
+                                playlist,
+                                position: 0,
+                            },
+                            first_level,
+                        );
+                    }
+                    ChallengeSelection::Back => {
+                        self.scene = Scene::LevelSelect(LevelSelect { cursor: 0 });
+                    }
+                    ChallengeSelection::None => (),
+                }
+            }
+            Scene::Stats(level_select) => {
+                if matches!(key, Key::Backspace | Key::L) {
+                    let cursor = level_select.cursor;
+                    self.scene = Scene::LevelSelect(LevelSelect { cursor });
+                }
+            }
+            Scene::Settings(menu) => match menu.key_pressed(key, &mut self.settings) {
+                SettingsAction::Changed(slider) => {
+                    self.audio.set_volumes(self.settings.volumes);
+                    // Give a sample of the new setting. There's no music to
+                    // sample yet.
+                    match slider {
+                        Slider::Master | Slider::Sfx => self.audio.play(Sfx::PaddleHit),
+                        Slider::Rumble => self.rumble(Pulse::BrickBreak),
+                        Slider::Music => (),
+                    }
+                    let path = self.paths.settings();
+                    if let Err(err) = self.settings.save(&path) {
+                        eprintln!("ERROR! Could not save settings to {path:?}: {err}");
+                    }
+                }
+                // Settings are opened from the pause menu or level select,
+                // and only the pause menu leaves a run paused
+                SettingsAction::Back if self.clock.is_paused() => {
+                    self.scene = Scene::Paused(PauseMenu::at_settings());
+                }
+                SettingsAction::Back => {
+                    self.scene = Scene::LevelSelect(LevelSelect { cursor: 0 });
+                }
+                SettingsAction::None => (),
+            },
+            Scene::Setup(wizard) => match wizard.key_pressed(key, &mut self.config) {
+                SetupAction::Changed => self.apply_theme(),
+                SetupAction::Finished => {
+                    let path = self.paths.config();
+                    if let Err(err) = self.config.save(&path) {
+                        eprintln!("ERROR! Could not save config to {path:?}: {err}");
+                    }
+                    self.scene = Scene::LevelSelect(LevelSelect { cursor: 0 });
+                }
+                SetupAction::None => (),
+            },
+            Scene::PickModifier(pick) => match key {
+                Key::Left | Key::A => pick.cursor = pick.cursor.saturating_sub(1),
+                Key::Right | Key::D => pick.cursor = (pick.cursor + 1).min(pick.choices.len() - 1),
+                Key::Enter | Key::Space => {
+                    let choice = pick.choices[pick.cursor];
+                    self.modifiers.push(choice);
+                    self.scene = Scene::Playing;
+                    self.load_level((self.level_index + 1) % self.levels.len());
+                }
+                _ => (),
+            },
+        }
+    }
+
+    fn tick(&mut self) {
+        if let Some((_, ticks)) = &mut self.toast {
+            *ticks -= 1;
+            if *ticks == 0 {
+                self.toast = None;
+            }
+        }
+        if let Some(panel_drop) = &mut self.panel_drop {
+            panel_drop.advance();
+        }
+        if let Some(focus_change) = &mut self.focus_change {
+            focus_change.advance();
+        }
+        if matches!(
+            self.scene,
+            Scene::Playing | Scene::Intro(_) | Scene::LevelComplete(_) | Scene::Replay(_)
+        ) && self.counts_towards_stats()
+        {
+            self.stats.play_ticks += 1;
+        }
+        if let Scene::LevelSelect(_) = self.scene {
+            self.idle_ticks += 1;
+            if self.idle_ticks >= DEMO_IDLE_TICKS {
+                self.start_demo();
+            }
+        }
+        if let Scene::Intro(intro) = &mut self.scene {
+            intro.advance();
+            if intro.is_finished() {
+                self.scene = Scene::Playing;
+            }
+        }
+        if let Scene::LevelComplete(complete) = &mut self.scene {
+            complete.ticks_left = complete.ticks_left.saturating_sub(1);
+            if complete.ticks_left == 0 {
+                let next = complete.next;
+                self.load_level(next);
+            }
+        }
+        if let Scene::Replay(replay) = &mut self.scene {
+            replay.advance();
+            if replay.is_finished() {
+                self.scene = Scene::Playing;
+            }
+        }
+        if matches!(self.scene, Scene::Playing) {
+            if let Some(mut autopilot) = self.autopilot.take() {
+                autopilot.steer(self);
+                self.autopilot = Some(autopilot);
+            }
+        }
+        for _ in 0..self.clock.frame() {
+            if !matches!(self.scene, Scene::Playing) {
+                return;
+            }
+            self.step();
+        }
+    }
+
+    /// Simulates a single tick of play
+    fn step(&mut self) {
+        self.clock.advance();
+        self.events.clear();
+        self.event_positions.clear();
+        self.update_level_events();
+        // The paddle moves first, so balls are swept against where it went
+        // this tick and held balls ride along without lagging behind
+        self.update_paddle_pos();
+        self.update_balls();
+        self.update_laser_bolts();
+        self.update_capsules();
+        self.update_boss();
+        self.update_hazards();
+        self.update_power_ups();
+        self.update_timed_effects();
+        self.update_tweens();
+        self.process_events();
+        self.update_music_intensity();
+        if self.config.instant_replay {
+            self.recorder.record(self.render_frame());
+        }
+
+        if self.bricks.all_destroyed() && self.boss.is_none() {
+            // Endless runs have nothing to complete, the next wave just
+            // comes early
+            if self.run_mode == RunMode::Endless {
+                self.spawn_wave();
+            } else if self.is_boss_level() && !self.boss_defeated {
+                let script = self.level().boss;
+                self.boss = Some(Boss::new(&self.field, &mut self.rng, script.as_ref()));
+                self.message = Some(("Boss incoming!", MESSAGE_TICKS));
+            } else {
+                self.complete_level();
+            }
+        }
+    }
+
+    /// The music gets more intense as the level is cleared or the bank
+    /// multiplier climbs, whichever is further along, and is all in for a
+    /// boss
+    fn update_music_intensity(&mut self) {
+        let bricks = &self.bricks;
+        let (total, left) = (0..bricks.is_destroyed.len())
+            .filter(|&index| !bricks.metal[index] && !bricks.secret[index])
+            .fold((0, 0), |(total, left), index| {
+                (total + 1, left + usize::from(!bricks.is_destroyed[index]))
+            });
+        let cleared = if total == 0 {
+            0.0
+        } else {
+            1.0 - left as f32 / total as f32
+        };
+        let combo = if self.score_bank.points > 0 {
+            (self.score_bank.multiplier() - 1.0) / (BANK_MAX_MULTIPLIER - 1.0)
+        } else {
+            0.0
+        };
+        let intensity = if self.boss.is_some() {
+            1.0
+        } else {
+            cleared.max(combo)
+        };
+        // Either end is always sent, so that every layer can come all the
+        // way in or go all the way out
+        let at_end = intensity == 0.0 || intensity == 1.0;
+        let moved = (intensity - self.music_intensity).abs();
+        if moved >= MUSIC_INTENSITY_STEP || (at_end && moved > 0.0) {
+            self.music_intensity = intensity;
+            self.audio.set_music_intensity(intensity);
+        }
+    }
+
+    fn level(&self) -> &'static Level {
+        match self.run_mode {
+            RunMode::Endless => &ENDLESS,
+            RunMode::Daily => self
+                .daily
+                .as_ref()
+                .map_or(self.levels[self.level_index], |daily| daily.level),
+            _ => self.levels[self.level_index],
+        }
+    }
+
+    fn load_level(&mut self, index: usize) {
+        self.level_index = index;
+        self.field = self.level().field;
+        self.bricks = Bricks::new(self.level(), &self.field);
+        self.audio.set_theme(self.level().theme);
+        self.music_intensity = 0.0;
+        self.audio.set_music_intensity(0.0);
+        self.clock.start_level();
+        self.waves = 0;
+        self.tricks.reset();
+        self.level_events = match self.run_mode {
+            RunMode::Endless => vec![LevelEvent {
+                at_tick: self.wave_interval(),
+                kind: LevelEventKind::Wave,
+            }],
+            _ => default_level_events(),
+        };
+        self.laser_bolts.clear();
+        self.capsules.clear();
+        self.hazards.clear();
+        self.barrier = None;
+        self.sound_cues.clear();
+        self.tumbling_bricks.clear();
+        self.boss = None;
+        self.boss_defeated = false;
+        self.top_exit_paid = false;
+        self.recorder.clear();
+        self.message = None;
+        self.level_start_score = self.score;
+
+        let effects = self
+            .run_effects()
+            .combine(modifiers::combined_effects(&self.modifiers))
+            .combine(self.config.difficulty.effects());
+        self.hitboxes = self.config.difficulty.hitboxes();
+        self.paddle_levels = if self.config.elevator_paddles {
+            vec![0.0, ELEVATOR_PADDLE_SPACING]
+        } else {
+            vec![0.0]
+        };
+        self.paddle_movement_speed = PADDLE_MOVEMENT_SPEED * effects.paddle_speed;
+        self.ball_diameter = BALL_DIAMETER * effects.ball_diameter;
+        self.modifier_effects = effects;
+        self.resize_paddle();
+        self.balls = vec![self.serve_from_paddle()];
+        self.laser_gun.add_ammo(effects.laser_ammo);
+        self.scene = Scene::Intro(
+            Tween::new(0.0, 0.0, INTRO_CARD_TICKS)
+                .then(Tween::new(0.0, 1.0, INTRO_WIPE_TICKS).with_easing(Easing::EaseIn)),
+        );
+    }
+
+    /// Sets the paddle width from the modifiers and any running wide or
+    /// narrow paddle effects, keeping the paddle centred where it was
+    fn resize_paddle(&mut self) {
+        let mut scale = self.modifier_effects.paddle_width;
+        if self.timed_effects.is_running(TimedEffect::WidePaddle) {
+            scale *= WIDE_PADDLE_SCALE;
+        }
+        if self.timed_effects.is_running(TimedEffect::NarrowPaddle) {
+            scale *= NARROW_PADDLE_SCALE;
+        }
+        // Paddles share the usual width between them
+        let width = PADDLE_WIDTH * scale / self.paddle_levels.len() as f32;
+        let center = self.paddle_pos.x + self.paddle_width / 2.0;
+        self.paddle_width = width.min(self.field.width());
+        self.paddle_pos.x = (center - self.paddle_width / 2.0)
+            .clamp(self.field.left, self.field.right - self.paddle_width);
+    }
+
+    /// Serves get a little faster with each level cleared in the run, on
+    /// top of the level's own ball speed
+    fn serve_speed(&self) -> f32 {
+        let speed_up =
+            (self.levels_cleared as f32 * LEVEL_SERVE_SPEED_UP).min(MAX_LEVEL_SERVE_SPEED_UP);
+        BALL_SERVE_SPEED
+            * self.level().ball_speed
+            * self.modifier_effects.ball_speed
+            * (1.0 + speed_up)
+    }
+
+    /// The way a served ball will launch, picked by where the paddle is.
+    /// Serves lean back towards the middle of the field, from the steepest
+    /// configured angle with the paddle in the middle to the shallowest
+    /// with it against a wall.
+    fn serve_aim(&self, speed: f32) -> WorldVec {
+        let travel = ((self.field.width() - self.paddle_width) / 2.0).max(f32::EPSILON);
+        let offset = self.paddle_pos.x + self.paddle_width / 2.0 - self.field.center_x();
+        let lean = (offset / travel).clamp(-1.0, 1.0);
+        let (min, max) = (self.config.serve_angle_min, self.config.serve_angle_max);
+        let elevation = (max + (min - max) * lean.abs()).to_radians();
+        let angle = if lean > 0.0 {
+            std::f32::consts::PI - elevation
+        } else {
+            elevation
+        };
+        let (sin, cos) = angle.sin_cos();
+        let aim = WorldVec::new(speed * cos, speed * sin);
+        if self.gravity() > 0.0 {
+            self.gravity_launch(aim, self.paddle_pos.y)
+        } else {
+            aim
+        }
+    }
+
+    /// The speed balls are served at, sped up by the ramp so far
+    fn nominal_ball_speed(&self) -> f32 {
+        self.serve_speed() * self.speed_ramp
+    }
+
+    /// How much gravity mode speeds balls up downwards each tick. It grows
+    /// with the ball speed so that arcs keep their shape as balls speed up.
+    fn gravity(&self) -> f32 {
+        if !self.config.gravity {
+            return 0.0;
+        }
+        self.nominal_ball_speed().powi(2) / (2.0 * GRAVITY_RISE)
+    }
+
+    /// The fastest gravity lets a ball fall
+    fn falling_speed_cap(&self) -> f32 {
+        self.nominal_ball_speed() * GRAVITY_MAX_SPEED
+    }
+
+    /// A ball leaving the paddle from height `y` heading along `vel`, at
+    /// its usual speed or faster if that is what it takes to reach the top
+    /// of the field under gravity. Energy a ball picked up falling is
+    /// dropped, so bounces can't build it up.
+    fn gravity_launch(&self, vel: WorldVec, y: f32) -> WorldVec {
+        let nominal = self.nominal_ball_speed();
+        let speed = magnitude(vel.x, vel.y).max(f32::EPSILON);
+        let (dir_x, dir_y) = (vel.x / speed, vel.y / speed);
+        let rise_speed = (2.0 * self.gravity() * (self.field.top - y).max(0.0)).sqrt();
+        let launch =
+            (rise_speed / dir_y.max(f32::EPSILON)).clamp(nominal, nominal * GRAVITY_MAX_SPEED);
+        WorldVec::new(dir_x * launch, dir_y * launch)
+    }
+
+    /// Speeds the balls up a step once enough paddle hits and broken
+    /// bricks have added up, until the ramp reaches its cap
+    fn count_speed_ramp_hit(&mut self) {
+        let every = self.config.speed_ramp_hits;
+        if every == 0 {
+            return;
+        }
+        self.speed_ramp_hits += 1;
+        if self.speed_ramp_hits < every {
+            return;
+        }
+        self.speed_ramp_hits = 0;
+        let ramp =
+            (self.speed_ramp * (1.0 + SPEED_RAMP_STEP)).min(1.0 + self.config.speed_ramp_max);
+        if ramp > self.speed_ramp {
+            self.update_ball_speed(ramp / self.speed_ramp);
+            self.speed_ramp = ramp;
+        }
+    }
+
+    /// A new ball resting on the middle of the paddle, for the player to
+    /// aim and launch. It starts the speed ramp over.
+    fn serve_from_paddle(&mut self) -> Ball {
+        self.speed_ramp = 1.0;
+        self.speed_ramp_hits = 0;
+        let hold = Hold {
+            paddle: PaddleId::LOWEST,
+            location: 0.5,
+            release: None,
+        };
+        let ball = Ball {
+            vel: self.serve_aim(self.serve_speed()),
+            held: Some(hold),
+            ..Ball::serve(0.0, 0.0)
+        };
+        Ball {
+            pos: self.held_ball_pos(hold).unwrap_or(ball.pos),
+            ..ball
+        }
+    }
+
+    fn run_effects(&self) -> ModifierEffects {
+        match self.run_mode {
+            RunMode::Challenge { playlist, .. } => self.playlists[playlist].effects,
+            RunMode::Campaign
+            | RunMode::Modifiers
+            | RunMode::Endless
+            | RunMode::TimeAttack
+            | RunMode::Daily
+            | RunMode::Demo => ModifierEffects::default(),
+        }
+    }
+
+    /// Starts a fresh run, keeping only settings and saved data
+    fn start_run(&mut self, run_mode: RunMode, first_level: usize) {
+        *self = GameState {
+            scene: Scene::Playing,
+            font: self.font.take(),
+            debug_stats: self.debug_stats,
+            progress: std::mem::take(&mut self.progress),
+            stats: self.stats,
+            daily: self.daily.take(),
+            leaderboards: std::mem::take(&mut self.leaderboards),
+            rng: self.config.rng(),
+            clock: GameClock::new(self.config.game_speed),
+            config: std::mem::take(&mut self.config),
+            audio: std::mem::take(&mut self.audio),
+            gamepads: self.gamepads.take(),
+            settings: self.settings,
+            paths: std::mem::take(&mut self.paths),
+            levels: std::mem::take(&mut self.levels),
+            level_thumbnails: std::mem::take(&mut self.level_thumbnails),
+            playlists: std::mem::take(&mut self.playlists),
+            viewport: self.viewport,
+            run_mode,
+            ..GameState::default()
+        };
+        self.apply_theme();
+        self.load_level(first_level);
+    }
+
+    fn apply_theme(&mut self) {
+        let palette = self.config.theme.palette();
+        self.background_color = palette.background;
+        self.ball_color = palette.ball;
+        self.paddle_color = palette.paddle;
+    }
+
+    fn start_level(&mut self, index: usize) {
+        self.start_run(RunMode::Campaign, index);
+    }
+
+    /// Starts today's daily challenge, making up its level the first time
+    /// it is played each day
+    fn start_daily(&mut self) {
+        let today = daily::today();
+        if self.daily.as_ref().is_none_or(|daily| daily.day != today) {
+            self.daily = Some(Daily::new(today));
+        }
+        self.start_run(RunMode::Daily, 0);
+    }
+
+    /// Starts a demo game from the first level, played by the autopilot
+    fn start_demo(&mut self) {
+        let seed = self.rng.next_u64();
+        self.start_run(RunMode::Demo, 0);
+        self.autopilot = Some(Autopilot::new(seed));
+    }
+
+    /// Stops the demo game and goes back to level select
+    fn end_demo(&mut self) {
+        self.autopilot = None;
+        self.run_mode = RunMode::Campaign;
+        self.idle_ticks = 0;
+        self.scene = Scene::LevelSelect(LevelSelect { cursor: 0 });
+    }
+
+    fn save_progress(&self) {
+        let path = self.paths.progress();
+        if let Err(err) = self.progress.save(&path) {
+            eprintln!("ERROR! Could not save progress to {path:?}: {err}");
+        }
+    }
+
+    /// Whether the current run adds to the lifetime stats. The demo plays
+    /// itself and a cheated run wasn't really played.
+    fn counts_towards_stats(&self) -> bool {
+        self.run_mode != RunMode::Demo && !self.cheated
+    }
+
+    fn save_stats(&self) {
+        let path = self.paths.stats();
+        if let Err(err) = self.stats.save(&path) {
+            eprintln!("ERROR! Could not save stats to {path:?}: {err}");
+        }
+    }
+
+    /// Records the result of the current level and carries the run on to
+    /// the next level of the campaign, or back to level select at the end
+    fn complete_level(&mut self) {
+        // The demo goes through every level in turn, counting none of them
+        if self.run_mode == RunMode::Demo {
+            let next = (self.level_index + 1) % self.levels.len();
+            self.show_level_complete(next, self.score - self.level_start_score, None);
+            return;
+        }
+        self.levels_cleared += 1;
+        if self.counts_towards_stats() {
+            self.stats.levels_cleared += 1;
+            self.save_stats();
+        }
+        let level_score = self.score - self.level_start_score;
+        // Only campaign runs count towards campaign progress. Modifier runs
+        // loop through the levels indefinitely and challenges follow their
+        // own playlist
+        match self.run_mode {
+            RunMode::Campaign => (),
+            // Clearing the bricks brings on the next wave instead
+            RunMode::Endless | RunMode::Demo => return,
+            RunMode::TimeAttack => {
+                self.record_time();
+                return;
+            }
+            RunMode::Daily => {
+                let best = self.record_daily_score();
+                let toast = if best {
+                    format!("New daily best {}!", self.score)
+                } else {
+                    format!("Daily score {}", self.score)
+                };
+                self.toast = Some((toast, TOAST_TICKS));
+                self.scene = Scene::LevelSelect(LevelSelect { cursor: 0 });
+                return;
+            }
+            RunMode::Modifiers => {
+                self.scene = Scene::PickModifier(ModifierPick {
+                    choices: modifiers::roll_choices(&mut self.rng),
+                    cursor: 0,
+                });
+                return;
+            }
+            RunMode::Challenge { playlist, position } => {
+                let next = position + 1;
+                match self.playlists[playlist].levels.get(next) {
+                    Some(&level) => {
+                        self.run_mode = RunMode::Challenge {
+                            playlist,
+                            position: next,
+                        };
+                        self.show_level_complete(level, level_score, None);
+                    }
+                    None => {
+                        self.record_challenge_score(playlist);
+                        self.scene = Scene::Challenges(ChallengeSelect { cursor: playlist });
+                    }
+                }
+                return;
+            }
+        }
+
+        // A cheated run carries on, but earns no stars or checkpoints
+        let stars = (!self.cheated).then(|| {
+            let stars = self.level().stars(level_score, self.clock.level_ticks());
+            self.progress.record(self.level_index, stars, level_score);
+            stars
+        });
+
+        let next = self.level_index + 1;
+        if next < self.levels.len() {
+            if next.is_multiple_of(CHECKPOINT_INTERVAL) && !self.cheated {
+                self.progress.checkpoint = self.progress.checkpoint.max(next);
+            }
+            self.show_level_complete(next, level_score, stars);
+        } else {
+            self.scene = Scene::LevelSelect(LevelSelect {
+                cursor: self.level_index,
+            });
+        }
+        self.save_progress();
+    }
+
+    /// Holds on the cleared level for a moment before loading `next`. Score
+    /// and lives carry on into the next level.
+    fn show_level_complete(&mut self, next: usize, score: u32, stars: Option<u8>) {
+        self.scene = Scene::LevelComplete(LevelComplete {
+            next,
+            name: self.level().name,
+            score,
+            stars,
+            ticks_left: LEVEL_COMPLETE_TICKS,
+        });
+        self.panel_drop = Some(Tween::new(0.0, 1.0, PANEL_DROP_TICKS).with_easing(Easing::EaseOut));
+    }
+
+    /// Saves the run in progress, for when the game has to stop in the
+    /// middle of one. A campaign run is saved to be resumed on the next
+    /// launch. Other runs can't be resumed, so challenge, endless and daily
+    /// runs record their score so far instead.
+    fn autosave_run(&mut self) {
+        let in_run = matches!(
+            self.scene,
+            Scene::Playing
+                | Scene::Intro(_)
+                | Scene::LevelComplete(_)
+                | Scene::Replay(_)
+                | Scene::Paused(_)
+        );
+        if !in_run || self.cheated {
+            return;
+        }
+        match self.run_mode {
+            RunMode::Campaign => {
+                let path = self.paths.run();
+                if let Err(err) = self.saved_run().save(&path) {
+                    eprintln!("ERROR! Could not save the run to {path:?}: {err}");
+                }
+            }
+            RunMode::Challenge { playlist, .. } => self.record_challenge_score(playlist),
+            RunMode::Endless => self.record_endless_score(),
+            RunMode::Daily => {
+                self.record_daily_score();
+            }
+            // Modifier runs have no checkpoints to continue from, time
+            // attack only counts finished levels and the demo keeps nothing
+            RunMode::Modifiers | RunMode::TimeAttack | RunMode::Demo => (),
+        }
+    }
+
+    fn saved_run(&self) -> SavedRun {
+        let fresh = Bricks::new(self.level(), &self.field);
+        let lowered = fresh
+            .y_positions
+            .first()
+            .zip(self.bricks.y_positions.first())
+            .map_or(0.0, |(fresh, now)| fresh - now);
+        let bricks = (0..self.bricks.is_destroyed.len())
+            .filter(|&index| !self.bricks.is_destroyed[index])
+            .map(|index| SavedBrick {
+                index,
+                hit_points: self.bricks.hit_points[index],
+                hidden: self.bricks.hidden[index],
+            })
+            .collect();
+        SavedRun {
+            level: self.level_index,
+            score: self.score,
+            level_start_score: self.level_start_score,
+            lives: self.lives,
+            levels_cleared: self.levels_cleared,
+            lowered,
+            brick_count: self.bricks.is_destroyed.len(),
+            bricks,
+        }
+    }
+
+    /// Picks up a campaign run saved by `autosave_run`, paused so the
+    /// player can resume it or quit to level select. Ignored if the levels
+    /// have changed since it was saved.
+    fn resume_run(&mut self, run: SavedRun) {
+        if run.level >= self.levels.len() {
+            eprintln!(
+                "ERROR! Ignoring the saved run, there is no level {}",
+                run.level + 1
+            );
+            return;
+        }
+        self.start_level(run.level);
+        if self.bricks.is_destroyed.len() != run.brick_count {
+            eprintln!("ERROR! Ignoring the saved run, its level has changed");
+            self.scene = Scene::LevelSelect(LevelSelect { cursor: 0 });
+            return;
+        }
+        self.score = run.score;
+        self.level_start_score = run.level_start_score;
+        self.lives = run.lives;
+        self.levels_cleared = run.levels_cleared;
+        self.bricks.is_destroyed.fill(true);
+        for brick in run.bricks {
+            self.bricks.is_destroyed[brick.index] = false;
+            self.bricks.hit_points[brick.index] = brick.hit_points;
+            self.bricks.hidden[brick.index] = brick.hidden;
+        }
+        for y in self.bricks.y_positions.iter_mut() {
+            *y -= run.lowered;
+        }
+        // Serves speed up with the levels cleared, which weren't restored
+        // when the level served its ball
+        self.balls = vec![self.serve_from_paddle()];
+        self.pause();
+        self.toast = Some((String::from("Resumed the interrupted run"), TOAST_TICKS));
+    }
+
+    fn checkpoint(&self) -> usize {
+        self.progress.checkpoint.min(self.levels.len() - 1)
+    }
+
+    /// Keeps the score of today's daily challenge if it is the day's best,
+    /// returning whether it was
+    fn record_daily_score(&mut self) -> bool {
+        let Some(day) = self.daily.as_ref().map(|daily| daily.day) else {
+            return false;
+        };
+        if self.cheated {
+            return false;
+        }
+        let best = self.progress.record_daily(day, self.score);
+        if best {
+            self.save_progress();
+        }
+        best
+    }
+
+    fn record_endless_score(&mut self) {
+        if self.score > self.progress.endless_best && !self.cheated {
+            self.progress.endless_best = self.score;
+            self.save_progress();
+        }
+    }
+
+    /// Keeps the time of a cleared time attack level if it is the best, and
+    /// goes back to level select
+    fn record_time(&mut self) {
+        let ticks = self.clock.level_ticks();
+        let previous = self.progress.level(self.level_index).best_ticks;
+        let toast = if self.cheated {
+            format!("Time {} (cheated, not kept)", format_ticks(ticks))
+        } else if self.progress.record_time(self.level_index, ticks) {
+            self.save_progress();
+            format!("New best time {}!", format_ticks(ticks))
+        } else {
+            let best = previous.map_or_else(String::new, format_ticks);
+            format!("Time {} (best {best})", format_ticks(ticks))
+        };
+        self.toast = Some((toast, TOAST_TICKS));
+        self.scene = Scene::LevelSelect(LevelSelect {
+            cursor: self.level_index,
+        });
+    }
+
+    fn record_challenge_score(&mut self, playlist: usize) {
+        if self.cheated {
+            return;
+        }
+        self.leaderboards
+            .insert(self.playlists[playlist].id, self.score);
+        let path = self.paths.leaderboards();
+        if let Err(err) = self.leaderboards.save(&path) {
+            eprintln!("ERROR! Could not save leaderboards to {path:?}: {err}");
+        }
+    }
+
+    fn pause(&mut self) {
+        self.clock.set_paused(true);
+        self.scene = Scene::Paused(PauseMenu { cursor: 0 });
+        self.panel_drop = Some(Tween::new(0.0, 1.0, PANEL_DROP_TICKS).with_easing(Easing::EaseOut));
+    }
+
+    /// Called when the window is minimized or loses focus. Play pauses and
+    /// stays paused until the player resumes it, and so do sounds until the
+    /// window is back.
+    fn window_deactivated(&mut self) {
+        // The key releases are going to the other window
+        self.paddle_vel_x = 0.0;
+        self.pause_play();
+        self.audio.set_paused(true);
+    }
+
+    /// Pauses if a level is being played, for when the player has to stop
+    fn pause_play(&mut self) {
+        // The demo has nobody to wait for
+        if self.autopilot.is_some() {
+            self.end_demo();
+            return;
+        }
+        if matches!(
+            self.scene,
+            Scene::Playing | Scene::Intro(_) | Scene::Replay(_)
+        ) {
+            self.pause();
+        }
+    }
+
+    fn gamepad_connected(&mut self, name: &str, returning: bool) {
+        let toast = if returning {
+            format!("{name} reconnected")
+        } else {
+            format!("{name} connected")
+        };
+        self.toast = Some((toast, TOAST_TICKS));
+    }
+
+    fn gamepad_disconnected(&mut self, name: &str, active: bool) {
+        self.toast = Some((format!("{name} disconnected"), TOAST_TICKS));
+        if active {
+            // Its stick or d-pad may have been held, and the release is
+            // never coming
+            self.paddle_vel_x = 0.0;
+            self.pause_play();
+        }
+    }
+
+    fn game_over(&mut self) {
+        if self.run_mode == RunMode::Demo {
+            self.end_demo();
+            return;
+        }
+        match self.run_mode {
+            RunMode::Challenge { playlist, .. } => self.record_challenge_score(playlist),
+            RunMode::Endless => self.record_endless_score(),
+            RunMode::Daily => {
+                self.record_daily_score();
+            }
+            RunMode::Campaign | RunMode::Modifiers | RunMode::TimeAttack | RunMode::Demo => (),
+        }
+        self.save_stats();
+        self.scene = Scene::GameOver;
+        self.panel_drop = Some(
+            Tween::new(0.0, 1.0, PANEL_DROP_TICKS)
+                .with_delay(PANEL_DROP_DELAY_TICKS)
+                .with_easing(Easing::EaseInOut),
+        );
+    }
+
+    fn update_ball_speed(&mut self, factor: f32) {
+        for ball in self.balls.iter_mut() {
+            ball.vel.x *= factor;
+            ball.vel.y *= factor;
+        }
+    }
+
+    /// The window title, which carries the run time and level when the
+    /// speedrun timer is on so that capture tools can read them
+    fn window_title(&self) -> String {
+        let in_run = matches!(
+            self.scene,
+            Scene::Playing
+                | Scene::Intro(_)
+                | Scene::LevelComplete(_)
+                | Scene::Replay(_)
+                | Scene::Paused(_)
+                | Scene::GameOver
+        );
+        if !self.config.speedrun_timer || !in_run {
+            return String::from(WINDOW_TITLE);
+        }
+        format!(
+            "{WINDOW_TITLE}  {}  {}",
+            format_ticks(self.clock.run_ticks()),
+            self.level().name
+        )
+    }
+
+    /// What is in play right now, as it is drawn
+    fn render_frame(&self) -> RenderFrame {
+        // Invulnerable balls blink, hidden for four ticks out of every eight
+        let shown = |ball: &&Ball| {
+            let invulnerable_ticks = ball
+                .invulnerable
+                .and_then(|handle| self.timed_effects.remaining(handle))
+                .unwrap_or(0);
+            invulnerable_ticks & 4 == 0
+        };
+        RenderFrame {
+            balls: self.balls.iter().filter(shown).copied().collect(),
+            ball_size: self.ball_size(),
+            paddles: self.paddle_rects(),
+            // Hidden bricks aren't drawn, the same as destroyed ones
+            destroyed: self
+                .bricks
+                .is_destroyed
+                .iter()
+                .zip(&self.bricks.hidden)
+                .map(|(destroyed, hidden)| *destroyed || *hidden)
+                .collect(),
+            hit_points: self.bricks.hit_points.clone(),
+            laser_bolts: self.laser_bolts.iter().map(|bolt| bolt.pos).collect(),
+            capsules: self.capsules.clone(),
+            hazards: self.hazards.clone(),
+            boss: self.boss.clone(),
+            barrier: self.barrier.filter(|barrier| {
+                // Blinks while its time is running out
+                let remaining = self.timed_effects.remaining(barrier.expires).unwrap_or(0);
+                remaining > BARRIER_WARNING_TICKS || remaining & 8 == 0
+            }),
+            fireball: self.timed_effects.is_running(TimedEffect::Fireball),
+        }
+    }
+
+    fn draw_ball(&self, canvas: &mut Canvas, frame: &RenderFrame) {
+        let viewport = self.viewport;
+        let size = frame.ball_size;
+        let (width, height) = viewport.to_pixels(size);
+        // Tiny balls are drawn bigger than they are, centered on where they
+        // really are, with an outline so they stand out
+        let tiny = width < MIN_BALL_PIXELS || height < MIN_BALL_PIXELS;
+        let (drawn_width, drawn_height) = (width.max(MIN_BALL_PIXELS), height.max(MIN_BALL_PIXELS));
+        let (inset_x, inset_y) = ((drawn_width - width) / 2, (drawn_height - height) / 2);
+        let color = if frame.fireball {
+            FIREBALL_COLOR
+        } else {
+            self.ball_color
+        };
+        for ball in &frame.balls {
+            if frame.fireball && ball.held.is_none() {
+                // Oldest first, so nearer copies cover older ones. Each is
+                // smaller and closer to the background than the one after.
+                for step in (1..=FIREBALL_TRAIL).rev() {
+                    let fade = step as f32 / (FIREBALL_TRAIL + 1) as f32;
+                    let pos = ball.pos + ball.vel * (-(step as f32) * FIREBALL_TRAIL_SPACING);
+                    let ScreenPos { x, y } = viewport.to_screen(pos);
+                    let trail_width = (drawn_width as f32 * (1.0 - fade / 2.0)) as usize;
+                    let trail_height = (drawn_height as f32 * (1.0 - fade / 2.0)) as usize;
+                    draw_ellipse(
+                        canvas,
+                        x.saturating_sub(inset_x) + (drawn_width - trail_width) / 2,
+                        y.saturating_sub(inset_y) + (drawn_height - trail_height) / 2,
+                        trail_width,
+                        trail_height,
+                        mix_colors(FIREBALL_COLOR, self.background_color, fade),
+                    );
+                }
+            }
+            let ScreenPos { x, y } = viewport.to_screen(ball.pos);
+            let (x, y) = (x.saturating_sub(inset_x), y.saturating_sub(inset_y));
+            if tiny {
+                draw_ellipse(
+                    canvas,
+                    x.saturating_sub(1),
+                    y.saturating_sub(1),
+                    drawn_width + 2,
+                    drawn_height + 2,
+                    self.background_color ^ 0xFFFFFF,
+                );
+            }
+            draw_ellipse(canvas, x, y, drawn_width, drawn_height, color);
+
+            if ball.held.is_some() {
+                // Dots from the middle of the ball show where it will go
+                let speed = magnitude(ball.vel.x, ball.vel.y).max(f32::EPSILON);
+                let direction = WorldVec::new(ball.vel.x / speed, ball.vel.y / speed);
+                let middle = ball.pos + WorldVec::new(size.x / 2.0, -size.y / 2.0);
+                for dot in 1..=AIM_DOTS {
+                    let distance = dot as f32 * AIM_DOT_SPACING;
+                    let pos =
+                        middle + WorldVec::new(direction.x * distance, direction.y * distance);
+                    let ScreenPos { x, y } = viewport.to_screen(pos);
+                    draw_ellipse(
+                        canvas,
+                        x.saturating_sub(2),
+                        y.saturating_sub(2),
+                        4,
+                        4,
+                        self.ball_color,
+                    );
+                }
+                // Aim assist marks where the serve will come back down.
+                // Replays show where the paddle was, so the mark would be
+                // wrong.
+                if self.config.aim_assist && !matches!(self.scene, Scene::Replay(_)) {
+                    self.draw_landing_marker(canvas, ball);
+                }
+            }
+        }
+    }
+
+    /// Adds the ball color to the pixels around each ball, fading out to
+    /// nothing at `BALL_LIGHT_RADIUS` pixels from its middle. Only pixels
+    /// within that radius are visited.
+    fn draw_ball_light(&self, canvas: &mut Canvas, frame: &RenderFrame) {
+        let (width, height) = self.viewport.to_pixels(frame.ball_size);
+        let radius = BALL_LIGHT_RADIUS as i32;
+        let radius_squared = (radius * radius) as f32;
+        let channels = [16, 8, 0].map(|shift| ((self.ball_color >> shift) & 0xFF) as f32);
+        for ball in &frame.balls {
+            let ScreenPos { x, y } = self.viewport.to_screen(ball.pos);
+            let center_x = (x + width / 2) as i32;
+            let center_y = (y + height / 2) as i32;
+            let top = (center_y - radius).max(0);
+            let bottom = (center_y + radius).min(canvas.height() as i32);
+            for row in top..bottom {
+                let delta_y = row - center_y;
+                // Only the pixels of this row inside the light's circle
+                let reach = ((radius * radius - delta_y * delta_y) as f32).sqrt() as i32;
+                let left = (center_x - reach).max(0);
+                let right = (center_x + reach + 1).min(canvas.width() as i32);
+                for col in left..right {
+                    let delta_x = col - center_x;
+                    // Falling off with the square of the distance saves a
+                    // square root per pixel and looks much the same
+                    let distance_squared = (delta_x * delta_x + delta_y * delta_y) as f32;
+                    let light = BALL_LIGHT_STRENGTH * (1.0 - distance_squared / radius_squared);
+                    if light <= 0.0 {
+                        continue;
+                    }
+                    let pixel = &mut canvas.buffer[row as usize * canvas.stride + col as usize];
+                    *pixel = [16, 8, 0]
+                        .iter()
+                        .zip(channels)
+                        .fold(0, |lit, (shift, add)| {
+                            let channel = ((*pixel >> shift) & 0xFF) as f32;
+                            lit | (((channel + add * light).min(255.0) as u32) << shift)
+                        });
+                }
+            }
+        }
+    }
+
+    /// Blows up the picture around the lowest ball into a corner of the
+    /// field, so it is easy to see how the ball got past the paddle
+    fn draw_replay_zoom(&self, canvas: &mut Canvas, frame: &RenderFrame) {
+        let Some(ball) = frame
+            .balls
+            .iter()
+            .min_by(|a, b| a.pos.y.total_cmp(&b.pos.y))
+        else {
+            return;
+        };
+        let (width, height) = self.viewport.to_pixels(frame.ball_size);
+        let ScreenPos { x, y } = self.viewport.to_screen(ball.pos);
+        let half = REPLAY_ZOOM_SOURCE / 2;
+        // Kept inside the field near the edges, rather than showing past it
+        let source = PixelRect {
+            x: (x + width / 2)
+                .saturating_sub(half)
+                .min(canvas.width().saturating_sub(REPLAY_ZOOM_SOURCE)),
+            y: (y + height / 2)
+                .saturating_sub(half)
+                .max(self.viewport.top)
+                .min(canvas.height().saturating_sub(REPLAY_ZOOM_SOURCE)),
+            width: REPLAY_ZOOM_SOURCE,
+            height: REPLAY_ZOOM_SOURCE,
+        };
+        let mut zoomed = Canvas {
+            buffer: vec![self.background_color; REPLAY_ZOOM_SOURCE * REPLAY_ZOOM_SOURCE],
+            stride: REPLAY_ZOOM_SOURCE,
+        };
+        blit(&mut zoomed, canvas, source, 0, 0, None);
+
+        let size = REPLAY_ZOOM_SOURCE * REPLAY_ZOOM_SCALE;
+        let dest = PixelRect {
+            x: canvas.width().saturating_sub(REPLAY_ZOOM_MARGIN + size),
+            y: self.viewport.top + REPLAY_ZOOM_MARGIN,
+            width: size,
+            height: size,
+        };
+        draw_rect(
+            canvas,
+            dest.x.saturating_sub(REPLAY_ZOOM_BORDER),
+            dest.y.saturating_sub(REPLAY_ZOOM_BORDER),
+            size + 2 * REPLAY_ZOOM_BORDER,
+            size + 2 * REPLAY_ZOOM_BORDER,
+            self.ball_color,
+        );
+        draw_subcanvas_scaled(canvas, &zoomed, dest, ScaleFilter::Bilinear);
+    }
+
+    fn draw_paddle(&self, canvas: &mut Canvas, frame: &RenderFrame) {
+        let viewport = self.viewport;
+        let squash = self.paddle_squash.as_ref().map_or(1.0, Sequence::value);
+        for paddle in &frame.paddles {
+            // Squashing only changes how the paddle looks: it keeps its top
+            // edge and spreads sideways as it flattens
+            let size = WorldVec::new(
+                paddle.size.x * (2.0 - squash).sqrt(),
+                paddle.size.y * squash,
+            );
+            let pos = paddle.pos + WorldVec::new((paddle.size.x - size.x) / 2.0, 0.0);
+            let ScreenPos { x, y } = viewport.to_screen(pos);
+            let (width, height) = viewport.to_pixels(size);
+            draw_rect(canvas, x, y, width, height, self.paddle_color);
+            if self.config.paddle_shape == PaddleShape::Notched {
+                let notch = (width as f32 * PADDLE_NOTCH_WIDTH) as usize;
+                let notch_x = x + (width - notch) / 2;
+                draw_rect(canvas, notch_x, y, notch, height / 3, self.background_color);
+            }
+        }
+    }
+
+    /// Tints the strips of the field where bricks score extra, more
+    /// strongly the bigger the multiplier
+    fn draw_score_zones(&self, canvas: &mut Canvas) {
+        let field = self.field;
+        for zone in self.level().score_zones {
+            let left = field.left + zone.from * field.width();
+            let right = field.left + zone.to * field.width();
+            let top_left = self.viewport.to_screen(WorldPos::new(left, field.top));
+            let bottom_right = self.viewport.to_screen(WorldPos::new(right, field.bottom));
+            let amount = SCORE_ZONE_TINT_AMOUNT * (zone.multiplier - 1) as f32;
+            draw_rect(
+                canvas,
+                top_left.x,
+                top_left.y,
+                bottom_right.x.saturating_sub(top_left.x),
+                bottom_right.y.saturating_sub(top_left.y),
+                mix_colors(self.background_color, SCORE_ZONE_TINT, amount),
+            );
+        }
+    }
+
+    /// Fills in the screen outside the play field and marks the gaps in the
+    /// walls
+    fn draw_walls(&self, canvas: &mut Canvas) {
+        let viewport = self.viewport;
+        let field = self.field;
+        let ScreenPos { x: left, y: top } =
+            viewport.to_screen(WorldPos::new(field.left, field.top));
+        let right = viewport.to_screen(WorldPos::new(field.right, field.top)).x;
+        let (width, height) = (viewport.width, viewport.height);
+        draw_rect(canvas, 0, viewport.top, left, height, WALL_COLOR);
+        draw_rect(
+            canvas,
+            right,
+            viewport.top,
+            width.saturating_sub(right),
+            height,
+            WALL_COLOR,
+        );
+        draw_rect(
+            canvas,
+            0,
+            viewport.top,
+            width,
+            top - viewport.top,
+            WALL_COLOR,
+        );
+        // Drawn just inside the field, since the HUD strip covers the space
+        // above a full height field
+        if field.open_top {
+            draw_rect(
+                canvas,
+                left,
+                top,
+                right - left,
+                WALL_GAP_MARKER_WIDTH,
+                OPEN_TOP_COLOR,
+            );
+        }
+
+        for gap in field.gaps {
+            let x = match gap.side {
+                Side::Left => left.saturating_sub(WALL_GAP_MARKER_WIDTH),
+                Side::Right => right,
+            };
+            let gap_top = viewport.to_screen(WorldPos::new(field.left, gap.top)).y;
+            let gap_bottom = viewport.to_screen(WorldPos::new(field.left, gap.bottom)).y;
+            let color = match gap.kind {
+                GapKind::Exit => WALL_GAP_COLOR,
+                GapKind::Wrap => WALL_WRAP_COLOR,
+            };
+            draw_rect(
+                canvas,
+                x,
+                gap_top,
+                WALL_GAP_MARKER_WIDTH,
+                gap_bottom - gap_top,
+                color,
+            );
+        }
+    }
+
+    /// Marks where each ball will come down to the paddle's height
+    fn draw_landing_markers(&self, canvas: &mut Canvas) {
+        for ball in &self.balls {
+            self.draw_landing_marker(canvas, ball);
+        }
+    }
+
+    fn draw_landing_marker(&self, canvas: &mut Canvas, ball: &Ball) {
+        let Some(x) = self.landing_x(ball) else {
+            return;
+        };
+        let size = self.ball_size();
+        let (width, height) = self
+            .viewport
+            .to_pixels(WorldVec::new(size.x / 2.0, size.y / 4.0));
+        // Centered under the ball, just above the paddle
+        let pos = WorldPos::new(x + size.x / 4.0, self.paddle_pos.y + size.y / 2.0);
+        let ScreenPos { x, y } = self.viewport.to_screen(pos);
+        draw_rect(canvas, x, y, width, height, self.ball_color);
+    }
+
+    /// The boss flashes when hit, under a bar showing its health left
+    fn draw_boss(&self, canvas: &mut Canvas, frame: &RenderFrame) {
+        let Some(boss) = &frame.boss else {
+            return;
+        };
+        let viewport = self.viewport;
+        let ScreenPos { x, y } = viewport.to_screen(boss.pos);
+        let (width, height) = viewport.to_pixels(boss::SIZE);
+        let color = if boss.flash_ticks > 0 {
+            BOSS_FLASH_COLOR
+        } else {
+            BOSS_COLOR
+        };
+        draw_rect(canvas, x, y, width, height, color);
+
+        let bar_pos = boss.pos + WorldVec::new(0.0, BOSS_HEALTH_GAP + BOSS_HEALTH_HEIGHT);
+        let ScreenPos { x, y } = viewport.to_screen(bar_pos);
+        let (_, bar_height) = viewport.to_pixels(WorldVec::new(0.0, BOSS_HEALTH_HEIGHT));
+        let health = (width as f32 * (1.0 - boss.hurt())).round() as usize;
+        draw_rect(canvas, x, y, width, bar_height, BOSS_HEALTH_BACKGROUND);
+        draw_rect(canvas, x, y, health, bar_height, BOSS_HEALTH_COLOR);
+    }
+
+    /// A bar across the field, with a jagged crack for each hit it has
+    /// taken
+    fn draw_barrier(&self, canvas: &mut Canvas, frame: &RenderFrame) {
+        let Some(barrier) = frame.barrier else {
+            return;
+        };
+        let viewport = self.viewport;
+        let ScreenPos { x, y } = viewport.to_screen(WorldPos::new(self.field.left, barrier.top));
+        let (width, height) = viewport.to_pixels(WorldVec::new(self.field.width(), BARRIER_HEIGHT));
+        draw_rect(canvas, x, y, width, height, BARRIER_COLOR);
+        for &crack in BARRIER_CRACKS.iter().take(barrier.cracks() as usize) {
+            let crack_x = x + (crack * width as f32) as usize;
+            // Zigzags down in steps of two pixels
+            for step in 0..height.div_ceil(2) {
+                let offset = if step % 2 == 0 { 0 } else { 2 };
+                draw_rect(
+                    canvas,
+                    crack_x + offset,
+                    y + step * 2,
+                    2,
+                    2,
+                    BARRIER_CRACK_COLOR,
+                );
+            }
+        }
+    }
+
+    fn draw_hazards(&self, canvas: &mut Canvas, frame: &RenderFrame) {
+        let viewport = self.viewport;
+        let (width, height) = viewport.to_pixels(WorldVec::new(HAZARD_SIZE, HAZARD_SIZE));
+        for &hazard in &frame.hazards {
+            let ScreenPos { x, y } = viewport.to_screen(hazard);
+            draw_ellipse(canvas, x, y, width, height, HAZARD_COLOR);
+        }
+    }
+
+    /// Each kind of sound has its own picture: a dot swelling for a knock,
+    /// shards flying apart for a break or glass, a rising plus for a
+    /// power-up, a sinking bar for a lost ball and a streak for the laser
+    fn draw_sound_cues(&self, canvas: &mut Canvas) {
+        let (width, height) = self
+            .viewport
+            .to_pixels(WorldVec::new(SOUND_CUE_SIZE, SOUND_CUE_SIZE));
+        let (width, height) = (width as f32, height as f32);
+        for cue in &self.sound_cues {
+            let age = cue.age.value();
+            let color = mix_colors(SOUND_CUE_COLOR, self.background_color, age);
+            let ScreenPos { x, y } = self.viewport.to_screen(cue.pos);
+            // Draws a rect centered `dx` and `dy` cue sizes from the cue's
+            // position, `w` and `h` cue sizes big
+            let mut part = |dx: f32, dy: f32, w: f32, h: f32| {
+                let (w, h) = ((w * width).max(1.0), (h * height).max(1.0));
+                let left = x as f32 + dx * width - w / 2.0;
+                let top = y as f32 + dy * height - h / 2.0;
+                draw_rect(
+                    canvas,
+                    left.max(0.0) as usize,
+                    top.max(0.0) as usize,
+                    w as usize,
+                    h as usize,
+                    color,
+                );
+            };
+            match cue.sfx {
+                Sfx::PaddleHit | Sfx::StoneHit | Sfx::MetalHit => {
+                    let size = 0.4 + 0.6 * age;
+                    let (w, h) = (width * size, height * size);
+                    draw_ellipse(
+                        canvas,
+                        (x as f32 - w / 2.0).max(0.0) as usize,
+                        (y as f32 - h / 2.0).max(0.0) as usize,
+                        w as usize,
+                        h as usize,
+                        color,
+                    );
+                }
+                Sfx::BrickBreak | Sfx::GlassHit => {
+                    let spread = 0.2 + 0.4 * age;
+                    for (sx, sy) in [(-1.0, -1.0), (1.0, -1.0), (-1.0, 1.0), (1.0, 1.0)] {
+                        part(sx * spread, sy * spread, 0.25, 0.25);
+                    }
+                }
+                Sfx::PowerUp => {
+                    let rise = -0.5 * age;
+                    part(0.0, rise, 0.8, 0.2);
+                    part(0.0, rise, 0.2, 0.8);
+                }
+                Sfx::BallLost => part(0.0, 0.5 * age, 0.8, 0.2),
+                Sfx::LaserFire => part(0.0, -0.5 - age, 0.1, 0.8),
+                Sfx::MenuMove | Sfx::MenuConfirm => (),
+            }
+        }
+    }
+
+    /// Capsules are pills with a stripe in the power-up's marker color, like
+    /// the bricks they came from. They spin as they fall, so each is drawn
+    /// flat on a picture of its own and then turned onto the canvas.
+    fn draw_capsules(&self, canvas: &mut Canvas, frame: &RenderFrame) {
+        let viewport = self.viewport;
+        let (width, height) = viewport.to_pixels(WorldVec::new(CAPSULE_WIDTH, CAPSULE_HEIGHT));
+        // Round ends as wide as the capsule is high
+        let cap = height.min(width / 2);
+        let middle = width - cap;
+        let mut picture = Canvas {
+            buffer: vec![CAPSULE_COLOR_KEY; width * height],
+            stride: width,
+        };
+        for capsule in &frame.capsules {
+            picture.buffer.fill(CAPSULE_COLOR_KEY);
+            let color = capsule.power_up.capsule_color();
+            draw_ellipse(&mut picture, 0, 0, cap, height, color);
+            draw_ellipse(&mut picture, width - cap, 0, cap, height, color);
+            draw_rect(&mut picture, cap / 2, 0, middle, height, color);
+            let stripe_color = capsule.power_up.marker_color();
+            draw_rect(
+                &mut picture,
+                cap / 2,
+                height / 3,
+                middle,
+                height / 3,
+                stripe_color,
+            );
+
+            let ScreenPos { x, y } = viewport.to_screen(capsule.pos);
+            draw_rotated_subcanvas(
+                canvas,
+                &picture,
+                x as f32 + width as f32 / 2.0,
+                y as f32 + height as f32 / 2.0,
+                capsule.pos.y * CAPSULE_SPIN,
+                Some(CAPSULE_COLOR_KEY),
+            );
+        }
+    }
+
+    fn draw_tumbling_bricks(&self, canvas: &mut Canvas) {
+        let (width, height) = self
+            .viewport
+            .to_pixels(WorldVec::new(self.bricks.width, self.bricks.height));
+        // Above the top of the field has no place on the screen
+        for brick in self
+            .tumbling_bricks
+            .iter()
+            .filter(|brick| brick.pos.y <= self.field.top)
+        {
+            let ScreenPos { x, y } = self.viewport.to_screen(brick.pos);
+            let fade = 1.0 - brick.ticks_left as f32 / TUMBLE_TICKS as f32;
+            draw_rotated_rect(
+                canvas,
+                x as f32,
+                y as f32,
+                width as f32,
+                height as f32,
+                brick.angle,
+                mix_colors(brick.color, self.background_color, fade),
+            );
+        }
+    }
+
+    fn draw_laser_bolts(&self, canvas: &mut Canvas, frame: &RenderFrame) {
+        let viewport = self.viewport;
+        let (width, height) =
+            viewport.to_pixels(WorldVec::new(LASER_BOLT_WIDTH, LASER_BOLT_HEIGHT));
+        for bolt in &frame.laser_bolts {
+            // Bolts that have partially left the top of the field are not drawn
+            if bolt.y <= self.field.top {
+                let ScreenPos { x, y } = viewport.to_screen(*bolt);
+                draw_rect(canvas, x, y, width, height, LASER_BOLT_COLOR);
+            }
+        }
+    }
+
+    fn draw_laser_meter(&self, canvas: &mut Canvas) {
+        if self.laser_gun.ammo == 0 && self.laser_gun.heat == 0.0 {
+            return;
+        }
+        const MARGIN: usize = 4;
+        const PIP_SIZE: usize = 4;
+        const METER_HEIGHT: usize = 6;
+        let bottom = canvas.height() - MARGIN;
+
+        // One pip per remaining shot
+        for n in 0..self.laser_gun.ammo as usize {
+            let x = MARGIN + n * (PIP_SIZE + 2);
+            draw_rect(
+                canvas,
+                x,
+                bottom - PIP_SIZE,
+                PIP_SIZE,
+                PIP_SIZE,
+                LASER_BOLT_COLOR,
+            );
+        }
+
+        // Heat bar above the ammo pips, dimmed while the gun is locked out
+        let meter_width = LASER_MAX_AMMO as usize * (PIP_SIZE + 2) - 2;
+        let meter_y = bottom - PIP_SIZE - 2 - METER_HEIGHT;
+        let heat_width = (self.laser_gun.heat * meter_width as f32) as usize;
+        let heat_color = if self.laser_gun.overheated {
+            0x808080
+        } else {
+            0xFFA500
+        };
+        draw_rect(canvas, MARGIN, meter_y, meter_width, METER_HEIGHT, 0xFFFFFF);
+        draw_rect(
+            canvas,
+            MARGIN,
+            meter_y,
+            heat_width,
+            METER_HEIGHT,
+            heat_color,
+        );
+    }
+
+    fn draw_modifier_pick(&self, pick: &ModifierPick, canvas: &mut Canvas) {
+        let Some(font) = self.font.as_ref() else {
+            return;
+        };
+        const GAP: usize = 12;
+        let title = compute_text_data(font, HUD_TEXT_HEIGHT * 1.5, "LEVEL CLEAR - PICK A MODIFIER");
+        let mut y = canvas.height() / 4;
+        draw_subcanvas(
+            canvas,
+            &title,
+            canvas.width().saturating_sub(title.width()) / 2,
+            y,
+        );
+        y += title.height() + GAP * 2;
+
+        for (index, choice) in pick.choices.iter().enumerate() {
+            let text = compute_multiline_text_data(
+                font,
+                HUD_TEXT_HEIGHT,
+                &[choice.name, choice.description],
+            );
+            let x = canvas.width().saturating_sub(text.width()) / 2;
+            if index == pick.cursor {
+                let inset = PANEL_BORDER + PANEL_PADDING;
+                draw_focus_outline(
+                    canvas,
+                    x.saturating_sub(inset),
+                    y.saturating_sub(inset),
+                    text.width() + 2 * inset,
+                    text.height() + 2 * inset,
+                    GAP / 2,
+                    self.focus(),
+                );
+            }
+            draw_panel(canvas, x, y, text.width(), text.height());
+            draw_subcanvas(canvas, &text, x, y);
+            y += text.height() + GAP * 3;
+        }
+
+        let active = self
+            .modifiers
+            .iter()
+            .map(|modifier| modifier.name)
+            .collect::<Vec<_>>()
+            .join(", ");
+        if !active.is_empty() {
+            let text = compute_text_data(font, HUD_TEXT_HEIGHT * 0.75, &active);
+            let x = canvas.width().saturating_sub(text.width()) / 2;
+            draw_subcanvas(canvas, &text, x, y + GAP);
+        }
+    }
+
+    fn draw_game_over(&self, canvas: &mut Canvas) {
+        let Some(font) = self.font.as_ref() else {
+            return;
+        };
+        let score = format!("score {}", self.score);
+        let checkpoint = format!(
+            "[C] continue from level {} (score reset)",
+            self.checkpoint() + 1
+        );
+        let text_canvas = if self.run_mode == RunMode::Endless {
+            let best = format!("best {}  waves {}", self.progress.endless_best, self.waves);
+            compute_multiline_text_data(
+                font,
+                HUD_TEXT_HEIGHT,
+                &["GAME OVER", &score, &best, " ", "[R] play endless again"],
+            )
+        } else if self.run_mode == RunMode::Daily {
+            let day = self.daily.as_ref().map_or(0, |daily| daily.day);
+            let best = format!("best today {}", self.progress.daily_best(day).unwrap_or(0));
+            compute_multiline_text_data(
+                font,
+                HUD_TEXT_HEIGHT,
+                &["GAME OVER", &score, &best, " ", "[R] try again"],
+            )
+        } else if self.run_mode == RunMode::TimeAttack {
+            compute_multiline_text_data(
+                font,
+                HUD_TEXT_HEIGHT,
+                &["GAME OVER", &score, " ", "[R] try again"],
+            )
+        } else if self.run_mode == RunMode::Campaign {
+            compute_multiline_text_data(
+                font,
+                HUD_TEXT_HEIGHT,
+                &[
+                    "GAME OVER",
+                    &score,
+                    " ",
+                    &checkpoint,
+                    "[R] restart from level 1",
+                ],
+            )
+        } else {
+            compute_multiline_text_data(
+                font,
+                HUD_TEXT_HEIGHT,
+                &["GAME OVER", &score, " ", "[R] restart from level 1"],
+            )
+        };
+        let x = canvas.width().saturating_sub(text_canvas.width()) / 2;
+        let drop = self.panel_drop.as_ref().map_or(1.0, Tween::value);
+        let rest_y = canvas.height().saturating_sub(text_canvas.height()) / 2;
+        let y = (drop * rest_y as f32) as usize;
+        draw_panel(canvas, x, y, text_canvas.width(), text_canvas.height());
+        draw_subcanvas(canvas, &text_canvas, x, y);
+    }
+
+    fn draw_hud(&self, canvas: &mut Canvas) {
+        let font = self
+            .font
+            .as_ref()
+            .expect("Method is only called if font.is_some()");
+        draw_rect(
+            canvas,
+            0,
+            0,
+            canvas.width(),
+            HUD_STRIP_HEIGHT,
+            HUD_STRIP_COLOR,
+        );
+
+        let mut score = format!("score {}", self.score);
+        if self.score_bank.points > 0 {
+            score += &format!(
+                "  bank {} x{:.2}",
+                self.score_bank.points,
+                self.score_bank.multiplier()
+            );
+        }
+        let mut name = format!("{}  lives {}", self.level().name, self.lives);
+        if self.run_mode == RunMode::Endless {
+            name += &format!("  wave {} x{}", self.waves, self.endless_multiplier());
+        }
+        // The level time so far, counted in ticks so that it stops with
+        // pauses and slow frames
+        if self.run_mode == RunMode::TimeAttack {
+            name += &format!("  time {}", format_ticks(self.clock.level_ticks()));
+            if let Some(best) = self.progress.level(self.level_index).best_ticks {
+                name += &format!(" (best {})", format_ticks(best));
+            }
+        }
+        // The bonus word with the letters still to find blanked out
+        if !self.bonus_letters.is_empty() {
+            let word = BONUS_WORD
+                .chars()
+                .map(|letter| {
+                    if self.bonus_letters.contains(&letter) {
+                        letter
+                    } else {
+                        '_'
+                    }
+                })
+                .collect::<String>();
+            name += &format!("  {word}");
+        }
+        // Seconds left on the paddle size, slow motion, fireball, debuff and
+        // barrier effects, counting down in the middle
+        let resized = self
+            .timed_effects
+            .iter()
+            .filter_map(|(kind, remaining)| {
+                let label = match kind {
+                    TimedEffect::WidePaddle => "wide",
+                    TimedEffect::NarrowPaddle => "narrow",
+                    TimedEffect::SlowMotion => "slow",
+                    TimedEffect::Fireball => "fire",
+                    TimedEffect::ReverseControls => "reversed",
+                    TimedEffect::SpeedUp => "fast",
+                    // A broken barrier's time doesn't matter any more
+                    TimedEffect::Barrier if self.barrier.is_some() => "barrier",
+                    _ => return None,
+                };
+                Some(format!(
+                    "{label} {}",
+                    remaining.div_ceil(clock::TICKS_PER_SECOND)
+                ))
+            })
+            .collect::<Vec<_>>()
+            .join("  ");
+        let name = compute_text_data(font, HUD_TEXT_HEIGHT, &name);
+        let score = compute_text_data(font, HUD_TEXT_HEIGHT, &score);
+        let resized = compute_text_data(font, HUD_TEXT_HEIGHT, &resized);
+        // Key out the text background so the strip shows between the letters
+        for (text_canvas, x) in [
+            (&name, HUD_STRIP_MARGIN as i32),
+            (
+                &resized,
+                (canvas.width() as i32 - resized.width() as i32) / 2,
+            ),
+            (
+                &score,
+                canvas.width() as i32 - (score.width() + HUD_STRIP_MARGIN) as i32,
+            ),
+        ] {
+            let y = HUD_STRIP_HEIGHT.saturating_sub(text_canvas.height()) / 2;
+            blit(
+                canvas,
+                text_canvas,
+                text_canvas.bounds(),
+                x,
+                y as i32,
+                Some(0xFFFFFF),
+            );
+        }
+
+        if let Some((message, _)) = self.message {
+            let text_canvas = compute_text_data(font, HUD_TEXT_HEIGHT, message);
+            let x = canvas.width().saturating_sub(text_canvas.width()) / 2;
+            let y = canvas.height() / 3;
+            draw_panel(canvas, x, y, text_canvas.width(), text_canvas.height());
+            draw_subcanvas(canvas, &text_canvas, x, y);
+        }
+
+        if let Some(banner) = &self.chain_banner {
+            let text = format!("CHAIN x{} +{}", banner.bricks, banner.points);
+            let text_canvas = compute_text_data(font, HUD_TEXT_HEIGHT, &text);
+            let ScreenPos { x, y } = self.viewport.to_screen(banner.pos);
+            let rise = (banner.rise.value() * HUD_TEXT_HEIGHT * 2.0) as usize;
+            let x = x.min(canvas.width().saturating_sub(text_canvas.width()));
+            let y = y.saturating_sub(rise);
+            draw_panel(canvas, x, y, text_canvas.width(), text_canvas.height());
+            draw_subcanvas(canvas, &text_canvas, x, y);
+        }
+    }
+
+    fn draw_debug_stats(&self, canvas: &mut Canvas) {
+        let Some(ball) = self.balls.first() else {
+            return;
+        };
+        let ball_position = format!(
+            "{pos:<12} ({pos_x:+.3}, {pos_y:+.3})",
+            pos = "pos:",
+            pos_x = ball.pos.x,
+            pos_y = ball.pos.y
+        );
+        let ball_velocity = format!(
+            "{vel:<12} ({vel_x:+.3}, {vel_y:+.3})",
+            vel = "vel:",
+            vel_x = ball.vel.x,
+            vel_y = ball.vel.y
+        );
+        let paddle_pos = format!(
+            "{pos:<12} ({pos_x:+.3}, {pos_y:+.3})",
+            pos = "paddle_pos:",
+            pos_x = self.paddle_pos.x,
+            pos_y = self.paddle_pos.y
+        );
+        let mut effects = self
+            .timed_effects
+            .iter()
+            .map(|(kind, ticks)| format!("{kind:?} {ticks}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        if effects.is_empty() {
+            effects = String::from("none");
+        }
+        let effects = format!("{label:<12} {effects}", label = "effects:");
+        let ball_count = format!(
+            "{label:<12} {count} ramp x{ramp:.2} ({hits}/{every})",
+            label = "balls:",
+            count = self.balls.len(),
+            ramp = self.speed_ramp,
+            hits = self.speed_ramp_hits,
+            every = self.config.speed_ramp_hits
+        );
+        let par = format!(
+            "{label:<12} {score} in {seconds}s ({stars}* best)",
+            label = "par:",
+            score = self.level().par_score,
+            seconds = self.level().par_ticks / clock::TICKS_PER_SECOND,
+            stars = self.progress.level(self.level_index).stars
+        );
+        let time = format!(
+            "{label:<12} level {level} run {run} x{scale:.2}",
+            label = "time:",
+            level = format_ticks(self.clock.level_ticks()),
+            run = format_ticks(self.clock.run_ticks()),
+            scale = self.clock.time_scale()
+        );
+        let audio = format!(
+            "{label:<12} {status}",
+            label = "audio:",
+            status = self.audio.describe()
+        );
+        let chains = format!(
+            "{label:<12} {count} longest {longest} bonus {bonus}",
+            label = "chains:",
+            count = self.chain_stats.count,
+            longest = self.chain_stats.longest,
+            bonus = self.chain_stats.bonus
+        );
+        let hash = format!(
+            "{label:<12} {hash:016x}",
+            label = "hash:",
+            hash = determinism::state_hash(self)
+        );
+        let text_canvas = compute_multiline_text_data(
+            self.font
+                .as_ref()
+                .expect("Method is only called if font.is_some()"),
+            self.debug_stats_height,
+            &[
+                &ball_position,
+                &ball_velocity,
+                &paddle_pos,
+                &effects,
+                &ball_count,
+                &par,
+                &time,
+                &audio,
+                &chains,
+                &hash,
+            ],
+        );
+        draw_subcanvas(canvas, &text_canvas, 0, HUD_STRIP_HEIGHT);
+    }
+
+    fn draw_bricks(&self, canvas: &mut Canvas, frame: &RenderFrame) {
+        let viewport = self.viewport;
+        let (width, height) =
+            viewport.to_pixels(WorldVec::new(self.bricks.width, self.bricks.height));
+        let bevel = (height / 8).max(1);
+        let shadow = (height / 4).max(1);
+
+        // All the shadows go down first so that none of them fall on a brick
+        let standing = self
+            .bricks
+            .x_positions
+            .iter()
+            .zip(self.bricks.y_positions.iter())
+            .zip(frame.destroyed.iter())
+            .filter(|(_, destroyed)| !**destroyed)
+            .map(|((x, y), _)| viewport.to_screen(WorldPos::new(*x, *y)));
+        let outer_shadow = mix_colors(self.background_color, 0x000000, BRICK_SHADOW_DARKNESS / 2.0);
+        let inner_shadow = mix_colors(self.background_color, 0x000000, BRICK_SHADOW_DARKNESS);
+        for ScreenPos { x, y } in standing {
+            // The outer half of the shadow is lighter, which softens its edge
+            draw_rect(canvas, x + shadow, y + shadow, width, height, outer_shadow);
+            draw_rect(
+                canvas,
+                x + shadow / 2,
+                y + shadow / 2,
+                width,
+                height,
+                inner_shadow,
+            );
+        }
+
+        for (
+            ((((((brick_x, brick_y), color), destroyed), power_up), explosive), hit_points),
+            metal,
+        ) in self
+            .bricks
+            .x_positions
+            .iter()
+            .zip(self.bricks.y_positions.iter())
+            .zip(self.bricks.colors.iter())
+            .zip(frame.destroyed.iter())
+            .zip(self.bricks.power_ups.iter())
+            .zip(self.bricks.explosive.iter())
+            .zip(frame.hit_points.iter())
+            .zip(self.bricks.metal.iter())
+        {
+            if !destroyed {
+                let ScreenPos { x, y } = viewport.to_screen(WorldPos::new(*brick_x, *brick_y));
+                let armor = hit_points.saturating_sub(1) as f32 * ARMOR_TINT_PER_HIT_POINT;
+                let color = mix_colors(*color, ARMORED_BRICK_COLOR, armor);
+                draw_rect(canvas, x, y, width, height, color);
+                if *metal {
+                    // A shine along the top edge
+                    draw_rect(canvas, x, y, width, height / 4, METAL_SHINE_COLOR);
+                }
+                if let Some(power_up) = power_up {
+                    // Mark power-up bricks with a stripe through the middle
+                    let stripe_color = power_up.marker_color();
+                    draw_rect(canvas, x, y + height / 3, width, height / 3, stripe_color);
+                }
+                if *explosive {
+                    // Mark explosive bricks with a square in the middle
+                    let size = height / 2;
+                    let marker_x = x + (width - size) / 2;
+                    let marker_y = y + (height - size) / 2;
+                    draw_rect(canvas, marker_x, marker_y, size, size, 0x000000);
+                    draw_rect(
+                        canvas,
+                        marker_x + size / 4,
+                        marker_y + size / 4,
+                        size - size / 2,
+                        size - size / 2,
+                        EXPLOSIVE_MARKER_COLOR,
+                    );
+                }
+                draw_bevel(canvas, x, y, width, height, bevel, color);
+            }
+        }
+    }
+
+    fn draw_all(&self, canvas: &mut Canvas) {
+        self.draw_scene(canvas);
+        self.draw_toast(canvas);
+        if self.confirm_quit {
+            dim_rect(canvas, canvas.bounds());
+            self.draw_confirm_quit(canvas);
+        }
+    }
+
+    fn draw_toast(&self, canvas: &mut Canvas) {
+        let (Some(font), Some((toast, _))) = (self.font.as_ref(), &self.toast) else {
+            return;
+        };
+        let text_canvas = compute_text_data(font, HUD_TEXT_HEIGHT, toast);
+        let x = canvas.width().saturating_sub(text_canvas.width()) / 2;
+        let y = canvas
+            .height()
+            .saturating_sub(text_canvas.height() + HUD_STRIP_HEIGHT);
+        draw_panel(canvas, x, y, text_canvas.width(), text_canvas.height());
+        draw_subcanvas(canvas, &text_canvas, x, y);
+    }
+
+    /// Shows `text` just below the HUD, for what is on screen instead of play
+    fn draw_banner(&self, canvas: &mut Canvas, text: &str) {
+        let Some(font) = self.font.as_ref() else {
+            return;
+        };
+        let text_canvas = compute_text_data(font, HUD_TEXT_HEIGHT, text);
+        let x = canvas.width().saturating_sub(text_canvas.width()) / 2;
+        let y = HUD_STRIP_HEIGHT + HUD_STRIP_MARGIN;
+        draw_panel(canvas, x, y, text_canvas.width(), text_canvas.height());
+        draw_subcanvas(canvas, &text_canvas, x, y);
+    }
+
+    fn draw_level_complete(&self, canvas: &mut Canvas, complete: &LevelComplete) {
+        let Some(font) = self.font.as_ref() else {
+            return;
+        };
+        let score = format!("score +{}", complete.score);
+        let stars = complete.stars.map_or(String::new(), |stars| {
+            format!(
+                "{}{}",
+                "*".repeat(stars as usize),
+                "-".repeat(3 - stars as usize)
+            )
+        });
+        let next = format!("next: {}  [Enter] go", self.levels[complete.next].name);
+        let text_canvas = compute_multiline_text_data(
+            font,
+            HUD_TEXT_HEIGHT,
+            &["LEVEL COMPLETE", complete.name, &score, &stars, " ", &next],
+        );
+        let x = canvas.width().saturating_sub(text_canvas.width()) / 2;
+        let drop = self.panel_drop.as_ref().map_or(1.0, Tween::value);
+        let rest_y = canvas.height().saturating_sub(text_canvas.height()) / 2;
+        let y = (drop * rest_y as f32) as usize;
+        draw_panel(canvas, x, y, text_canvas.width(), text_canvas.height());
+        draw_subcanvas(canvas, &text_canvas, x, y);
+    }
+
+    /// Draws the intro card over a dimmed view of the level, with both
+    /// pushed `wipe` of the way off the right of the screen
+    fn draw_intro(&self, canvas: &mut Canvas, wipe: f32) {
+        let offset = (wipe * canvas.width() as f32) as usize;
+        dim_rect(
+            canvas,
+            PixelRect {
+                x: offset,
+                y: 0,
+                width: canvas.width().saturating_sub(offset),
+                height: canvas.height(),
+            },
+        );
+        let Some(font) = self.font.as_ref() else {
+            return;
+        };
+        let number = format!("LEVEL {}", self.level_index + 1);
+        let text_canvas =
+            compute_multiline_text_data(font, HUD_TEXT_HEIGHT * 1.5, &[&number, self.level().name]);
+        let x = canvas.width().saturating_sub(text_canvas.width()) / 2 + offset;
+        let y = canvas.height().saturating_sub(text_canvas.height()) / 2;
+        draw_panel(canvas, x, y, text_canvas.width(), text_canvas.height());
+        draw_subcanvas(canvas, &text_canvas, x, y);
+    }
+
+    fn draw_confirm_quit(&self, canvas: &mut Canvas) {
+        let Some(font) = self.font.as_ref() else {
+            return;
+        };
+        let text_canvas = compute_multiline_text_data(
+            font,
+            HUD_TEXT_HEIGHT,
+            &["QUIT THE GAME?", " ", "[Y] quit  [N] stay"],
+        );
+        let x = canvas.width().saturating_sub(text_canvas.width()) / 2;
+        let y = canvas.height().saturating_sub(text_canvas.height()) / 2;
+        draw_panel(canvas, x, y, text_canvas.width(), text_canvas.height());
+        draw_subcanvas(canvas, &text_canvas, x, y);
+    }
+
+    fn draw_scene(&self, canvas: &mut Canvas) {
+        canvas.buffer.fill(self.background_color);
+        match &self.scene {
+            Scene::LevelSelect(level_select) => {
+                level_select.draw(
+                    canvas,
+                    self.font.as_deref(),
+                    &self.levels,
+                    &self.level_thumbnails,
+                    &self.progress,
+                    self.focus(),
+                );
+                return;
+            }
+            Scene::GameOver => {
+                self.draw_game_over(canvas);
+                return;
+            }
+            Scene::PickModifier(pick) => {
+                self.draw_modifier_pick(pick, canvas);
+                return;
+            }
+            Scene::Challenges(challenge_select) => {
+                challenge_select.draw(
+                    canvas,
+                    self.font.as_deref(),
+                    &self.playlists,
+                    &self.leaderboards,
+                    self.focus(),
+                );
+                return;
+            }
+            Scene::Stats(_) => {
+                stats::draw(canvas, self.font.as_deref(), &self.stats);
+                return;
+            }
+            Scene::Settings(menu) => {
+                menu.draw(canvas, self.font.as_deref(), &self.settings, self.focus());
+                return;
+            }
+            Scene::Setup(wizard) => {
+                wizard.draw(canvas, self.font.as_deref(), self.focus());
+                return;
+            }
+            Scene::Playing
+            | Scene::Intro(_)
+            | Scene::LevelComplete(_)
+            | Scene::Replay(_)
+            | Scene::Paused(_) => (),
+        }
+
+        let live;
+        let frame = match &self.scene {
+            Scene::Replay(replay) => replay.frame(),
+            _ => {
+                live = self.render_frame();
+                &live
+            }
+        };
+        self.draw_score_zones(canvas);
+        self.draw_walls(canvas);
+        self.draw_ball(canvas, frame);
+        self.draw_paddle(canvas, frame);
+        // The markers are for the balls in play, not the ones replayed
+        if self.config.landing_marker && !matches!(self.scene, Scene::Replay(_)) {
+            self.draw_landing_markers(canvas);
+        }
+        self.draw_bricks(canvas, frame);
+        // Like the sound cues, these are of the game in play
+        if !matches!(self.scene, Scene::Replay(_)) {
+            self.draw_tumbling_bricks(canvas);
+        }
+        self.draw_laser_bolts(canvas, frame);
+        self.draw_capsules(canvas, frame);
+        self.draw_boss(canvas, frame);
+        self.draw_hazards(canvas, frame);
+        self.draw_barrier(canvas, frame);
+        if !matches!(self.scene, Scene::Replay(_)) {
+            self.draw_sound_cues(canvas);
+        }
+        self.draw_laser_meter(canvas);
+        if self.config.ball_light {
+            self.draw_ball_light(canvas, frame);
+        }
+
+        if let Some(rumble) = &self.rumble {
+            // Alternate the offset direction every couple of ticks while
+            // the amplitude dies out
+            let amplitude = rumble.value().round() as i32;
+            let direction = if rumble.elapsed() & 2 == 0 { 1 } else { -1 };
+            shake_canvas(
+                canvas,
+                direction * amplitude,
+                amplitude / 2,
+                self.background_color,
+            );
+        }
+
+        // Drawn after the shake, so the zoom holds still
+        if matches!(self.scene, Scene::Replay(_)) && self.config.replay_zoom {
+            self.draw_replay_zoom(canvas, frame);
+        }
+
+        if self.font.is_some() {
+            self.draw_hud(canvas);
+        }
+
+        if let Scene::Intro(intro) = &self.scene {
+            self.draw_intro(canvas, intro.value());
+        }
+
+        if self.autopilot.is_some() {
+            self.draw_banner(canvas, "DEMO  press any key");
+        } else if let Scene::Replay(_) = &self.scene {
+            self.draw_banner(canvas, "REPLAY  [Enter] skip");
+        }
+
+        if let Scene::LevelComplete(complete) = &self.scene {
+            dim_rect(canvas, canvas.bounds());
+            self.draw_level_complete(canvas, complete);
+        }
+
+        if let Scene::Paused(menu) = &self.scene {
+            dim_rect(canvas, canvas.bounds());
+            let drop = self.panel_drop.as_ref().map_or(1.0, Tween::value);
+            menu.draw(canvas, self.font.as_deref(), self.focus(), drop);
+        }
+
+        if self.config.show_hitboxes {
+            self.draw_hitboxes(canvas, frame);
+        }
+        if self.debug_stats && self.font.is_some() {
+            self.draw_debug_stats(canvas);
+        }
+    }
+
+    /// Outlines the paddles and capsules as drawn and as they collide
+    fn draw_hitboxes(&self, canvas: &mut Canvas, frame: &RenderFrame) {
+        let viewport = self.viewport;
+        let mut outline = |pos: WorldPos, size: WorldVec, color: u32| {
+            // A capsule's hitbox can reach past the top of the screen
+            let ScreenPos { x, y } = viewport.to_screen(WorldPos::new(pos.x, pos.y.min(1.0)));
+            let (width, height) = viewport.to_pixels(size);
+            draw_outline(canvas, x, y, width, height, color);
+        };
+        for paddle in self.paddle_rects() {
+            outline(paddle.pos, paddle.size, GREEN);
+        }
+        for paddle in self.paddle_hitboxes() {
+            outline(paddle.pos, paddle.size, MAGENTA);
+        }
+        for capsule in &frame.capsules {
+            outline(
+                capsule.pos,
+                WorldVec::new(CAPSULE_WIDTH, CAPSULE_HEIGHT),
+                GREEN,
+            );
+            let (pos, size) = self.capsule_hitbox(capsule);
+            outline(pos, size, MAGENTA);
+        }
     }
 }
 
 impl Default for GameState {
     fn default() -> Self {
+        let levels: Vec<&'static Level> = LEVELS.iter().collect();
         GameState {
+            scene: Scene::LevelSelect(LevelSelect { cursor: 0 }),
             font: None,
             debug_stats: true,
             debug_stats_height: 16.0,
-            ball_pos_x: 0.0,
-            ball_pos_y: 0.0,
-            ball_vel_x: 0.0039,
-            ball_vel_y: 0.0024,
-            ball_diameter: 0.032,
+            balls: vec![Ball::serve(BALL_SERVE_SPEED, std::f32::consts::FRAC_PI_4)],
+            ball_diameter: BALL_DIAMETER,
             ball_color: MAGENTA,
             background_color: CYAN,
-            paddle_pos_x: -0.04,
-            paddle_pos_y: -0.8,
-            paddle_width: 0.2,
+            paddle_pos: WorldPos::new(-0.04, -0.8),
+            paddle_levels: vec![0.0],
+            paddle_width: PADDLE_WIDTH,
             paddle_height: 0.02,
             paddle_vel_x: 0.0,
-            paddle_movement_speed: 0.022,
+            paddle_moved_x: 0.0,
+            auto_move: AutoMove::default(),
+            paddle_movement_speed: PADDLE_MOVEMENT_SPEED,
             paddle_color: YELLOW,
-            bricks: Bricks::new(),
+            timed_effects: TimedEffects::default(),
+            laser_gun: LaserGun::default(),
+            laser_bolts: Vec::new(),
+            capsules: Vec::new(),
+            hazards: Vec::new(),
+            barrier: None,
+            boss: None,
+            boss_defeated: false,
+            top_exit_paid: false,
+            bricks: Bricks::new(&LEVELS[0], &LEVELS[0].field),
+            field: LEVELS[0].field,
+            clock: GameClock::default(),
+            level_events: default_level_events(),
+            rumble: None,
+            paddle_squash: None,
+            panel_drop: None,
+            focus_change: None,
+            confirm_quit: false,
+            quit: false,
+            lives: STARTING_LIVES,
+            bonus_letters: Vec::new(),
+            levels_cleared: 0,
+            waves: 0,
+            speed_ramp: 1.0,
+            speed_ramp_hits: 0,
+            score: 0,
+            score_bank: ScoreBank::default(),
+            chain_stats: ChainStats::default(),
+            chain_banner: None,
+            message: None,
+            toast: None,
+            tricks: TrickDetector::default(),
+            music_intensity: 0.0,
+            events: Vec::new(),
+            event_positions: Vec::new(),
+            sound_cues: Vec::new(),
+            tumbling_bricks: Vec::new(),
+            viewport: Viewport {
+                width: WIDTH,
+                height: HEIGHT - HUD_STRIP_HEIGHT,
+                top: HUD_STRIP_HEIGHT,
+            },
+            level_thumbnails: level_select::thumbnails(&levels),
+            levels,
+            playlists: Vec::new(),
+            level_index: 0,
+            level_start_score: 0,
+            progress: Progress::default(),
+            stats: Stats::default(),
+            daily: None,
+            run_mode: RunMode::Campaign,
+            autopilot: None,
+            idle_ticks: 0,
+            cheat_codes: CheatCodes::default(),
+            hitboxes: config::Difficulty::Normal.hitboxes(),
+            cheated: false,
+            modifiers: Vec::new(),
+            modifier_effects: ModifierEffects::default(),
+            rng: Rng::from_time(),
+            leaderboards: Leaderboards::default(),
+            config: Config::default(),
+            audio: Audio::default(),
+            gamepads: None,
+            settings: Settings::default(),
+            paths: Paths::default(),
+            recorder: Recorder::default(),
         }
     }
 }
 
 pub fn main() -> Res<()> {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    match args.first().map(String::as_str) {
+        Some("verify-determinism") => return determinism::run_cli(&args[1..]),
+        Some("soak-test") => return soak::run_cli(&args[1..]),
+        Some("bench-light") => return bench::run_cli(&args[1..]),
+        Some("bench-rotation") => return bench::run_rotation_cli(&args[1..]),
+        Some("latency-test") => return latency::run_cli(&args[1..]),
+        Some("record-demo") => return demo::run_cli(&args[1..]),
+        _ => (),
+    }
+    // Forgets where the window was, for when it was left somewhere it
+    // can't be seen, like on a monitor that has since been unplugged
+    let reset_window = args.iter().any(|arg| arg == RESET_WINDOW_FLAG);
+    let paths = Paths::from_args(args.into_iter().filter(|arg| arg != RESET_WINDOW_FLAG))?;
     let mut canvas = Canvas {
         buffer: vec![0; WIDTH * HEIGHT],
         stride: WIDTH,
     };
 
-    let font_path = "fonts/RobotoMono/RobotoMono-VariableFont_wght.ttf";
-    let font = {
-        let font_path = std::env::current_dir().unwrap().join(font_path);
-        let data = std::fs::read(&font_path).unwrap();
-        Font::try_from_vec(data).unwrap_or_else(|| {
-            panic!("error constructing a Font from data at {:?}", font_path);
-        })
-    };
+    let mut window = create_window()?;
 
+    let Some(loaded) = load_with_splash(&mut window, &mut canvas, &paths)? else {
+        return Ok(());
+    };
     let mut game_state = GameState {
-        font: Some(font),
+        font: Some(loaded.font),
         debug_stats: true,
+        progress: loaded.progress,
+        stats: loaded.stats,
+        leaderboards: loaded.leaderboards,
+        audio: Audio::start(
+            audio::open_backend(),
+            loaded.sounds,
+            loaded.settings.volumes,
+        ),
+        gamepads: Gamepads::new(),
+        settings: loaded.settings,
+        config: loaded.config,
+        paths,
+        viewport: canvas.viewport(),
         ..GameState::default()
     };
+    game_state.levels.extend(loaded.levels);
+    game_state.level_thumbnails = level_select::thumbnails(&game_state.levels);
+    game_state.playlists = loaded.playlists;
+    if loaded.first_run {
+        game_state.scene = Scene::Setup(SetupWizard::new(&game_state.config));
+    }
+    if reset_window {
+        game_state.config.window_position = None;
+    } else if let Some((x, y)) = game_state.config.window_position {
+        window.set_position(x, y);
+    }
+    let saved_position = game_state.config.window_position;
+    game_state.apply_theme();
+    if let Some(run) = SavedRun::take(&game_state.paths.run()) {
+        game_state.resume_run(run);
+    }
+    let result = game_loop(&mut window, &mut game_state, &mut canvas);
+    // A run quit halfway through has counted stats that aren't saved yet
+    game_state.save_stats();
+
+    // Before the first run's setup is finished there is no config file, and
+    // writing one would skip the setup next time
+    let path = game_state.paths.config();
+    let position = game_state.config.window_position;
+    if position != saved_position && path.exists() {
+        if let Err(err) = Config::save_window_position(&path, position) {
+            eprintln!("ERROR! Could not save the window position to {path:?}: {err}");
+        }
+    }
+    result
+}
 
-    let mut window = Window::new(
-        "BREAKRS - ESC to exit",
-        WIDTH,
-        HEIGHT,
-        WindowOptions::default(),
-    )
-    .map_err(|err| {
-        eprintln!("ERROR! Could not create window: {err}");
-    })?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use physics::WallGap;
 
-    // Limit to max ~60 fps update rate
-    window.limit_update_rate(Some(std::time::Duration::from_micros(16600)));
-    game_loop(&mut window, &mut game_state, &mut canvas)?;
-    Ok(())
+    const FLOOR: f32 = -0.8;
+
+    /// A state with nothing in play but one ball at `pos` moving at `vel`
+    fn lone_ball(field: PlayField, pos: WorldPos, vel: WorldVec) -> GameState {
+        let mut state = GameState {
+            field,
+            ..GameState::default()
+        };
+        state.bricks.is_destroyed.fill(true);
+        state.paddle_levels.clear();
+        state.balls = vec![Ball {
+            pos,
+            vel,
+            ..Ball::serve(0.0, 0.0)
+        }];
+        state
+    }
+
+    /// Plays the lone ball on until it comes down through `floor`, and
+    /// where it crossed, measured the way predictions measure it
+    fn simulated_landing_x(state: &mut GameState, floor: f32) -> f32 {
+        for _ in 0..seconds(30) {
+            let before = state.balls[0].pos;
+            assert!(state.update_ball_pos(0), "ball was lost");
+            let after = state.balls[0].pos;
+            if after.y < before.y && after.y <= floor {
+                let t = (before.y - floor) / (before.y - after.y);
+                return before.x + (after.x - before.x) * t;
+            }
+        }
+        panic!("ball never came down");
+    }
+
+    fn predicted_landing_x(state: &GameState, floor: f32) -> Option<f32> {
+        let ball = state.balls[0];
+        let walls = state.field.walls(state.ball_size(), floor);
+        predict_landing_x(
+            ball.pos,
+            ball.vel,
+            state.gravity(),
+            state.falling_speed_cap(),
+            walls,
+            10,
+        )
+    }
+
+    fn assert_prediction_matches(field: PlayField, pos: WorldPos, vel: WorldVec) {
+        assert_state_prediction_matches(lone_ball(field, pos, vel));
+    }
+
+    fn assert_state_prediction_matches(mut state: GameState) {
+        let predicted = predicted_landing_x(&state, FLOOR).expect("no prediction");
+        let landed = simulated_landing_x(&mut state, FLOOR);
+        assert!(
+            (predicted - landed).abs() < 1e-4,
+            "predicted {predicted}, landed at {landed}"
+        );
+    }
+
+    /// A state with one paddle that meets balls exactly at its edges
+    fn exact_paddle() -> GameState {
+        let mut state = GameState::default();
+        state.hitboxes.paddle_reach = 0.0;
+        state.hitboxes.save_grace_ms = 0.0;
+        state
+    }
+
+    fn falling_ball_at(pos: WorldPos, vel: WorldVec) -> Ball {
+        Ball {
+            pos,
+            vel,
+            ..Ball::serve(0.0, 0.0)
+        }
+    }
+
+    #[test]
+    fn a_fast_paddle_sweeps_over_the_ball() {
+        let mut state = exact_paddle();
+        let size = state.ball_size();
+        let width = state.paddle_width;
+        let from = state.field.left + 0.05;
+        // Between where the paddle starts and ends the tick, clear of both
+        let ball = falling_ball_at(
+            WorldPos::new(from + width + size.x / 2.0, state.paddle_pos.y + size.y),
+            WorldVec::new(0.0, -0.001),
+        );
+        state.paddle_pos.x = from + 2.0 * (width + size.x);
+        assert!(state.paddle_collision(&ball).is_none());
+        state.paddle_moved_x = 2.0 * (width + size.x);
+        assert!(state.paddle_collision(&ball).is_some());
+    }
+
+    #[test]
+    fn a_fast_ball_cannot_pass_through_the_paddle() {
+        let state = exact_paddle();
+        let size = state.ball_size();
+        let paddle = state.paddle_pos;
+        // Above the paddle as the tick starts, below it as the tick ends
+        let ball = falling_ball_at(
+            WorldPos::new(
+                paddle.x + (state.paddle_width - size.x) / 2.0,
+                paddle.y + size.y + 0.05,
+            ),
+            WorldVec::new(0.0, -(0.1 + size.y + state.paddle_height)),
+        );
+        let contact = state.paddle_collision(&ball).expect("ball went through");
+        assert!((contact.location - 0.5).abs() < 1e-5);
+        assert!(!contact.late);
+    }
+
+    #[test]
+    fn a_pinhead_ball_bounces_off_the_paddle() {
+        let mut state = exact_paddle();
+        state.bricks.is_destroyed.fill(true);
+        state.ball_diameter *= modifiers::MODIFIERS
+            .iter()
+            .find(|modifier| modifier.name == "Pinhead")
+            .unwrap()
+            .effects
+            .ball_diameter;
+        let size = state.ball_size();
+        let paddle = state.paddle_pos;
+        // Falls further in a tick than the ball and paddle are tall
+        let fall = 2.0 * (size.y + state.paddle_height);
+        state.balls = vec![falling_ball_at(
+            WorldPos::new(
+                paddle.x + (state.paddle_width - size.x) / 2.0,
+                paddle.y + size.y + fall / 2.0,
+            ),
+            WorldVec::new(0.0, -fall),
+        )];
+        assert!(state.update_ball_pos(0));
+        assert!(state.balls[0].vel.y > 0.0, "ball went through the paddle");
+    }
+
+    #[test]
+    fn balls_touching_the_paddle_ends_are_hit() {
+        let state = exact_paddle();
+        let size = state.ball_size();
+        let paddle = state.paddle_pos;
+        let vel = WorldVec::new(0.0, -0.01);
+        let y = paddle.y + size.y;
+        let at = |x| state.paddle_collision(&falling_ball_at(WorldPos::new(x, y), vel));
+
+        let left = at(paddle.x - size.x).expect("missed the left end");
+        assert!(left.location.abs() < 1e-5);
+        let right = at(paddle.x + state.paddle_width).expect("missed the right end");
+        assert!((right.location - 1.0).abs() < 1e-5);
+        assert!(at(paddle.x - size.x - 0.001).is_none());
+        assert!(at(paddle.x + state.paddle_width + 0.001).is_none());
+    }
+
+    /// A paddle that has just reached a ball which fell past it a tick ago
+    fn late_paddle(difficulty: config::Difficulty) -> GameState {
+        let mut state = GameState {
+            hitboxes: difficulty.hitboxes(),
+            ..GameState::default()
+        };
+        state.bricks.is_destroyed.fill(true);
+        let size = state.ball_size();
+        let fall = 0.01;
+        let paddle = state.paddle_pos;
+        // The ball's top is a tick's fall below the paddle's bottom, and
+        // the paddle only now moved over it
+        let x = paddle.x + (state.paddle_width - size.x) / 2.0;
+        let y = paddle.y - state.paddle_height - fall;
+        state.paddle_moved_x = state.paddle_width + size.x + 0.01;
+        state.balls = vec![falling_ball_at(
+            WorldPos::new(x, y),
+            WorldVec::new(0.0, -fall),
+        )];
+        state
+    }
+
+    #[test]
+    fn easy_saves_a_ball_a_tick_past_the_paddle() {
+        let mut state = late_paddle(config::Difficulty::Easy);
+        let contact = state.paddle_collision(&state.balls[0]).expect("not saved");
+        assert!(contact.late);
+        assert!(state.update_ball_pos(0));
+        assert!(state.balls[0].vel.y > 0.0, "ball kept falling");
+    }
+
+    #[test]
+    fn hard_loses_a_ball_a_tick_past_the_paddle() {
+        let mut state = late_paddle(config::Difficulty::Hard);
+        assert!(state.paddle_collision(&state.balls[0]).is_none());
+        state.paddle_moved_x = 0.0;
+        let lost = (0..seconds(2)).any(|_| !state.update_ball_pos(0));
+        assert!(lost, "ball was saved");
+    }
+
+    #[test]
+    fn a_held_ball_stays_on_its_paddle_when_another_expires() {
+        let mut state = GameState {
+            paddle_levels: vec![0.0, ELEVATOR_PADDLE_SPACING],
+            ..GameState::default()
+        };
+        state.timed_effects.start(TimedEffect::SecondPaddle, 1);
+        let upper = PaddleId {
+            level: 1,
+            mirrored: false,
+        };
+        let hold = Hold {
+            paddle: upper,
+            location: 0.5,
+            release: None,
+        };
+        let before = state.held_ball_pos(hold).unwrap();
+        state.timed_effects.update();
+        // The mirrored paddles are gone, and with them the old position of
+        // the upper paddle in the list
+        assert_eq!(state.paddle_rects().len(), 2);
+        let after = state.held_ball_pos(hold).expect("ball dropped");
+        assert_eq!((after.x, after.y), (before.x, before.y));
+    }
+
+    #[test]
+    fn the_mirrored_paddle_reports_how_far_it_really_moved() {
+        let mut state = GameState::default();
+        state.timed_effects.start(TimedEffect::SecondPaddle, 1000);
+        // Far enough to run the paddle into the right wall part way through
+        state.paddle_vel_x = 0.4;
+        for _ in 0..10 {
+            let before = state.paddle_rects()[1].pos.x;
+            state.update_paddle_pos();
+            let mirrored = state.paddle_rects()[1];
+            assert!((mirrored.moved_x - (mirrored.pos.x - before)).abs() < 1e-6);
+        }
+        assert_eq!(state.paddle_moved_x, 0.0);
+    }
+
+    #[test]
+    fn endless_rows_do_not_pile_up() {
+        let mut bricks = GameState::default().bricks;
+        for _ in 0..100 {
+            bricks.is_destroyed.fill(true);
+            bricks.add_top_row(0, 0, (0, PowerUp::Laser), None);
+        }
+        // Only the new row is left
+        assert_eq!(bricks.is_destroyed.len(), bricks.columns);
+        assert_eq!(bricks.y_positions.len(), bricks.columns);
+        assert!(bricks.is_destroyed.iter().all(|&destroyed| !destroyed));
+    }
+
+    #[test]
+    fn predicts_a_straight_fall() {
+        let pos = WorldPos::new(0.0, 0.5);
+        assert_prediction_matches(PlayField::FULL, pos, WorldVec::new(0.003, -0.01));
+    }
+
+    #[test]
+    fn predicts_a_side_wall_bounce() {
+        let pos = WorldPos::new(0.7, 0.5);
+        assert_prediction_matches(PlayField::FULL, pos, WorldVec::new(0.02, -0.01));
+    }
+
+    #[test]
+    fn predicts_a_top_wall_bounce() {
+        let pos = WorldPos::new(-0.3, 0.5);
+        assert_prediction_matches(PlayField::FULL, pos, WorldVec::new(0.004, 0.012));
+    }
+
+    /// A lone ball in gravity mode
+    fn falling_ball(pos: WorldPos, vel: WorldVec) -> GameState {
+        let mut state = lone_ball(PlayField::FULL, pos, vel);
+        state.config.gravity = true;
+        state
+    }
+
+    #[test]
+    fn predicts_an_arc_under_gravity() {
+        let speed = GameState::default().nominal_ball_speed();
+        let vel = WorldVec::new(speed * 0.3, speed * 0.9);
+        let state = falling_ball(WorldPos::new(-0.6, -0.5), vel);
+        assert!(state.gravity() > 0.0);
+        assert_state_prediction_matches(state);
+    }
+
+    #[test]
+    fn predicts_a_fall_held_to_the_speed_cap() {
+        let speed = GameState::default().nominal_ball_speed();
+        // Thrown down fast enough that it soon reaches its top speed
+        let vel = WorldVec::new(speed * 0.5, -speed * 1.9);
+        let pos = WorldPos::new(0.2, 0.9);
+        let mut state = falling_ball(pos, vel);
+        simulated_landing_x(&mut state, FLOOR);
+        let ball = state.balls[0];
+        let cap = state.falling_speed_cap();
+        assert!((magnitude(ball.vel.x, ball.vel.y) - cap).abs() < 1e-6);
+        assert_state_prediction_matches(falling_ball(pos, vel));
+    }
+
+    #[test]
+    fn predicts_a_ball_moving_through_a_wrap_gap() {
+        const GAPS: &[WallGap] = &[WallGap {
+            side: Side::Right,
+            bottom: -0.5,
+            top: 0.6,
+            kind: GapKind::Wrap,
+        }];
+        let field = PlayField::FULL.with_gaps(GAPS);
+        let pos = WorldPos::new(0.85, -0.3);
+        let vel = WorldVec::new(0.03, -0.03);
+        let mut state = lone_ball(field, pos, vel);
+        let landed = simulated_landing_x(&mut state, FLOOR);
+        // It came back in on the left rather than bouncing
+        assert!(landed < 0.0);
+        assert_prediction_matches(field, pos, vel);
+    }
+
+    #[test]
+    fn predicts_nothing_through_an_exit_gap() {
+        const GAPS: &[WallGap] = &[WallGap {
+            side: Side::Right,
+            bottom: -0.5,
+            top: 0.6,
+            kind: GapKind::Exit,
+        }];
+        let field = PlayField::FULL.with_gaps(GAPS);
+        let state = lone_ball(field, WorldPos::new(0.7, 0.5), WorldVec::new(0.02, -0.01));
+        assert_eq!(predicted_landing_x(&state, FLOOR), None);
+    }
+
+    #[test]
+    fn cheated_runs_add_nothing_to_the_stats() {
+        for cheated in [false, true] {
+            let mut state = GameState {
+                cheated,
+                ..GameState::default()
+            };
+            let event = GameEvent::BrickDestroyed(Cause::Ball, 10, Material::Plain);
+            state.emit(event, WorldPos::new(0.0, 0.0));
+            state.process_events();
+            assert_eq!(state.stats.bricks_broken, u64::from(!cheated));
+        }
+    }
+
+    #[test]
+    fn leaving_the_stats_screen_keeps_the_level_select_cursor() {
+        let mut state = GameState {
+            scene: Scene::LevelSelect(LevelSelect { cursor: 2 }),
+            ..GameState::default()
+        };
+        state.key_pressed(Key::I);
+        assert!(matches!(state.scene, Scene::Stats(_)));
+        state.key_pressed(Key::Backspace);
+        assert!(matches!(
+            state.scene,
+            Scene::LevelSelect(LevelSelect { cursor: 2 })
+        ));
+    }
+
+    #[test]
+    fn campaign_bricks_stop_dropping_above_the_paddle() {
+        let mut state = GameState {
+            scene: Scene::Playing,
+            ..GameState::default()
+        };
+        let dropped = |state: &GameState| state.lowest_brick_bottom().unwrap();
+
+        let before = dropped(&state);
+        state.run_level_event(LevelEventKind::BrickDrop);
+        assert!((dropped(&state) - (before - state.bricks.row_pitch)).abs() < 1e-6);
+
+        // Half a row above the floor there's no room for another drop
+        let gap = dropped(&state) - state.brick_floor() - state.bricks.row_pitch / 2.0;
+        for y in state.bricks.y_positions.iter_mut() {
+            *y -= gap;
+        }
+        let before = dropped(&state);
+        state.run_level_event(LevelEventKind::BrickDrop);
+        assert_eq!(dropped(&state), before);
+        assert!(matches!(state.scene, Scene::Playing));
+    }
+
+    #[test]
+    fn a_resumed_run_picks_up_where_it_was_saved() {
+        let mut state = GameState::default();
+        state.start_level(1);
+        state.score = 1_500;
+        state.lives = 1;
+        state.bricks.is_destroyed[0] = true;
+        state.bricks.is_destroyed[3] = true;
+        state.bricks.hit_points[5] = 1;
+        state.run_level_event(LevelEventKind::BrickDrop);
+        let saved = state.saved_run();
+
+        let mut resumed = GameState::default();
+        resumed.resume_run(saved);
+        assert_eq!(resumed.level_index, 1);
+        assert_eq!(resumed.score, 1_500);
+        assert_eq!(resumed.lives, 1);
+        assert_eq!(resumed.bricks.is_destroyed, state.bricks.is_destroyed);
+        assert_eq!(resumed.bricks.hit_points[5], 1);
+        for (resumed, saved) in resumed
+            .bricks
+            .y_positions
+            .iter()
+            .zip(&state.bricks.y_positions)
+        {
+            assert!((resumed - saved).abs() < 1e-6);
+        }
+        assert!(matches!(resumed.scene, Scene::Paused(_)));
+    }
+
+    #[test]
+    fn shaking_moves_only_the_play_field() {
+        // Each pixel is numbered by where it started, from 1
+        let (width, height) = (4, HUD_STRIP_HEIGHT + 3);
+        let mut canvas = Canvas {
+            buffer: (1..=(width * height) as u32).collect(),
+            stride: width,
+        };
+        let start = canvas.buffer.clone();
+        shake_canvas(&mut canvas, 1, 1, 0);
+
+        let field = HUD_STRIP_HEIGHT * width;
+        assert_eq!(canvas.buffer[..field], start[..field]);
+        let at = |x: usize, y: usize| start[field + y * width + x];
+        assert_eq!(canvas.buffer[field..field + width], [0; 4]);
+        assert_eq!(
+            canvas.buffer[field + width..],
+            [
+                0,
+                at(0, 0),
+                at(1, 0),
+                at(2, 0),
+                0,
+                at(0, 1),
+                at(1, 1),
+                at(2, 1)
+            ]
+        );
+
+        shake_canvas(&mut canvas, -1, -1, 0);
+        assert_eq!(canvas.buffer[..field], start[..field]);
+        assert_eq!(
+            canvas.buffer[field..],
+            [
+                at(0, 0),
+                at(1, 0),
+                at(2, 0),
+                0,
+                at(0, 1),
+                at(1, 1),
+                at(2, 1),
+                0,
+                0,
+                0,
+                0,
+                0
+            ]
+        );
+    }
 }
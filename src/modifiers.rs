@@ -0,0 +1,132 @@
+use crate::rng::Rng;
+
+/// Multiplicative adjustments to the base game settings. Effects stack by
+/// combining, so a run's modifiers fold down into a single set of effects.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ModifierEffects {
+    pub paddle_width: f32,
+    pub paddle_speed: f32,
+    pub ball_speed: f32,
+    pub ball_diameter: f32,
+    pub points: f32,
+    pub laser_ammo: u32,
+}
+
+impl Default for ModifierEffects {
+    fn default() -> Self {
+        NEUTRAL
+    }
+}
+
+impl ModifierEffects {
+    pub fn combine(self, other: ModifierEffects) -> ModifierEffects {
+        ModifierEffects {
+            paddle_width: self.paddle_width * other.paddle_width,
+            paddle_speed: self.paddle_speed * other.paddle_speed,
+            ball_speed: self.ball_speed * other.ball_speed,
+            ball_diameter: self.ball_diameter * other.ball_diameter,
+            points: self.points * other.points,
+            laser_ammo: self.laser_ammo + other.laser_ammo,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Modifier {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub effects: ModifierEffects,
+}
+
+pub const NEUTRAL: ModifierEffects = ModifierEffects {
+    paddle_width: 1.0,
+    paddle_speed: 1.0,
+    ball_speed: 1.0,
+    ball_diameter: 1.0,
+    points: 1.0,
+    laser_ammo: 0,
+};
+
+pub const MODIFIERS: &[Modifier] = &[
+    Modifier {
+        name: "Wide Load",
+        description: "bigger paddle, faster ball",
+        effects: ModifierEffects {
+            paddle_width: 1.5,
+            ball_speed: 1.25,
+            ..NEUTRAL
+        },
+    },
+    Modifier {
+        name: "High Stakes",
+        description: "double points, smaller paddle",
+        effects: ModifierEffects {
+            points: 2.0,
+            paddle_width: 0.7,
+            ..NEUTRAL
+        },
+    },
+    Modifier {
+        name: "Molasses",
+        description: "slower ball, fewer points",
+        effects: ModifierEffects {
+            ball_speed: 0.8,
+            points: 0.75,
+            ..NEUTRAL
+        },
+    },
+    Modifier {
+        name: "Quick Hands",
+        description: "faster paddle, faster ball",
+        effects: ModifierEffects {
+            paddle_speed: 1.5,
+            ball_speed: 1.1,
+            ..NEUTRAL
+        },
+    },
+    Modifier {
+        name: "Beach Ball",
+        description: "bigger ball, fewer points",
+        effects: ModifierEffects {
+            ball_diameter: 1.5,
+            points: 0.8,
+            ..NEUTRAL
+        },
+    },
+    Modifier {
+        name: "Pinhead",
+        description: "half size ball, more points",
+        effects: ModifierEffects {
+            ball_diameter: 0.5,
+            points: 1.5,
+            ..NEUTRAL
+        },
+    },
+    Modifier {
+        name: "Armory",
+        description: "laser ammo each level, smaller paddle",
+        effects: ModifierEffects {
+            laser_ammo: 12,
+            paddle_width: 0.85,
+            ..NEUTRAL
+        },
+    },
+];
+
+pub fn combined_effects(modifiers: &[Modifier]) -> ModifierEffects {
+    modifiers
+        .iter()
+        .fold(ModifierEffects::default(), |effects, modifier| {
+            effects.combine(modifier.effects)
+        })
+}
+
+/// Picks three distinct modifiers to offer the player
+pub fn roll_choices(rng: &mut Rng) -> [Modifier; 3] {
+    let mut pool = MODIFIERS.to_vec();
+    let mut choices = [MODIFIERS[0]; 3];
+    for choice in choices.iter_mut() {
+        *choice = pool.swap_remove(rng.below(pool.len()));
+    }
+    choices
+}
@@ -0,0 +1,133 @@
+//! Where the game keeps its files.
+//!
+//! Config and settings go in the platform's config directory and saved data
+//! in its data directory (on Linux `~/.config/breakrs` and
+//! `~/.local/share/breakrs`), so the game finds them wherever it is started
+//! from. `--data-dir <dir>` keeps everything in `<dir>` instead.
+//!
+//! The files the game ships with, its fonts and assets, are looked up next
+//! to the executable, or in the nearest directory above it that has an
+//! `assets` directory, which finds them in the project both when the game
+//! is installed and when it is run from a build directory.
+
+use crate::Res;
+use directories::ProjectDirs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+const DATA_DIR_FLAG: &str = "--data-dir";
+
+#[derive(Clone, Debug, Default)]
+pub struct Paths {
+    config_dir: PathBuf,
+    data_dir: PathBuf,
+}
+
+impl Paths {
+    /// Reads `--data-dir` from the command line arguments (without the
+    /// program name)
+    pub fn from_args(mut args: impl Iterator<Item = String>) -> Res<Paths> {
+        let mut data_dir = None;
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                DATA_DIR_FLAG => match args.next() {
+                    Some(dir) => data_dir = Some(PathBuf::from(dir)),
+                    None => {
+                        eprintln!("ERROR! {DATA_DIR_FLAG} needs a directory");
+                        return Err(());
+                    }
+                },
+                _ => {
+                    eprintln!(
+                        "ERROR! Unknown argument `{arg}`. Usage: breakrs [{DATA_DIR_FLAG} <dir>] [--reset-window]"
+                    );
+                    return Err(());
+                }
+            }
+        }
+        Ok(Paths::new(data_dir))
+    }
+
+    fn new(data_dir: Option<PathBuf>) -> Paths {
+        let paths = match (data_dir, ProjectDirs::from("", "", "breakrs")) {
+            (Some(dir), _) => Paths {
+                config_dir: dir.clone(),
+                data_dir: dir,
+            },
+            (None, Some(dirs)) => Paths {
+                config_dir: dirs.config_dir().to_path_buf(),
+                data_dir: dirs.data_dir().to_path_buf(),
+            },
+            (None, None) => {
+                eprintln!(
+                    "ERROR! Could not find the home directory, using the working directory instead"
+                );
+                Paths {
+                    config_dir: PathBuf::from("."),
+                    data_dir: PathBuf::from("."),
+                }
+            }
+        };
+        // Saving fails later if the directories are missing, so there's no
+        // need to give up here
+        for dir in [&paths.config_dir, &paths.data_dir] {
+            if let Err(err) = std::fs::create_dir_all(dir) {
+                eprintln!("ERROR! Could not create {dir:?}: {err}");
+            }
+        }
+        paths
+    }
+
+    pub fn config(&self) -> PathBuf {
+        self.config_dir.join("config.txt")
+    }
+
+    pub fn settings(&self) -> PathBuf {
+        self.config_dir.join("settings.txt")
+    }
+
+    pub fn progress(&self) -> PathBuf {
+        self.data_dir.join("progress.txt")
+    }
+
+    pub fn leaderboards(&self) -> PathBuf {
+        self.data_dir.join("challenges.txt")
+    }
+
+    pub fn stats(&self) -> PathBuf {
+        self.data_dir.join("stats.txt")
+    }
+
+    /// Where a run cut short by a window failure is kept until the next
+    /// launch
+    pub fn run(&self) -> PathBuf {
+        self.data_dir.join("run.txt")
+    }
+
+    /// Where level files are read from
+    pub fn levels(&self) -> PathBuf {
+        self.data_dir.join("levels")
+    }
+
+    /// Where the player's own challenge files are read from
+    pub fn challenges(&self) -> PathBuf {
+        self.data_dir.join("challenges")
+    }
+}
+
+/// Where a file the game ships with is, from its path in the project
+pub fn resource(path: impl AsRef<Path>) -> PathBuf {
+    static ROOT: OnceLock<PathBuf> = OnceLock::new();
+    let root = ROOT.get_or_init(|| {
+        let exe = std::env::current_exe().and_then(|exe| exe.canonicalize());
+        let found = exe.ok().and_then(|exe| {
+            exe.ancestors()
+                .skip(1)
+                .find(|dir| dir.join("assets").is_dir())
+                .map(Path::to_path_buf)
+        });
+        // Run some other way, fall back on the working directory
+        found.unwrap_or_else(|| PathBuf::from("."))
+    });
+    root.join(path)
+}
@@ -0,0 +1,91 @@
+use crate::{
+    compute_multiline_text_data, draw_focus_outline, draw_panel, draw_subcanvas, Canvas,
+    PANEL_BORDER, PANEL_PADDING,
+};
+use minifb::Key;
+use rusttype::Font;
+
+const TEXT_HEIGHT: f32 = 20.0;
+const ROW_GAP: usize = 12;
+
+const ITEMS: [(&str, PauseAction); 4] = [
+    ("Resume", PauseAction::Resume),
+    ("Restart Level", PauseAction::Restart),
+    ("Settings", PauseAction::OpenSettings),
+    ("Quit to Menu", PauseAction::Quit),
+];
+
+#[derive(Clone, Copy)]
+pub enum PauseAction {
+    None,
+    Resume,
+    Restart,
+    OpenSettings,
+    Quit,
+}
+
+pub struct PauseMenu {
+    pub cursor: usize,
+}
+
+impl PauseMenu {
+    /// The menu opened on the settings item, for coming back from settings
+    pub fn at_settings() -> PauseMenu {
+        PauseMenu { cursor: 2 }
+    }
+
+    pub fn key_pressed(&mut self, key: Key) -> PauseAction {
+        match key {
+            Key::Up | Key::W => self.cursor = self.cursor.saturating_sub(1),
+            Key::Down | Key::S => self.cursor = (self.cursor + 1).min(ITEMS.len() - 1),
+            Key::Enter | Key::Space => return ITEMS[self.cursor].1,
+            Key::P | Key::Backspace => return PauseAction::Resume,
+            _ => (),
+        }
+        PauseAction::None
+    }
+
+    /// Draws the menu in a panel that has dropped `drop` of the way from the
+    /// top of the screen to the middle
+    pub fn draw(&self, canvas: &mut Canvas, font: Option<&Font>, focus: f32, drop: f32) {
+        let Some(font) = font else {
+            return;
+        };
+        let rows = ITEMS
+            .iter()
+            .map(|(label, _)| compute_multiline_text_data(font, TEXT_HEIGHT, &[label]))
+            .collect::<Vec<_>>();
+        let title = compute_multiline_text_data(font, TEXT_HEIGHT * 1.5, &["PAUSED"]);
+        let width = rows
+            .iter()
+            .map(Canvas::width)
+            .chain([title.width()])
+            .max()
+            .unwrap_or(0);
+        let height =
+            title.height() + ROW_GAP + rows.iter().map(|row| row.height() + ROW_GAP).sum::<usize>();
+
+        let x = canvas.width().saturating_sub(width) / 2;
+        let rest_y = canvas.height().saturating_sub(height) / 2;
+        let mut y = (drop * rest_y as f32) as usize;
+        draw_panel(canvas, x, y, width, height);
+        draw_subcanvas(canvas, &title, x + (width - title.width()) / 2, y);
+        y += title.height() + ROW_GAP;
+        for (index, row) in rows.iter().enumerate() {
+            let row_x = x + (width - row.width()) / 2;
+            if index == self.cursor {
+                draw_focus_outline(
+                    canvas,
+                    row_x.saturating_sub(PANEL_PADDING),
+                    y.saturating_sub(PANEL_BORDER),
+                    row.width() + 2 * PANEL_PADDING,
+                    row.height() + 2 * PANEL_BORDER,
+                    PANEL_BORDER,
+                    focus,
+                );
+            }
+            draw_subcanvas(canvas, row, row_x, y);
+            y += row.height() + ROW_GAP;
+        }
+    }
+}
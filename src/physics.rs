@@ -0,0 +1,325 @@
+use crate::clock::seconds;
+use crate::magnitude;
+use crate::units::{WorldPos, WorldVec};
+use constants::{FIELD_BOTTOM, FIELD_LEFT, FIELD_RIGHT, FIELD_TOP};
+
+pub mod constants;
+
+// Predictions give up on a ball that takes longer than this to come down
+const MAX_PREDICTION_TICKS: u32 = seconds(30);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Side {
+    Left,
+    Right,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GapKind {
+    /// Balls leave the field through the gap
+    Exit,
+    /// Balls come back in through the other side wall at the same height
+    Wrap,
+}
+
+/// An opening in a side wall between two heights
+#[derive(Clone, Copy, Debug)]
+pub struct WallGap {
+    pub side: Side,
+    pub bottom: f32,
+    pub top: f32,
+    pub kind: GapKind,
+}
+
+/// The part of the world that play happens in. The screen always shows the
+/// whole world, so a level can pull the walls in from the edges of the
+/// screen, open gaps in the side walls and open the top.
+#[derive(Clone, Copy, Debug)]
+pub struct PlayField {
+    pub left: f32,
+    pub right: f32,
+    pub top: f32,
+    pub bottom: f32,
+    pub gaps: &'static [WallGap],
+    // Balls that go out of an open top drop straight back in where they
+    // left. The first one out in a level scores a bonus.
+    pub open_top: bool,
+}
+
+impl PlayField {
+    pub const FULL: PlayField = PlayField {
+        left: FIELD_LEFT,
+        right: FIELD_RIGHT,
+        top: FIELD_TOP,
+        bottom: FIELD_BOTTOM,
+        gaps: &[],
+        open_top: false,
+    };
+
+    /// The full field with both side walls moved in by `inset`
+    pub const fn inset(inset: f32) -> PlayField {
+        PlayField {
+            left: FIELD_LEFT + inset,
+            right: FIELD_RIGHT - inset,
+            ..PlayField::FULL
+        }
+    }
+
+    pub const fn with_gaps(self, gaps: &'static [WallGap]) -> PlayField {
+        PlayField { gaps, ..self }
+    }
+
+    pub const fn with_open_top(self) -> PlayField {
+        PlayField {
+            open_top: true,
+            ..self
+        }
+    }
+
+    pub fn width(&self) -> f32 {
+        self.right - self.left
+    }
+
+    pub fn center_x(&self) -> f32 {
+        (self.left + self.right) / 2.0
+    }
+
+    /// The kind of gap in the `side` wall that something with its top at
+    /// `top` and `height` tall fits through, if there is one
+    pub fn gap_at(&self, side: Side, top: f32, height: f32) -> Option<GapKind> {
+        self.gaps
+            .iter()
+            .find(|gap| gap.side == side && top <= gap.top && top - height >= gap.bottom)
+            .map(|gap| gap.kind)
+    }
+
+    /// The walls a ball of `size` bounces between, down to `floor`
+    pub fn walls(&self, size: WorldVec, floor: f32) -> Walls {
+        Walls {
+            left: self.left,
+            right: self.right - size.x,
+            top: self.top,
+            floor,
+            field: *self,
+            ball_height: size.y,
+        }
+    }
+}
+
+impl Default for PlayField {
+    fn default() -> Self {
+        PlayField::FULL
+    }
+}
+
+/// The box a ball's position stays inside. Positions are the ball's top left
+/// corner, so the right wall is already moved in by the ball's width.
+#[derive(Clone, Copy, Debug)]
+pub struct Walls {
+    pub left: f32,
+    pub right: f32,
+    pub top: f32,
+    // The height at which the landing position is wanted, such as the top
+    // of the paddle
+    pub floor: f32,
+    // For the gaps in the side walls, which a ball this tall may fit through
+    pub field: PlayField,
+    pub ball_height: f32,
+}
+
+/// Where a ball moving from `pos` at `vel` will reach the floor, bouncing off
+/// the side and top walls and wrapping through wrap gaps on the way, while
+/// `gravity` takes that much off its upward speed each tick, up to
+/// `max_speed`. The ball is moved a tick at a time the same way play moves
+/// it, speed first and then position, so the prediction is where it really
+/// lands. Bricks and paddles are ignored. Returns `None` if the ball leaves
+/// through an exit gap, never comes down or would bounce more than
+/// `max_bounces` times first.
+pub fn predict_landing_x(
+    mut pos: WorldPos,
+    mut vel: WorldVec,
+    gravity: f32,
+    max_speed: f32,
+    walls: Walls,
+    max_bounces: u32,
+) -> Option<f32> {
+    // Already under the floor and still going down
+    if pos.y < walls.floor && vel.y < 0.0 {
+        return None;
+    }
+    let mut bounces = 0;
+    for _ in 0..MAX_PREDICTION_TICKS {
+        if gravity > 0.0 {
+            vel.y -= gravity;
+            let speed = magnitude(vel.x, vel.y);
+            if speed > max_speed {
+                vel = vel * (max_speed / speed);
+            }
+        }
+        let (x, y) = (pos.x + vel.x, pos.y + vel.y);
+        let gap = if x <= walls.left {
+            walls.field.gap_at(Side::Left, y, walls.ball_height)
+        } else if x >= walls.right {
+            walls.field.gap_at(Side::Right, y, walls.ball_height)
+        } else {
+            None
+        };
+        let wrapped = match gap {
+            Some(GapKind::Exit) => return None,
+            Some(GapKind::Wrap) => true,
+            None => false,
+        };
+
+        let falling = vel.y < 0.0;
+        if (x <= walls.left || x >= walls.right) && !wrapped {
+            vel.x = -vel.x;
+            bounces += 1;
+        }
+        if y >= walls.top {
+            vel.y = -vel.y;
+            bounces += 1;
+        }
+        let next = WorldPos::new(
+            if wrapped && x <= walls.left {
+                walls.right - (walls.left - x)
+            } else if wrapped {
+                walls.left + (x - walls.right)
+            } else if x > walls.right {
+                walls.right - (x - walls.right)
+            } else if x < walls.left {
+                walls.left + (walls.left - x)
+            } else {
+                x
+            },
+            if y > walls.top {
+                walls.top - (y - walls.top)
+            } else {
+                y
+            },
+        );
+
+        if falling && next.y <= walls.floor {
+            if wrapped {
+                return Some(next.x);
+            }
+            // Part way through the tick, along the line it was drawn on
+            let t = (pos.y - walls.floor) / (pos.y - next.y);
+            return Some((pos.x + (next.x - pos.x) * t).clamp(walls.left, walls.right));
+        }
+        if bounces > max_bounces {
+            return None;
+        }
+        pos = next;
+    }
+    None
+}
+
+/// The part of the times from `from` to `to` during which `start + rate * t`
+/// is at least zero, or `None` if it never is. Chaining these narrows down
+/// when two moving boxes overlap on every side at once.
+pub fn while_non_negative(start: f32, rate: f32, from: f32, to: f32) -> Option<(f32, f32)> {
+    let (from, to) = if rate > 0.0 {
+        (from.max(-start / rate), to)
+    } else if rate < 0.0 {
+        (from, to.min(-start / rate))
+    } else if start >= 0.0 {
+        (from, to)
+    } else {
+        return None;
+    };
+    (from <= to).then_some((from, to))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Walls with no gaps, sized so that every step below lands on values
+    // floats hold exactly
+    const WALLS: Walls = Walls {
+        left: -1.0,
+        right: 0.5,
+        top: 1.0,
+        floor: -0.5,
+        field: PlayField::FULL,
+        ball_height: 0.05,
+    };
+
+    fn landing_x(pos: (f32, f32), vel: (f32, f32), max_bounces: u32) -> Option<f32> {
+        predict_landing_x(
+            WorldPos::new(pos.0, pos.1),
+            WorldVec::new(vel.0, vel.1),
+            0.0,
+            f32::INFINITY,
+            WALLS,
+            max_bounces,
+        )
+    }
+
+    #[test]
+    fn a_straight_drop_lands_below_the_ball() {
+        assert_eq!(landing_x((0.25, 0.5), (0.0, -0.125), 0), Some(0.25));
+    }
+
+    #[test]
+    fn a_side_wall_reflects_the_landing() {
+        // Unbounded, the ball would cover 1.0 across in the 8 ticks it
+        // takes to fall 1.0, ending up at 1.0. The right wall folds that
+        // back to 2 * 0.5 - 1.0.
+        assert_eq!(landing_x((0.0, 0.5), (0.125, -0.125), 1), Some(0.0));
+    }
+
+    #[test]
+    fn a_top_bounce_adds_the_way_up_and_back() {
+        // 2 ticks up to the top and 12 back down to the floor, moving
+        // 1 / 32 across each tick
+        assert_eq!(landing_x((0.0, 0.75), (0.03125, 0.125), 1), Some(0.4375));
+    }
+
+    #[test]
+    fn too_many_bounces_give_up() {
+        assert_eq!(landing_x((0.0, 0.5), (0.125, -0.125), 0), None);
+        assert_eq!(landing_x((0.0, 0.75), (0.03125, 0.125), 0), None);
+    }
+
+    #[test]
+    fn a_ball_below_the_floor_only_lands_if_rising() {
+        assert_eq!(landing_x((0.0, -0.75), (0.0, -0.125), 4), None);
+        // Up to the top and back down to the floor
+        assert_eq!(landing_x((0.0, -0.75), (0.0, 0.125), 4), Some(0.0));
+    }
+
+    #[test]
+    fn non_negative_while_rising_past_zero() {
+        assert_eq!(while_non_negative(-1.0, 2.0, 0.0, 1.0), Some((0.5, 1.0)));
+        // Reaches zero only after the window ends
+        assert_eq!(while_non_negative(-3.0, 2.0, 0.0, 1.0), None);
+    }
+
+    #[test]
+    fn non_negative_until_falling_past_zero() {
+        assert_eq!(while_non_negative(1.0, -4.0, 0.0, 1.0), Some((0.0, 0.25)));
+        assert_eq!(while_non_negative(-0.5, -1.0, 0.0, 1.0), None);
+    }
+
+    #[test]
+    fn non_negative_without_moving() {
+        assert_eq!(while_non_negative(0.0, 0.0, 0.2, 0.8), Some((0.2, 0.8)));
+        assert_eq!(while_non_negative(-0.1, 0.0, 0.2, 0.8), None);
+    }
+
+    #[test]
+    fn non_negative_only_narrows_the_window() {
+        // Fast enough to cross zero many times over within a tick
+        assert_eq!(
+            while_non_negative(-1.0, 100.0, 0.5, 0.75),
+            Some((0.5, 0.75))
+        );
+        assert_eq!(
+            while_non_negative(1.0, -100.0, 0.0, 0.75),
+            Some((0.0, 0.01))
+        );
+        // Touching zero exactly at the end of the window still counts
+        assert_eq!(while_non_negative(-1.0, 1.0, 0.0, 1.0), Some((1.0, 1.0)));
+    }
+}
@@ -0,0 +1,72 @@
+//! Physics tuning in one place.
+//!
+//! Distances are in world units: the field spans `-1.0..=1.0` on both axes
+//! with y pointing up, whatever the window size. Speeds are in world units
+//! per tick and angles are in radians.
+
+/// Edges of the playing field
+pub const FIELD_LEFT: f32 = -1.0;
+pub const FIELD_RIGHT: f32 = 1.0;
+pub const FIELD_TOP: f32 = 1.0;
+pub const FIELD_BOTTOM: f32 = -1.0;
+
+/// World units per tick
+pub const BALL_SERVE_SPEED: f32 = 0.00458;
+/// While a ball is in play, it gets this much faster, as a fraction of its
+/// speed, every so many paddle hits and broken bricks
+pub const SPEED_RAMP_STEP: f32 = 0.03;
+/// How many paddle hits and broken bricks each step takes, and how far
+/// above the serve speed the ramp can go, unless the config overrides them
+pub const SPEED_RAMP_HITS: u32 = 10;
+pub const SPEED_RAMP_MAX: f32 = 0.3;
+/// Serves get this much faster, as a fraction of the serve speed, for each
+/// level cleared in a run, up to the cap
+pub const LEVEL_SERVE_SPEED_UP: f32 = 0.05;
+pub const MAX_LEVEL_SERVE_SPEED_UP: f32 = 0.5;
+/// World units across. The drawn height is adjusted for the viewport so the
+/// ball stays round.
+pub const BALL_DIAMETER: f32 = 0.032;
+/// Angle between a split ball and the ball it split from
+pub const SPLIT_BALL_ANGLE: f32 = std::f32::consts::PI / 6.0;
+
+/// World units across
+pub const PADDLE_WIDTH: f32 = 0.2;
+/// World units per tick
+pub const PADDLE_MOVEMENT_SPEED: f32 = 0.022;
+/// How far from vertical the ball leaves the ends of a flat paddle. In
+/// between, the exit angle goes smoothly from one end's to the other's.
+pub const PADDLE_MAX_DEFLECTION: f32 = std::f32::consts::PI / 3.0;
+/// With the classic flat paddle, the paddle is split into this many equal
+/// zones across. The ball reflects about a vertical normal off the middle
+/// zone and about normals tilted outwards off the outer zones.
+pub const PADDLE_ZONES: f32 = 3.0;
+/// How far the outer zones' normals tilt from vertical, unless the config
+/// overrides it. 30 degrees gives the normals `(±1, √3)`.
+pub const PADDLE_EDGE_ANGLE: f32 = std::f32::consts::PI / 6.0;
+/// Fraction of a notched paddle's width taken by the notch in its middle
+pub const PADDLE_NOTCH_WIDTH: f32 = 0.2;
+/// Shallowest angle above the horizontal a ball leaves a convex or moving
+/// paddle at, so that a glancing hit off its edge can't send the ball back
+/// down
+pub const PADDLE_MIN_EXIT_ELEVATION: f32 = std::f32::consts::PI / 12.0;
+/// Fraction of the paddle's movement in the tick before a hit that is added
+/// to the ball's sideways speed as it leaves, so a moving paddle can steer
+/// it. The ball keeps its speed, and never leaves flatter than
+/// `PADDLE_MIN_EXIT_ELEVATION`.
+pub const PADDLE_ENGLISH: f32 = 0.05;
+
+/// In gravity mode, a ball launched straight up at its usual speed rises
+/// this far before it falls back, whatever that speed is
+pub const GRAVITY_RISE: f32 = 1.2;
+/// In gravity mode, balls leave the paddle fast enough to reach the top of
+/// the field, but never faster than this many times their usual speed
+pub const GRAVITY_MAX_SPEED: f32 = 2.0;
+
+/// Fraction the ball speed changes by for each press of +/-, unless the
+/// config overrides it
+pub const BALL_SPEED_STEP: f32 = 0.05;
+
+/// World units per tick
+pub const LASER_BOLT_SPEED: f32 = 0.03;
+/// World units per tick
+pub const CAPSULE_FALL_SPEED: f32 = 0.008;
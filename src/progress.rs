@@ -0,0 +1,214 @@
+use crate::versioned::{self, Format};
+use std::path::Path;
+
+const FORMAT: Format = Format {
+    name: "progress",
+    migrations: &[versioned::add_version],
+};
+
+#[derive(Clone, Copy, Default)]
+pub struct LevelProgress {
+    pub stars: u8,
+    pub best_score: u32,
+    // The fastest clear in time attack, in ticks
+    pub best_ticks: Option<u32>,
+}
+
+/// Per-level results, stored one level per line as
+/// `<level index> <stars> <best score>`, plus the furthest campaign
+/// checkpoint reached stored as `checkpoint <level index>`, the best
+/// endless score as `endless <score>`, each level's best time attack
+/// time as `time <level index> <ticks>` and the best daily challenge score
+/// of the last day played as `daily <day> <score>`
+#[derive(Default)]
+pub struct Progress {
+    pub levels: Vec<LevelProgress>,
+    pub checkpoint: usize,
+    pub endless_best: u32,
+    // The day of the last daily challenge played and its best score
+    daily: Option<(u32, u32)>,
+}
+
+impl Progress {
+    pub fn load(path: &Path) -> Progress {
+        let text = match versioned::read(path, &FORMAT) {
+            Ok(text) => text,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Progress::default(),
+            Err(err) => {
+                eprintln!("ERROR! Could not read progress from {path:?}: {err}");
+                return Progress::default();
+            }
+        };
+
+        let mut progress = Progress::default();
+        for (line_number, line) in text.lines().enumerate() {
+            if let Some(checkpoint) = line.strip_prefix("checkpoint ") {
+                match checkpoint.trim().parse() {
+                    Ok(checkpoint) => progress.checkpoint = checkpoint,
+                    Err(err) => eprintln!(
+                        "ERROR! Ignoring malformed checkpoint on line {} of {path:?}: {err}",
+                        line_number + 1
+                    ),
+                }
+                continue;
+            }
+            if let Some(best) = line.strip_prefix("endless ") {
+                match best.trim().parse() {
+                    Ok(best) => progress.endless_best = best,
+                    Err(err) => eprintln!(
+                        "ERROR! Ignoring malformed endless score on line {} of {path:?}: {err}",
+                        line_number + 1
+                    ),
+                }
+                continue;
+            }
+            if let Some(daily) = line.strip_prefix("daily ") {
+                let fields = daily
+                    .split_whitespace()
+                    .map(|field| field.parse::<u32>())
+                    .collect::<Result<Vec<_>, _>>();
+                match fields.as_deref() {
+                    Ok(&[day, score]) => progress.daily = Some((day, score)),
+                    _ => eprintln!(
+                        "ERROR! Ignoring malformed daily score on line {} of {path:?}",
+                        line_number + 1
+                    ),
+                }
+                continue;
+            }
+            if let Some(time) = line.strip_prefix("time ") {
+                let fields = time
+                    .split_whitespace()
+                    .map(|field| field.parse::<u32>())
+                    .collect::<Result<Vec<_>, _>>();
+                match fields.as_deref() {
+                    Ok(&[index, ticks]) => {
+                        progress.level_mut(index as usize).best_ticks = Some(ticks)
+                    }
+                    _ => eprintln!(
+                        "ERROR! Ignoring malformed best time on line {} of {path:?}",
+                        line_number + 1
+                    ),
+                }
+                continue;
+            }
+
+            let fields = line
+                .split_whitespace()
+                .map(|field| field.parse::<u32>())
+                .collect::<Result<Vec<_>, _>>();
+            match fields.as_deref() {
+                Ok(&[index, stars, best_score]) => {
+                    let level = progress.level_mut(index as usize);
+                    level.stars = stars.min(3) as u8;
+                    level.best_score = best_score;
+                }
+                Ok(&[]) => (),
+                _ => eprintln!(
+                    "ERROR! Ignoring malformed progress entry on line {} of {path:?}",
+                    line_number + 1
+                ),
+            }
+        }
+        progress
+    }
+
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        let mut text = self
+            .levels
+            .iter()
+            .enumerate()
+            .map(|(index, level)| format!("{index} {} {}\n", level.stars, level.best_score))
+            .collect::<String>();
+        text += &format!("checkpoint {}\n", self.checkpoint);
+        text += &format!("endless {}\n", self.endless_best);
+        if let Some((day, score)) = self.daily {
+            text += &format!("daily {day} {score}\n");
+        }
+        for (index, level) in self.levels.iter().enumerate() {
+            if let Some(ticks) = level.best_ticks {
+                text += &format!("time {index} {ticks}\n");
+            }
+        }
+        versioned::write(path, &FORMAT, &text)
+    }
+
+    pub fn level(&self, index: usize) -> LevelProgress {
+        self.levels.get(index).copied().unwrap_or_default()
+    }
+
+    fn level_mut(&mut self, index: usize) -> &mut LevelProgress {
+        if self.levels.len() <= index {
+            self.levels.resize(index + 1, LevelProgress::default());
+        }
+        &mut self.levels[index]
+    }
+
+    /// Keeps the best star rating and best score seen for a level
+    pub fn record(&mut self, index: usize, stars: u8, score: u32) {
+        let level = self.level_mut(index);
+        level.stars = level.stars.max(stars);
+        level.best_score = level.best_score.max(score);
+    }
+
+    /// Keeps the fastest time attack clear of a level, returning whether
+    /// `ticks` beat it
+    pub fn record_time(&mut self, index: usize, ticks: u32) -> bool {
+        let level = self.level_mut(index);
+        let is_best = level.best_ticks.is_none_or(|best| ticks < best);
+        if is_best {
+            level.best_ticks = Some(ticks);
+        }
+        is_best
+    }
+
+    /// The best daily challenge score on `day`, if it has been played
+    pub fn daily_best(&self, day: u32) -> Option<u32> {
+        self.daily
+            .filter(|&(played, _)| played == day)
+            .map(|(_, score)| score)
+    }
+
+    /// Keeps the best daily challenge score of `day`, returning whether
+    /// `score` beat it. Scores from earlier days are dropped.
+    pub fn record_daily(&mut self, day: u32, score: u32) -> bool {
+        let is_best = self.daily_best(day).is_none_or(|best| score > best);
+        if is_best {
+            self.daily = Some((day, score));
+        }
+        is_best
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn saved_progress_loads_back_the_same() {
+        let path =
+            std::env::temp_dir().join(format!("breakrs-progress-{}.txt", std::process::id()));
+        let mut progress = Progress {
+            checkpoint: 4,
+            endless_best: 12_340,
+            ..Progress::default()
+        };
+        progress.record(0, 3, 900);
+        progress.record(2, 1, 150);
+        progress.record_time(2, 1_234);
+        progress.record_daily(20_000, 5_600);
+        progress.save(&path).unwrap();
+        let loaded = Progress::load(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.levels.len(), progress.levels.len());
+        for (loaded, saved) in loaded.levels.iter().zip(&progress.levels) {
+            assert_eq!(loaded.stars, saved.stars);
+            assert_eq!(loaded.best_score, saved.best_score);
+            assert_eq!(loaded.best_ticks, saved.best_ticks);
+        }
+        assert_eq!(loaded.checkpoint, 4);
+        assert_eq!(loaded.endless_best, 12_340);
+        assert_eq!(loaded.daily_best(20_000), Some(5_600));
+    }
+}
@@ -0,0 +1,84 @@
+//! The instant replay shown after a ball is lost.
+//!
+//! While playing, a [`Recorder`] keeps the last few seconds of what was
+//! drawn as [`RenderFrame`]s. Losing a life turns the recording into a
+//! [`Replay`], which plays it back in slow motion before the next serve.
+
+use crate::boss::Boss;
+use crate::clock::seconds;
+use crate::units::{WorldPos, WorldVec};
+use crate::{Ball, Barrier, Capsule, PaddleRect};
+use std::collections::VecDeque;
+
+// How much play is kept for the replay
+const RECORDED_TICKS: usize = seconds(5) as usize;
+// Recorded ticks played back per tick, so the replay runs at half speed
+const REPLAY_SPEED: f32 = 0.5;
+
+/// Everything in play that moves or changes while a level is played, as
+/// it is drawn. The walls and where the bricks are come from the level.
+pub struct RenderFrame {
+    // Only the balls that are shown, leaving out blinking ones while they
+    // are hidden
+    pub balls: Vec<Ball>,
+    pub ball_size: WorldVec,
+    pub paddles: Vec<PaddleRect>,
+    pub destroyed: Vec<bool>,
+    pub hit_points: Vec<u8>,
+    pub laser_bolts: Vec<WorldPos>,
+    pub capsules: Vec<Capsule>,
+    pub hazards: Vec<WorldPos>,
+    pub boss: Option<Boss>,
+    pub barrier: Option<Barrier>,
+    // Balls are drawn as fireballs
+    pub fireball: bool,
+}
+
+#[derive(Default)]
+pub struct Recorder {
+    frames: VecDeque<RenderFrame>,
+}
+
+impl Recorder {
+    pub fn record(&mut self, frame: RenderFrame) {
+        if self.frames.len() == RECORDED_TICKS {
+            self.frames.pop_front();
+        }
+        self.frames.push_back(frame);
+    }
+
+    pub fn clear(&mut self) {
+        self.frames.clear();
+    }
+
+    /// Starts a replay of everything recorded so far, leaving the recorder
+    /// empty. `None` if nothing has been recorded.
+    pub fn replay(&mut self) -> Option<Replay> {
+        if self.frames.is_empty() {
+            return None;
+        }
+        Some(Replay {
+            frames: std::mem::take(&mut self.frames).into(),
+            position: 0.0,
+        })
+    }
+}
+
+pub struct Replay {
+    frames: Vec<RenderFrame>,
+    position: f32,
+}
+
+impl Replay {
+    pub fn advance(&mut self) {
+        self.position += REPLAY_SPEED;
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.position as usize >= self.frames.len()
+    }
+
+    pub fn frame(&self) -> &RenderFrame {
+        &self.frames[(self.position as usize).min(self.frames.len() - 1)]
+    }
+}
@@ -0,0 +1,175 @@
+//! A campaign run saved when the game has to stop in the middle of it, so
+//! that it can be picked up again on the next launch.
+
+use crate::versioned::{self, Format};
+use std::path::Path;
+
+const FORMAT: Format = Format {
+    name: "run",
+    migrations: &[versioned::add_version],
+};
+
+/// A brick still standing. Bricks that aren't saved were destroyed.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SavedBrick {
+    pub index: usize,
+    pub hit_points: u8,
+    pub hidden: bool,
+}
+
+/// Where a run was, stored one value per line as `<name> <value>` and one
+/// standing brick per line as `brick <index> <hit points> <hidden>`. The
+/// ball, capsules and running power-ups aren't kept: the run picks up with
+/// a new ball on the paddle.
+#[derive(Debug, Default, PartialEq)]
+pub struct SavedRun {
+    pub level: usize,
+    pub score: u32,
+    pub level_start_score: u32,
+    pub lives: u32,
+    pub levels_cleared: u32,
+    // How far the bricks had dropped
+    pub lowered: f32,
+    // The number of bricks the level has, standing or not, to make sure
+    // they are put back in the same level
+    pub brick_count: usize,
+    pub bricks: Vec<SavedBrick>,
+}
+
+impl SavedRun {
+    /// Reads the saved run and removes it, so that it is only picked up
+    /// once. `None` if there wasn't one or it couldn't be read.
+    pub fn take(path: &Path) -> Option<SavedRun> {
+        let text = match versioned::read(path, &FORMAT) {
+            Ok(text) => text,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return None,
+            Err(err) => {
+                eprintln!("ERROR! Could not read the saved run from {path:?}: {err}");
+                return None;
+            }
+        };
+        if let Err(err) = std::fs::remove_file(path) {
+            eprintln!("ERROR! Could not remove the saved run {path:?}: {err}");
+        }
+        match SavedRun::parse(&text) {
+            Ok(run) => Some(run),
+            Err(err) => {
+                eprintln!("ERROR! Ignoring the saved run in {path:?}: {err}");
+                None
+            }
+        }
+    }
+
+    fn parse(text: &str) -> Result<SavedRun, String> {
+        let mut run = SavedRun::default();
+        for (line_number, line) in text.lines().enumerate() {
+            let fields = line.split_whitespace().collect::<Vec<_>>();
+            match fields.as_slice() {
+                [] => Some(()),
+                ["level", value] => value.parse().map(|value| run.level = value).ok(),
+                ["score", value] => value.parse().map(|value| run.score = value).ok(),
+                ["level_start_score", value] => value
+                    .parse()
+                    .map(|value| run.level_start_score = value)
+                    .ok(),
+                ["lives", value] => value.parse().map(|value| run.lives = value).ok(),
+                ["levels_cleared", value] => {
+                    value.parse().map(|value| run.levels_cleared = value).ok()
+                }
+                ["lowered", value] => value.parse().map(|value| run.lowered = value).ok(),
+                ["bricks", value] => value.parse().map(|value| run.brick_count = value).ok(),
+                ["brick", index, hit_points, hidden] => {
+                    match (index.parse(), hit_points.parse(), hidden.parse::<u8>()) {
+                        (Ok(index), Ok(hit_points), Ok(hidden @ 0..=1)) => {
+                            run.bricks.push(SavedBrick {
+                                index,
+                                hit_points,
+                                hidden: hidden == 1,
+                            });
+                            Some(())
+                        }
+                        _ => None,
+                    }
+                }
+                _ => None,
+            }
+            .ok_or_else(|| format!("line {} is malformed", line_number + 1))?;
+        }
+        if let Some(brick) = run
+            .bricks
+            .iter()
+            .find(|brick| brick.index >= run.brick_count)
+        {
+            return Err(format!("there is no brick {}", brick.index));
+        }
+        Ok(run)
+    }
+
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        let mut text = format!(
+            "level {}\nscore {}\nlevel_start_score {}\nlives {}\n",
+            self.level, self.score, self.level_start_score, self.lives
+        );
+        text += &format!(
+            "levels_cleared {}\nlowered {}\nbricks {}\n",
+            self.levels_cleared, self.lowered, self.brick_count
+        );
+        for brick in &self.bricks {
+            text += &format!(
+                "brick {} {} {}\n",
+                brick.index,
+                brick.hit_points,
+                u8::from(brick.hidden)
+            );
+        }
+        versioned::write(path, &FORMAT, &text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_saved_run_is_taken_back_once() {
+        let path = std::env::temp_dir().join(format!("breakrs-run-{}.txt", std::process::id()));
+        let run = SavedRun {
+            level: 3,
+            score: 4_200,
+            level_start_score: 3_900,
+            lives: 2,
+            levels_cleared: 3,
+            lowered: 0.125,
+            brick_count: 20,
+            bricks: vec![
+                SavedBrick {
+                    index: 0,
+                    hit_points: 2,
+                    hidden: false,
+                },
+                SavedBrick {
+                    index: 19,
+                    hit_points: 1,
+                    hidden: true,
+                },
+            ],
+        };
+        run.save(&path).unwrap();
+        assert_eq!(SavedRun::take(&path), Some(run));
+        assert_eq!(SavedRun::take(&path), None);
+    }
+
+    #[test]
+    fn bricks_past_the_end_of_the_level_are_rejected() {
+        let text = "level 0\nbricks 4\nbrick 4 1 0\n";
+        assert_eq!(
+            SavedRun::parse(text),
+            Err(String::from("there is no brick 4"))
+        );
+        let text = "level 0\nbricks 4\nbrick 1 1 2\n";
+        assert_eq!(
+            SavedRun::parse(text),
+            Err(String::from("line 3 is malformed"))
+        );
+    }
+}
@@ -0,0 +1,222 @@
+use crate::versioned::{self, Format};
+use crate::{compute_multiline_text_data, draw_focus_outline, draw_rect, draw_subcanvas, Canvas};
+use minifb::Key;
+use rusttype::Font;
+use std::path::Path;
+
+const TEXT_HEIGHT: f32 = 20.0;
+const ROW_GAP: usize = 24;
+const BAR_WIDTH: usize = 200;
+const BAR_HEIGHT: usize = 12;
+const BAR_BORDER: usize = 2;
+const BAR_COLOR: u32 = 0x000000;
+const BAR_BACKGROUND: u32 = 0xFFFFFF;
+// Sliders move in tenths so they can be set exactly from the keyboard
+const SLIDER_STEP: f32 = 0.1;
+
+const FORMAT: Format = Format {
+    name: "settings",
+    migrations: &[versioned::add_version],
+};
+
+/// Volumes of the three mixer buses, each in `0.0..=1.0`. The master volume
+/// scales the other two.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Volumes {
+    pub master: f32,
+    pub music: f32,
+    pub sfx: f32,
+}
+
+impl Default for Volumes {
+    fn default() -> Self {
+        Volumes {
+            master: 1.0,
+            music: 0.8,
+            sfx: 1.0,
+        }
+    }
+}
+
+/// Settings changed from the settings menu
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Settings {
+    pub volumes: Volumes,
+    // Gamepad rumble strength in `0.0..=1.0`
+    pub rumble: f32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            volumes: Volumes::default(),
+            rumble: 1.0,
+        }
+    }
+}
+
+impl Settings {
+    /// Reads settings stored as `<name> = <percent>` lines
+    pub fn load(path: &Path) -> Settings {
+        let mut settings = Settings::default();
+        let text = match versioned::read(path, &FORMAT) {
+            Ok(text) => text,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return settings,
+            Err(err) => {
+                eprintln!("ERROR! Could not read settings from {path:?}: {err}");
+                return settings;
+            }
+        };
+
+        for (line_number, line) in text.lines().enumerate() {
+            let entry = line.split_once('=').and_then(|(name, percent)| {
+                let percent = percent.trim().parse::<u32>().ok().filter(|p| *p <= 100)?;
+                Some((name.trim(), percent as f32 / 100.0))
+            });
+            match entry {
+                Some(("master", value)) => settings.volumes.master = value,
+                Some(("music", value)) => settings.volumes.music = value,
+                Some(("sfx", value)) => settings.volumes.sfx = value,
+                Some(("rumble", value)) => settings.rumble = value,
+                _ if line.trim().is_empty() => (),
+                _ => eprintln!(
+                    "ERROR! Ignoring malformed setting on line {} of {path:?}",
+                    line_number + 1
+                ),
+            }
+        }
+        settings
+    }
+
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        let percent = |value: f32| (value * 100.0).round() as u32;
+        let text = format!(
+            "master = {}\nmusic = {}\nsfx = {}\nrumble = {}\n",
+            percent(self.volumes.master),
+            percent(self.volumes.music),
+            percent(self.volumes.sfx),
+            percent(self.rumble)
+        );
+        versioned::write(path, &FORMAT, &text)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Slider {
+    Master,
+    Music,
+    Sfx,
+    Rumble,
+}
+
+impl Slider {
+    const ALL: [Slider; 4] = [Slider::Master, Slider::Music, Slider::Sfx, Slider::Rumble];
+
+    fn label(self) -> &'static str {
+        match self {
+            Slider::Master => "master",
+            Slider::Music => "music",
+            Slider::Sfx => "effects",
+            Slider::Rumble => "rumble",
+        }
+    }
+
+    fn value(self, settings: &mut Settings) -> &mut f32 {
+        match self {
+            Slider::Master => &mut settings.volumes.master,
+            Slider::Music => &mut settings.volumes.music,
+            Slider::Sfx => &mut settings.volumes.sfx,
+            Slider::Rumble => &mut settings.rumble,
+        }
+    }
+}
+
+pub enum SettingsAction {
+    None,
+    Changed(Slider),
+    Back,
+}
+
+pub struct SettingsMenu {
+    pub cursor: usize,
+}
+
+impl SettingsMenu {
+    pub fn key_pressed(&mut self, key: Key, settings: &mut Settings) -> SettingsAction {
+        let slider = Slider::ALL[self.cursor];
+        let step = match key {
+            Key::Up | Key::W => {
+                self.cursor = self.cursor.saturating_sub(1);
+                return SettingsAction::None;
+            }
+            Key::Down | Key::S => {
+                self.cursor = (self.cursor + 1).min(Slider::ALL.len() - 1);
+                return SettingsAction::None;
+            }
+            Key::Left | Key::A => -SLIDER_STEP,
+            Key::Right | Key::D => SLIDER_STEP,
+            Key::Backspace | Key::L => return SettingsAction::Back,
+            _ => return SettingsAction::None,
+        };
+        let value = slider.value(settings);
+        // Round to whole steps so repeated changes don't drift
+        let changed = ((*value + step) / SLIDER_STEP).round() * SLIDER_STEP;
+        *value = changed.clamp(0.0, 1.0);
+        SettingsAction::Changed(slider)
+    }
+
+    pub fn draw(&self, canvas: &mut Canvas, font: Option<&Font>, settings: &Settings, focus: f32) {
+        let Some(font) = font else {
+            return;
+        };
+        let title = compute_multiline_text_data(font, TEXT_HEIGHT * 1.5, &["SETTINGS"]);
+        let mut y = canvas.height() / 4;
+        draw_subcanvas(
+            canvas,
+            &title,
+            canvas.width().saturating_sub(title.width()) / 2,
+            y,
+        );
+        y += title.height() + ROW_GAP * 2;
+
+        let mut settings = *settings;
+        for (index, slider) in Slider::ALL.into_iter().enumerate() {
+            let value = *slider.value(&mut settings);
+            let label = format!("{:<8} {:>3}%", slider.label(), (value * 100.0).round());
+            let text = compute_multiline_text_data(font, TEXT_HEIGHT, &[&label]);
+            let width = text.width() + ROW_GAP + BAR_WIDTH;
+            let x = canvas.width().saturating_sub(width) / 2;
+            if index == self.cursor {
+                draw_focus_outline(canvas, x, y, width, text.height(), ROW_GAP / 4, focus);
+            }
+            draw_subcanvas(canvas, &text, x, y);
+
+            let bar_x = x + text.width() + ROW_GAP;
+            let bar_y = y + text.height().saturating_sub(BAR_HEIGHT) / 2;
+            draw_rect(canvas, bar_x, bar_y, BAR_WIDTH, BAR_HEIGHT, BAR_COLOR);
+            draw_rect(
+                canvas,
+                bar_x + BAR_BORDER,
+                bar_y + BAR_BORDER,
+                BAR_WIDTH - 2 * BAR_BORDER,
+                BAR_HEIGHT - 2 * BAR_BORDER,
+                BAR_BACKGROUND,
+            );
+            let filled = ((BAR_WIDTH - 2 * BAR_BORDER) as f32 * value) as usize;
+            draw_rect(
+                canvas,
+                bar_x + BAR_BORDER,
+                bar_y + BAR_BORDER,
+                filled,
+                BAR_HEIGHT - 2 * BAR_BORDER,
+                BAR_COLOR,
+            );
+            y += text.height() + ROW_GAP;
+        }
+
+        let hint =
+            compute_multiline_text_data(font, TEXT_HEIGHT, &["[Left/Right] adjust  [L] back"]);
+        let x = canvas.width().saturating_sub(hint.width()) / 2;
+        draw_subcanvas(canvas, &hint, x, y + ROW_GAP);
+    }
+}
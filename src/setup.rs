@@ -0,0 +1,171 @@
+//! The setup shown on first launch, before there is a config file. Each
+//! step picks one setting and changes the config straight away, so the theme
+//! can be seen while choosing it.
+
+use crate::config::{Config, Controls, Difficulty, Theme};
+use crate::{
+    compute_multiline_text_data, draw_focus_outline, draw_panel, draw_subcanvas, Canvas,
+    PANEL_BORDER, PANEL_PADDING,
+};
+use minifb::Key;
+use rusttype::Font;
+
+const TEXT_HEIGHT: f32 = 20.0;
+const ROW_GAP: usize = 24;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Step {
+    Controls,
+    Difficulty,
+    Theme,
+}
+
+impl Step {
+    const ALL: [Step; 3] = [Step::Controls, Step::Difficulty, Step::Theme];
+
+    fn title(self) -> &'static str {
+        match self {
+            Step::Controls => "CONTROLS",
+            Step::Difficulty => "DIFFICULTY",
+            Step::Theme => "THEME",
+        }
+    }
+
+    /// Each option's label and description
+    fn options(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Step::Controls => &[
+                ("A / D", "move the paddle with A and D"),
+                ("Arrows", "move the paddle with the arrow keys"),
+            ],
+            Step::Difficulty => &[
+                ("Easy", "wider paddle, slower ball, fewer points"),
+                ("Normal", "the game as designed"),
+                ("Hard", "narrower paddle, faster ball, more points"),
+            ],
+            Step::Theme => &[
+                ("Classic", "bright cyan"),
+                ("Night", "easy on the eyes"),
+                ("Paper", "dark on light"),
+            ],
+        }
+    }
+
+    fn current(self, config: &Config) -> usize {
+        match self {
+            Step::Controls => Controls::ALL.iter().position(|c| *c == config.controls),
+            Step::Difficulty => Difficulty::ALL.iter().position(|d| *d == config.difficulty),
+            Step::Theme => Theme::ALL.iter().position(|t| *t == config.theme),
+        }
+        .unwrap_or(0)
+    }
+
+    fn choose(self, config: &mut Config, option: usize) {
+        match self {
+            Step::Controls => config.controls = Controls::ALL[option],
+            Step::Difficulty => config.difficulty = Difficulty::ALL[option],
+            Step::Theme => config.theme = Theme::ALL[option],
+        }
+    }
+}
+
+pub enum SetupAction {
+    None,
+    Changed,
+    Finished,
+}
+
+pub struct SetupWizard {
+    step: usize,
+    cursor: usize,
+}
+
+impl SetupWizard {
+    pub fn new(config: &Config) -> SetupWizard {
+        SetupWizard {
+            step: 0,
+            cursor: Step::ALL[0].current(config),
+        }
+    }
+
+    /// The focused option, numbered across all steps so that moving to
+    /// another step counts as a change of focus
+    pub fn focus(&self) -> usize {
+        self.step * 10 + self.cursor
+    }
+
+    pub fn key_pressed(&mut self, key: Key, config: &mut Config) -> SetupAction {
+        let step = Step::ALL[self.step];
+        let last = step.options().len() - 1;
+        match key {
+            Key::Up | Key::W => self.cursor = self.cursor.saturating_sub(1),
+            Key::Down | Key::S => self.cursor = (self.cursor + 1).min(last),
+            Key::Enter | Key::Space if self.step + 1 == Step::ALL.len() => {
+                return SetupAction::Finished;
+            }
+            Key::Enter | Key::Space => {
+                self.step += 1;
+                self.cursor = Step::ALL[self.step].current(config);
+                return SetupAction::None;
+            }
+            Key::Backspace if self.step > 0 => {
+                self.step -= 1;
+                self.cursor = Step::ALL[self.step].current(config);
+                return SetupAction::None;
+            }
+            _ => return SetupAction::None,
+        }
+        step.choose(config, self.cursor);
+        SetupAction::Changed
+    }
+
+    pub fn draw(&self, canvas: &mut Canvas, font: Option<&Font>, focus: f32) {
+        let Some(font) = font else {
+            return;
+        };
+        let step = Step::ALL[self.step];
+        let heading = format!("SETUP {}/{}", self.step + 1, Step::ALL.len());
+        let title = compute_multiline_text_data(font, TEXT_HEIGHT * 1.5, &[&heading, step.title()]);
+        let mut y = canvas.height() / 6;
+        draw_subcanvas(
+            canvas,
+            &title,
+            canvas.width().saturating_sub(title.width()) / 2,
+            y,
+        );
+        y += title.height() + ROW_GAP * 2;
+
+        for (index, (label, description)) in step.options().iter().enumerate() {
+            let text = compute_multiline_text_data(font, TEXT_HEIGHT, &[label, description]);
+            let x = canvas.width().saturating_sub(text.width()) / 2;
+            if index == self.cursor {
+                let inset = PANEL_BORDER + PANEL_PADDING;
+                draw_focus_outline(
+                    canvas,
+                    x.saturating_sub(inset),
+                    y.saturating_sub(inset),
+                    text.width() + 2 * inset,
+                    text.height() + 2 * inset,
+                    ROW_GAP / 4,
+                    focus,
+                );
+            }
+            draw_panel(canvas, x, y, text.width(), text.height());
+            draw_subcanvas(canvas, &text, x, y);
+            y += text.height() + ROW_GAP * 2;
+        }
+
+        let mut hint = String::from("[Up/Down] choose");
+        hint += if self.step + 1 == Step::ALL.len() {
+            "  [Enter] done"
+        } else {
+            "  [Enter] next"
+        };
+        if self.step > 0 {
+            hint += "  [Backspace] back";
+        }
+        let hint = compute_multiline_text_data(font, TEXT_HEIGHT, &[&hint]);
+        let x = canvas.width().saturating_sub(hint.width()) / 2;
+        draw_subcanvas(canvas, &hint, x, y);
+    }
+}
@@ -0,0 +1,168 @@
+//! `breakrs soak-test` plays campaign runs back to back without a window and
+//! as fast as the machine allows, with the paddle following the ball. After
+//! every frame it checks that the game state still makes sense, and it
+//! reports memory use every so often so that slow leaks show up as a number
+//! that keeps growing.
+
+use crate::autopilot::Autopilot;
+use crate::config::Config;
+use crate::{GameState, LaserBolt, Res, RunMode, Scene, LASER_BOLT_HEIGHT, MAX_BALLS, MAX_LIVES};
+use std::time::{Duration, Instant};
+
+const DEFAULT_SEED: u64 = 1;
+const DEFAULT_HOURS: f32 = 1.0;
+const REPORT_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Default)]
+struct Totals {
+    frames: u64,
+    runs: u64,
+    levels: u64,
+}
+
+/// Runs the soak test with the options in `args`, reporting progress on
+/// stdout. Fails as soon as the game state breaks an invariant.
+pub fn run_cli(args: &[String]) -> Res<()> {
+    let mut seed = DEFAULT_SEED;
+    let mut hours = DEFAULT_HOURS;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let value = args.next();
+        let parsed = match (arg.as_str(), value) {
+            ("--seed", Some(value)) => value.parse().map(|value| seed = value).is_ok(),
+            ("--hours", Some(value)) => value
+                .parse::<f32>()
+                .map(|value| hours = value)
+                .is_ok_and(|_| hours.is_finite() && hours > 0.0),
+            _ => false,
+        };
+        if !parsed {
+            eprintln!("ERROR! Usage: breakrs soak-test [--seed <seed>] [--hours <hours>]");
+            return Err(());
+        }
+    }
+
+    let duration = Duration::from_secs_f32(hours * 3600.0);
+    let start = Instant::now();
+    let mut next_report = start + REPORT_INTERVAL;
+    let first_memory = resident_kb();
+    let mut totals = Totals::default();
+    let mut state = new_run(seed);
+    let mut autopilot = Autopilot::new(seed.wrapping_add(1));
+
+    println!("Soak testing with seed {seed} for {hours} hours");
+    while start.elapsed() < duration {
+        match state.scene {
+            Scene::Playing | Scene::Intro(_) | Scene::LevelComplete(_) | Scene::Replay(_) => (),
+            // A game over or the end of the campaign, either way go again
+            _ => {
+                totals.runs += 1;
+                state = new_run(seed.wrapping_add(totals.runs));
+            }
+        }
+
+        autopilot.steer(&mut state);
+        let level = state.level_index;
+        state.tick();
+        totals.frames += 1;
+        if state.level_index != level {
+            totals.levels += 1;
+        }
+
+        if let Err(broken) = check_invariants(&state) {
+            println!(
+                "Soak test failed after {} frames (run {}, level {}): {broken}",
+                totals.frames,
+                totals.runs + 1,
+                state.level_index + 1
+            );
+            return Err(());
+        }
+
+        if Instant::now() >= next_report {
+            next_report += REPORT_INTERVAL;
+            report(start, &totals);
+        }
+    }
+
+    report(start, &totals);
+    println!(
+        "Soak test passed, resident memory went from {} to {}",
+        format_kb(first_memory),
+        format_kb(resident_kb())
+    );
+    Ok(())
+}
+
+fn new_run(seed: u64) -> GameState {
+    let mut state = GameState {
+        config: Config {
+            seed: Some(seed),
+            ..Config::default()
+        },
+        ..GameState::default()
+    };
+    state.start_run(RunMode::Campaign, 0);
+    state
+}
+
+/// Things that must hold after every frame, whatever happens in play
+fn check_invariants(state: &GameState) -> Result<(), String> {
+    if matches!(state.scene, Scene::Playing) && state.balls.is_empty() {
+        return Err(String::from("no ball in play"));
+    }
+    if state.balls.len() > MAX_BALLS {
+        return Err(format!(
+            "{} balls, more than {MAX_BALLS}",
+            state.balls.len()
+        ));
+    }
+    let field = state.field;
+    for ball in &state.balls {
+        let in_field = (field.left..=field.right).contains(&ball.pos.x)
+            && (field.bottom..=field.top).contains(&ball.pos.y);
+        if !in_field || !ball.vel.x.is_finite() || !ball.vel.y.is_finite() {
+            return Err(format!(
+                "ball at ({}, {}) moving ({}, {})",
+                ball.pos.x, ball.pos.y, ball.vel.x, ball.vel.y
+            ));
+        }
+    }
+    let paddle_right = state.paddle_pos.x + state.paddle_width;
+    if state.paddle_pos.x < field.left || paddle_right > field.right {
+        return Err(format!("paddle at {}", state.paddle_pos.x));
+    }
+    // Bolts are dropped once their bottom edge leaves the field
+    let escaped = |bolt: &&LaserBolt| bolt.pos.y - LASER_BOLT_HEIGHT >= field.top;
+    if let Some(bolt) = state.laser_bolts.iter().find(escaped) {
+        return Err(format!("laser bolt left the field at {}", bolt.pos.y));
+    }
+    if state.lives > MAX_LIVES {
+        return Err(format!("{} lives", state.lives));
+    }
+    Ok(())
+}
+
+fn report(start: Instant, totals: &Totals) {
+    println!(
+        "{:>6}s  {} frames  {} runs  {} levels cleared  resident {}",
+        start.elapsed().as_secs(),
+        totals.frames,
+        totals.runs,
+        totals.levels,
+        format_kb(resident_kb())
+    );
+}
+
+/// Resident set size, where the platform makes it easy to find
+fn resident_kb() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmRSS:"))?;
+    line.trim().strip_suffix("kB")?.trim().parse().ok()
+}
+
+fn format_kb(kb: Option<u64>) -> String {
+    kb.map_or(String::from("unknown"), |kb| format!("{kb} kB"))
+}
@@ -0,0 +1,122 @@
+use crate::clock::TICKS_PER_SECOND;
+use crate::versioned::{self, Format};
+use crate::{compute_multiline_text_data, draw_subcanvas, Canvas, GameEvent};
+use rusttype::Font;
+use std::path::Path;
+
+const TEXT_HEIGHT: f32 = 20.0;
+const ROW_GAP: usize = 12;
+
+const FORMAT: Format = Format {
+    name: "stats",
+    migrations: &[versioned::add_version],
+};
+
+/// Totals kept across every session, stored one per line as
+/// `<name> <value>`
+#[derive(Clone, Copy, Default)]
+pub struct Stats {
+    pub bricks_broken: u64,
+    pub balls_lost: u64,
+    pub levels_cleared: u64,
+    // Frames spent in a run, not counting time paused
+    pub play_ticks: u64,
+}
+
+impl Stats {
+    pub fn load(path: &Path) -> Stats {
+        let mut stats = Stats::default();
+        let text = match versioned::read(path, &FORMAT) {
+            Ok(text) => text,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return stats,
+            Err(err) => {
+                eprintln!("ERROR! Could not read stats from {path:?}: {err}");
+                return stats;
+            }
+        };
+
+        for (line_number, line) in text.lines().enumerate() {
+            let mut fields = line.split_whitespace();
+            let total = match (fields.next(), fields.next().map(str::parse::<u64>)) {
+                (Some(name), Some(Ok(value))) => stats.total_mut(name).map(|total| (total, value)),
+                (None, None) => continue,
+                _ => None,
+            };
+            match total {
+                Some((total, value)) => *total = value,
+                None => eprintln!(
+                    "ERROR! Ignoring malformed stat on line {} of {path:?}",
+                    line_number + 1
+                ),
+            }
+        }
+        stats
+    }
+
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        let text = format!(
+            "bricks_broken {}\nballs_lost {}\nlevels_cleared {}\nplay_ticks {}\n",
+            self.bricks_broken, self.balls_lost, self.levels_cleared, self.play_ticks
+        );
+        versioned::write(path, &FORMAT, &text)
+    }
+
+    fn total_mut(&mut self, name: &str) -> Option<&mut u64> {
+        match name {
+            "bricks_broken" => Some(&mut self.bricks_broken),
+            "balls_lost" => Some(&mut self.balls_lost),
+            "levels_cleared" => Some(&mut self.levels_cleared),
+            "play_ticks" => Some(&mut self.play_ticks),
+            _ => None,
+        }
+    }
+
+    /// Counts whatever `event` adds to the totals
+    pub fn record(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::BrickDestroyed(..) => self.bricks_broken += 1,
+            GameEvent::BallMissed => self.balls_lost += 1,
+            _ => (),
+        }
+    }
+}
+
+/// Play time as hours and minutes, or minutes and seconds under an hour
+fn format_play_time(ticks: u64) -> String {
+    let seconds = ticks / TICKS_PER_SECOND as u64;
+    let (hours, minutes) = (seconds / 3600, seconds / 60 % 60);
+    if hours > 0 {
+        format!("{hours}h {minutes:02}m")
+    } else {
+        format!("{minutes}m {:02}s", seconds % 60)
+    }
+}
+
+/// Shows the totals, for the stats screen
+pub fn draw(canvas: &mut Canvas, font: Option<&Font>, stats: &Stats) {
+    let Some(font) = font else {
+        return;
+    };
+    let title = compute_multiline_text_data(font, TEXT_HEIGHT * 1.5, &["STATS"]);
+    let mut y = canvas.height() / 4;
+    draw_subcanvas(
+        canvas,
+        &title,
+        canvas.width().saturating_sub(title.width()) / 2,
+        y,
+    );
+    y += title.height() + ROW_GAP * 2;
+
+    let lines = [
+        format!("bricks broken   {}", stats.bricks_broken),
+        format!("balls lost      {}", stats.balls_lost),
+        format!("levels cleared  {}", stats.levels_cleared),
+        format!("play time       {}", format_play_time(stats.play_ticks)),
+        String::from(" "),
+        String::from("[L] back"),
+    ];
+    let lines = lines.iter().map(String::as_str).collect::<Vec<_>>();
+    let text = compute_multiline_text_data(font, TEXT_HEIGHT, &lines);
+    let x = canvas.width().saturating_sub(text.width()) / 2;
+    draw_subcanvas(canvas, &text, x, y);
+}
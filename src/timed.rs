@@ -0,0 +1,88 @@
+/// Identifies one running timed effect, so it can be looked up or cancelled
+/// after others of the same kind have started
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EffectHandle(u32);
+
+struct Running<K> {
+    handle: EffectHandle,
+    kind: K,
+    remaining: u32,
+}
+
+/// Effects that last for a number of ticks. Anything with a duration
+/// (power-ups, invulnerability, temporary balls) starts an effect here
+/// rather than counting down a field of its own, and reacts to the effect
+/// expiring through [`TimedEffects::update`].
+pub struct TimedEffects<K> {
+    running: Vec<Running<K>>,
+    next_handle: u32,
+}
+
+impl<K> Default for TimedEffects<K> {
+    fn default() -> Self {
+        TimedEffects {
+            running: Vec::new(),
+            next_handle: 0,
+        }
+    }
+}
+
+impl<K: Copy + PartialEq> TimedEffects<K> {
+    pub fn start(&mut self, kind: K, duration: u32) -> EffectHandle {
+        let handle = EffectHandle(self.next_handle);
+        self.next_handle = self.next_handle.wrapping_add(1);
+        self.running.push(Running {
+            handle,
+            kind,
+            remaining: duration,
+        });
+        handle
+    }
+
+    /// Restarts the first running effect of this kind with a new duration,
+    /// or starts one if there is none, for effects that don't stack
+    pub fn refresh(&mut self, kind: K, duration: u32) -> EffectHandle {
+        match self.running.iter_mut().find(|effect| effect.kind == kind) {
+            Some(effect) => {
+                effect.remaining = duration;
+                effect.handle
+            }
+            None => self.start(kind, duration),
+        }
+    }
+
+    /// Ticks left on an effect, or `None` once it has expired
+    pub fn remaining(&self, handle: EffectHandle) -> Option<u32> {
+        self.running
+            .iter()
+            .find(|effect| effect.handle == handle)
+            .map(|effect| effect.remaining)
+    }
+
+    pub fn is_running(&self, kind: K) -> bool {
+        self.running.iter().any(|effect| effect.kind == kind)
+    }
+
+    /// Every running effect with its ticks left
+    pub fn iter(&self) -> impl Iterator<Item = (K, u32)> + '_ {
+        self.running
+            .iter()
+            .map(|effect| (effect.kind, effect.remaining))
+    }
+
+    /// Counts down every effect by one tick and returns the ones that just
+    /// expired
+    pub fn update(&mut self) -> Vec<(EffectHandle, K)> {
+        for effect in self.running.iter_mut() {
+            effect.remaining = effect.remaining.saturating_sub(1);
+        }
+        let (expired, running) = std::mem::take(&mut self.running)
+            .into_iter()
+            .partition::<Vec<_>, _>(|effect| effect.remaining == 0);
+        self.running = running;
+        expired
+            .into_iter()
+            .map(|effect| (effect.handle, effect.kind))
+            .collect()
+    }
+}
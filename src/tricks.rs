@@ -0,0 +1,98 @@
+//! Trick shots, spotted in the game events of a ball's flight. Each trick is
+//! a matcher over the events since the ball last left the paddle, checked
+//! whenever the ball breaks a brick.
+//!
+//! Events don't say which ball they came from, so tricks only count while a
+//! single ball is in play.
+
+use crate::{Cause, GameEvent};
+
+// More than this many events since the paddle and the oldest are dropped,
+// which only happens to balls that bounce around for a long time
+const MAX_HISTORY: usize = 256;
+
+pub struct Trick {
+    pub name: &'static str,
+    pub points: u32,
+    // Given the events up to and including the brick break, whether they
+    // make the trick
+    matches: fn(&[GameEvent]) -> bool,
+}
+
+pub const TRICKS: &[Trick] = &[
+    Trick {
+        name: "Bank shot",
+        points: 500,
+        matches: bank_shot,
+    },
+    Trick {
+        name: "Behind the wall",
+        points: 250,
+        matches: behind_the_wall,
+    },
+];
+
+// Wall bounces a bank shot takes between leaving the paddle or another
+// brick and breaking a brick
+const BANK_SHOT_BOUNCES: usize = 3;
+
+/// The ball came off three or more walls since it last hit the paddle or a
+/// brick
+fn bank_shot(history: &[GameEvent]) -> bool {
+    let Some(hit) = last_brick_hit(history) else {
+        return false;
+    };
+    history[..hit]
+        .iter()
+        .rev()
+        .take_while(|event| !matches!(event, GameEvent::PaddleHit | GameEvent::BallHitBrick { .. }))
+        .filter(|event| matches!(event, GameEvent::WallBounce))
+        .count()
+        >= BANK_SHOT_BOUNCES
+}
+
+/// The ball got above the bricks and broke one coming down
+fn behind_the_wall(history: &[GameEvent]) -> bool {
+    last_brick_hit(history)
+        .is_some_and(|hit| matches!(history[hit], GameEvent::BallHitBrick { from_above: true }))
+}
+
+fn last_brick_hit(history: &[GameEvent]) -> Option<usize> {
+    history
+        .iter()
+        .rposition(|event| matches!(event, GameEvent::BallHitBrick { .. }))
+}
+
+/// The events of the ball in play since it last hit the paddle
+#[derive(Default)]
+pub struct TrickDetector {
+    history: Vec<GameEvent>,
+}
+
+impl TrickDetector {
+    /// Adds a tick's events to the history, returning the tricks made
+    pub fn observe(&mut self, events: &[GameEvent]) -> Vec<&'static Trick> {
+        let mut made = Vec::new();
+        for &event in events {
+            if matches!(event, GameEvent::PaddleHit | GameEvent::BallMissed) {
+                self.history.clear();
+            }
+            self.history.push(event);
+            if matches!(
+                event,
+                GameEvent::BrickDestroyed(Cause::Ball | Cause::Fireball, ..)
+            ) {
+                made.extend(TRICKS.iter().filter(|trick| (trick.matches)(&self.history)));
+            }
+        }
+        if self.history.len() > MAX_HISTORY {
+            self.history.drain(..self.history.len() - MAX_HISTORY);
+        }
+        made
+    }
+
+    /// Forgets the history, for when it can't be told which ball did what
+    pub fn reset(&mut self) {
+        self.history.clear();
+    }
+}
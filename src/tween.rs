@@ -0,0 +1,131 @@
+//! Tweens move a value between two numbers over a fixed number of ticks.
+//!
+//! Visual effects that play out over time (shakes, squashes, slides) hold a
+//! [`Tween`] or a [`Sequence`] of them, advance it once per tick and read
+//! the current value when drawing, rather than counting ticks themselves.
+
+/// How a tween's progress is shaped between its start and end
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Easing {
+    Linear,
+    // Starts slowly and speeds up
+    EaseIn,
+    // Starts quickly and slows down
+    EaseOut,
+    EaseInOut,
+}
+
+impl Easing {
+    /// Maps linear progress in `0.0..=1.0` to eased progress
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t,
+            Easing::EaseOut => t * (2.0 - t),
+            Easing::EaseInOut if t < 0.5 => 2.0 * t * t,
+            Easing::EaseInOut => 1.0 - 2.0 * (1.0 - t) * (1.0 - t),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Tween {
+    from: f32,
+    to: f32,
+    // Ticks spent holding `from` before the tween starts moving
+    delay: u32,
+    duration: u32,
+    easing: Easing,
+    elapsed: u32,
+}
+
+impl Tween {
+    pub fn new(from: f32, to: f32, duration: u32) -> Self {
+        Tween {
+            from,
+            to,
+            delay: 0,
+            duration,
+            easing: Easing::Linear,
+            elapsed: 0,
+        }
+    }
+
+    pub fn with_easing(self, easing: Easing) -> Self {
+        Tween { easing, ..self }
+    }
+
+    pub fn with_delay(self, delay: u32) -> Self {
+        Tween { delay, ..self }
+    }
+
+    /// Plays `next` once this tween has finished
+    pub fn then(self, next: Tween) -> Sequence {
+        Sequence::from(self).then(next)
+    }
+
+    pub fn advance(&mut self) {
+        if !self.is_finished() {
+            self.elapsed += 1;
+        }
+    }
+
+    /// Ticks advanced so far, including the delay
+    pub fn elapsed(&self) -> u32 {
+        self.elapsed
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.elapsed >= self.delay + self.duration
+    }
+
+    pub fn value(&self) -> f32 {
+        let moving = self.elapsed.saturating_sub(self.delay);
+        let t = if self.duration == 0 {
+            1.0
+        } else {
+            moving as f32 / self.duration as f32
+        };
+        self.from + (self.to - self.from) * self.easing.apply(t)
+    }
+}
+
+/// Tweens played one after another
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sequence {
+    steps: Vec<Tween>,
+    current: usize,
+}
+
+impl From<Tween> for Sequence {
+    fn from(tween: Tween) -> Self {
+        Sequence {
+            steps: vec![tween],
+            current: 0,
+        }
+    }
+}
+
+impl Sequence {
+    pub fn then(mut self, next: Tween) -> Self {
+        self.steps.push(next);
+        self
+    }
+
+    pub fn advance(&mut self) {
+        let step = &mut self.steps[self.current];
+        step.advance();
+        if step.is_finished() && self.current + 1 < self.steps.len() {
+            self.current += 1;
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.current + 1 == self.steps.len() && self.steps[self.current].is_finished()
+    }
+
+    pub fn value(&self) -> f32 {
+        self.steps[self.current].value()
+    }
+}
@@ -0,0 +1,103 @@
+//! Coordinate types for the two spaces the game works in.
+//!
+//! Gameplay happens in world space, where the play field spans `-1.0..=1.0`
+//! on both axes with `y` pointing up. Drawing happens in screen space, in
+//! whole pixels with `y` pointing down from the top left of a canvas. The
+//! only way between the two is through a [`Viewport`], so world floats and
+//! pixel counts can't be mixed by accident.
+
+use std::ops::{Add, AddAssign, Mul, Sub};
+
+/// A point in world space
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct WorldPos {
+    pub x: f32,
+    pub y: f32,
+}
+
+/// A displacement, velocity or size in world units
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct WorldVec {
+    pub x: f32,
+    pub y: f32,
+}
+
+/// A pixel position on a canvas
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ScreenPos {
+    pub x: usize,
+    pub y: usize,
+}
+
+impl WorldPos {
+    pub const fn new(x: f32, y: f32) -> Self {
+        WorldPos { x, y }
+    }
+}
+
+impl WorldVec {
+    pub const fn new(x: f32, y: f32) -> Self {
+        WorldVec { x, y }
+    }
+}
+
+impl Add<WorldVec> for WorldPos {
+    type Output = WorldPos;
+
+    fn add(self, offset: WorldVec) -> WorldPos {
+        WorldPos::new(self.x + offset.x, self.y + offset.y)
+    }
+}
+
+impl AddAssign<WorldVec> for WorldPos {
+    fn add_assign(&mut self, offset: WorldVec) {
+        *self = *self + offset;
+    }
+}
+
+impl Sub for WorldPos {
+    type Output = WorldVec;
+
+    fn sub(self, other: WorldPos) -> WorldVec {
+        WorldVec::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl Mul<f32> for WorldVec {
+    type Output = WorldVec;
+
+    fn mul(self, factor: f32) -> WorldVec {
+        WorldVec::new(self.x * factor, self.y * factor)
+    }
+}
+
+/// Maps the world onto a `width` by `height` pixel area starting `top`
+/// rows down the screen
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Viewport {
+    pub width: usize,
+    pub height: usize,
+    pub top: usize,
+}
+
+impl Viewport {
+    pub fn to_screen(self, pos: WorldPos) -> ScreenPos {
+        let x = (self.width as f32 * (1.0 + pos.x) / 2.0).ceil() as usize;
+        let y = self.height - (self.height as f32 * (1.0 + pos.y) / 2.0).ceil() as usize;
+        ScreenPos { x, y: y + self.top }
+    }
+
+    /// World-space size of a shape `width` world units wide that appears
+    /// round in this viewport
+    pub fn round_size(self, width: f32) -> WorldVec {
+        WorldVec::new(width, width * self.width as f32 / self.height as f32)
+    }
+
+    /// Pixel extent of a world-space size, rounded up so that nothing with
+    /// a nonzero size disappears
+    pub fn to_pixels(self, size: WorldVec) -> (usize, usize) {
+        let width = (size.x / 2.0 * self.width as f32).ceil() as usize;
+        let height = (size.y / 2.0 * self.height as f32).ceil() as usize;
+        (width, height)
+    }
+}
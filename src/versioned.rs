@@ -0,0 +1,88 @@
+//! Version stamps for the files the game saves.
+//!
+//! Every saved file starts with a `version <n>` line. When a format changes,
+//! its version goes up and a migration is added that rewrites text in the
+//! old format into the new one, so files saved by older builds still load.
+//! Files from before versions were added count as version 0.
+
+use std::io::{Error, ErrorKind};
+use std::path::{Path, PathBuf};
+
+/// Rewrites the text of a file in one version of a format into the next
+pub type Migration = fn(String) -> Result<String, String>;
+
+pub struct Format {
+    pub name: &'static str,
+    // `migrations[n]` upgrades version `n` to version `n + 1`, so the
+    // current version is the number of migrations
+    pub migrations: &'static [Migration],
+}
+
+impl Format {
+    fn version(&self) -> usize {
+        self.migrations.len()
+    }
+}
+
+/// The first versioned formats kept the unversioned layout unchanged
+pub fn add_version(text: String) -> Result<String, String> {
+    Ok(text)
+}
+
+/// Reads a file and upgrades it to the current version of its format,
+/// returning the text without the version line
+pub fn read(path: &Path, format: &Format) -> std::io::Result<String> {
+    let text = std::fs::read_to_string(path)?;
+    let (first, rest) = text.split_once('\n').unwrap_or((&text, ""));
+    let (version, mut text) = match first.strip_prefix("version ") {
+        Some(version) => {
+            let version = version.trim().parse::<usize>().map_err(|err| {
+                Error::new(ErrorKind::InvalidData, format!("invalid version: {err}"))
+            })?;
+            (version, String::from(rest))
+        }
+        None => (0, text),
+    };
+
+    if version > format.version() {
+        // Saving over the file would lose whatever the newer build wrote,
+        // so keep a copy of it
+        let backup = backup_path(path, version);
+        let copied = match std::fs::copy(path, &backup) {
+            Ok(_) => format!("kept a copy at {backup:?}"),
+            Err(err) => format!("could not keep a copy at {backup:?}: {err}"),
+        };
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!(
+                "{} version {version} is newer than this build supports ({}), {copied}",
+                format.name,
+                format.version()
+            ),
+        ));
+    }
+
+    for (from, migrate) in format.migrations.iter().enumerate().skip(version) {
+        text = migrate(text).map_err(|err| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "could not upgrade {} from version {from}: {err}",
+                    format.name
+                ),
+            )
+        })?;
+    }
+    Ok(text)
+}
+
+/// Writes `text` after a line stamping it with the current version
+pub fn write(path: &Path, format: &Format, text: &str) -> std::io::Result<()> {
+    std::fs::write(path, format!("version {}\n{text}", format.version()))
+}
+
+fn backup_path(path: &Path, version: usize) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".v{version}"));
+    path.with_file_name(name)
+}